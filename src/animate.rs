@@ -0,0 +1,173 @@
+use std::env;
+use advent_of_code_2021::day_11::{flash_octopi, octopi_from_input, simple_energy_increase, OctopusGrid};
+use advent_of_code_2021::day_13::{count_dots, fold_grid, parse_grid, Fold};
+use advent_of_code_2021::{Grid, ParseError};
+#[cfg(test)]
+use advent_of_code_2021::read_lines;
+
+/// The days with steps to animate
+pub(crate) const DAYS: [usize; 2] = [11, 13];
+
+/// Clears the terminal and moves the cursor to the top left, to draw the next frame over the last
+pub(crate) const CLEAR: &str = "\x1b[2J\x1b[H";
+
+const RESET: &str = "\x1b[0m";
+const HIGHLIGHT: &str = "\x1b[1;93m";
+const DIM: &str = "\x1b[38;5;238m";
+
+/// Gives up on the octopi all flashing at once after this many steps, like part two does
+const MAX_STEPS: usize = 500;
+
+/// The number of columns and lines of the terminal, as far as the shell lets on
+pub(crate) fn terminal_size() -> (usize, usize) {
+    let size = |name, default| env::var(name).ok().and_then(|size| size.parse().ok()).unwrap_or(default);
+    (size("COLUMNS", 80), size("LINES", 24))
+}
+
+/// Draws a frame for the starting state and each step of one of the days in `DAYS`, each frame
+/// fitting in a terminal of the given size
+pub(crate) fn frames(day: usize, inputs: &[String], (columns, lines): (usize, usize)) -> Result<Vec<String>, ParseError> {
+    match day {
+        11 => octopus_frames(inputs),
+        13 => paper_frames(inputs, columns, lines.saturating_sub(2)),
+        _ => panic!("There's no animation for day {}", day),
+    }
+}
+
+/// Steps the octopi until they all flash at once, highlighting the ones that flashed
+fn octopus_frames(inputs: &[String]) -> Result<Vec<String>, ParseError> {
+    let mut octopi = octopi_from_input(inputs)?;
+    let octopi_count = octopi.width() * octopi.height();
+    let mut frames = vec![format!("Step 0\n{}", draw_octopi(&octopi, false))];
+    let mut total_flashes = 0;
+
+    for step in 1..=MAX_STEPS {
+        simple_energy_increase(&mut octopi);
+        let flashes = flash_octopi(&mut octopi);
+        total_flashes += flashes;
+
+        frames.push(format!(
+            "Step {}: {} flashed, {} in total\n{}", step, flashes, total_flashes, draw_octopi(&octopi, true)
+        ));
+        if flashes == octopi_count {
+            break;
+        }
+    }
+
+    Ok(frames)
+}
+
+fn draw_octopi(octopi: &OctopusGrid, highlight_flashed: bool) -> String {
+    draw(octopi, |&energy| match energy {
+        0 if highlight_flashed => format!("{}0{}", HIGHLIGHT, RESET),
+        // Brighter shades of grey as they build up energy
+        _ => format!("\x1b[38;5;{}m{}{}", 236 + energy.min(9) * 2, energy, RESET),
+    })
+}
+
+/// Makes each fold, highlighting the dots that landed somewhere new
+fn paper_frames(inputs: &[String], columns: usize, lines: usize) -> Result<Vec<String>, ParseError> {
+    let (mut paper, folds) = parse_grid(inputs)?;
+    let mut width = paper.width();
+    let mut height = paper.height();
+    let mut frames = vec![format!(
+        "Before folding: {} dots\n{}", count_dots(&paper), draw_paper(&paper, &paper, (width, height), (columns, lines))
+    )];
+
+    for (fold_idx, fold) in folds.iter().enumerate() {
+        let before = paper.clone();
+        fold_grid(&mut paper, fold);
+
+        let along = match fold {
+            Fold::Left(x) => {
+                width = width.min(*x);
+                format!("x={}", x)
+            }
+            Fold::Up(y) => {
+                height = height.min(*y);
+                format!("y={}", y)
+            }
+        };
+        frames.push(format!(
+            "Fold {} along {}: {} dots\n{}",
+            fold_idx + 1, along, count_dots(&paper), draw_paper(&paper, &before, (width, height), (columns, lines))
+        ));
+    }
+
+    Ok(frames)
+}
+
+/// Draws the part of the paper that's left, shrunk to fit the terminal by drawing a block of the
+/// paper as a single dot when any of it has one
+fn draw_paper(paper: &Grid<bool>, before: &Grid<bool>, (width, height): (usize, usize), (columns, lines): (usize, usize)) -> String {
+    let block = width.div_ceil(columns.max(1)).max(height.div_ceil(lines.max(1))).max(1);
+
+    let shrunk = Grid::from_fn(width.div_ceil(block), height.div_ceil(block), |(row, col)| {
+        let cells = paper.view(row * block, col * block, block.min(height - row * block), block.min(width - col * block));
+        let is_dot = cells.iter().any(|(_, &dot)| dot);
+        let has_moved = cells.iter().any(|((cell_row, cell_col), &dot)| {
+            dot && !before[(row * block + cell_row, col * block + cell_col)]
+        });
+        (is_dot, has_moved)
+    });
+
+    draw(&shrunk, |&(is_dot, has_moved)| match (is_dot, has_moved) {
+        (true, true) => format!("{}#{}", HIGHLIGHT, RESET),
+        (true, false) => "#".to_string(),
+        (false, _) => format!("{}.{}", DIM, RESET),
+    })
+}
+
+fn draw<T>(grid: &Grid<T>, mut cell: impl FnMut(&T) -> String) -> String {
+    grid.rows()
+        .map(|row| row.iter().map(&mut cell).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+fn strip_colors(frame: &str) -> String {
+    let mut stripped = String::new();
+    let mut in_escape = false;
+
+    for c in frame.chars() {
+        match (in_escape, c) {
+            (false, '\x1b') => in_escape = true,
+            (false, _) => stripped.push(c),
+            (true, 'm') => in_escape = false,
+            (true, _) => {}
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+#[test]
+fn test_octopus_frames() {
+    let inputs = read_lines("data/day_11_sample.txt").unwrap();
+    let frames = frames(11, &inputs, (80, 24)).unwrap();
+
+    // The sample all flash at once in step 195
+    assert_eq!(frames.len(), 196);
+    assert!(strip_colors(&frames[0]).starts_with("Step 0\n5483143223\n"));
+    assert!(strip_colors(&frames[2]).starts_with("Step 2: 35 flashed, 35 in total\n8807476555\n"));
+    assert!(frames[2].starts_with(&format!("Step 2: 35 flashed, 35 in total\n\x1b[38;5;252m8{}\x1b[38;5;252m8{}{}0{}", RESET, RESET, HIGHLIGHT, RESET)));
+}
+
+#[test]
+fn test_paper_frames() {
+    let inputs = read_lines("data/day_13_sample.txt").unwrap();
+    let frames = frames(13, &inputs, (80, 24)).unwrap();
+
+    assert_eq!(frames.len(), 3);
+    assert_eq!(strip_colors(&frames[2]), "Fold 2 along x=5: 16 dots\n#####\n#...#\n#...#\n#...#\n#####\n.....\n.....");
+    assert!(strip_colors(&frames[1]).starts_with("Fold 1 along y=7: 17 dots\n#.##..#..#.\n#...#......\n"));
+
+    // The dots at x=0 and x=2 of the top row got mirrored there from the bottom, the one at x=3
+    // was there all along
+    assert!(frames[1].contains(&format!("dots\n{}#{}{}.{}{}#{}#", HIGHLIGHT, RESET, DIM, RESET, HIGHLIGHT, RESET)));
+
+    // Shrunk to fit in 6 columns, with each character standing for 2 by 2 cells
+    let frames = paper_frames(&inputs, 6, 8).unwrap();
+    assert_eq!(strip_colors(&frames[0]).lines().take(3).collect::<Vec<_>>(), vec!["Before folding: 18 dots", ".####.", "#....."]);
+}
@@ -0,0 +1,97 @@
+use std::time::Duration;
+use advent_of_code_2021::{ParseError, Part, Solver};
+#[cfg(test)]
+use advent_of_code_2021::read_lines;
+#[cfg(test)]
+use advent_of_code_2021::registry::find_solver;
+
+/// The spread of the run times of a benchmark
+#[derive(Debug, PartialEq)]
+pub(crate) struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub(crate) fn from_durations(durations: &[Duration]) -> Stats {
+        let mut sorted = durations.to_vec();
+        sorted.sort();
+
+        let middle = sorted.len() / 2;
+        let median = match sorted.len() % 2 {
+            0 => (sorted[middle - 1] + sorted[middle]) / 2,
+            _ => sorted[middle],
+        };
+
+        Stats { min: sorted[0], median, max: sorted[sorted.len() - 1] }
+    }
+}
+
+/// Parses and solves a part of a day `runs` times, returning the statistics for the parse and
+/// solve times separately
+pub(crate) fn bench(
+    solver: &dyn Solver,
+    inputs: &[String],
+    part: Part,
+    runs: usize
+) -> Result<(Stats, Stats), ParseError> {
+    let mut parse_times = Vec::new();
+    let mut solve_times = Vec::new();
+
+    for _ in 0..runs {
+        let (parse_time, solve_time) = solver.time_part(inputs, part)?;
+        parse_times.push(parse_time);
+        solve_times.push(solve_time);
+    }
+
+    Ok((Stats::from_durations(&parse_times), Stats::from_durations(&solve_times)))
+}
+
+pub(crate) fn report(day: usize, part: Part, runs: usize, parse: &Stats, solve: &Stats) -> String {
+    let mut lines = vec![
+        format!("Benchmarked day {} part {} over {} runs", day, part, runs),
+        format!("{:<8}{:>14}{:>14}{:>14}", "", "min", "median", "max"),
+    ];
+
+    for (name, stats) in [("parse", parse), ("solve", solve)] {
+        lines.push(format!(
+            "{:<8}{:>14}{:>14}{:>14}",
+            name,
+            format!("{:.2?}", stats.min),
+            format!("{:.2?}", stats.median),
+            format!("{:.2?}", stats.max)
+        ));
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+#[test]
+fn test_stats() {
+    let durations = vec![
+        Duration::from_millis(30),
+        Duration::from_millis(10),
+        Duration::from_millis(20),
+    ];
+    let stats = Stats::from_durations(&durations);
+    assert_eq!(stats, Stats {
+        min: Duration::from_millis(10),
+        median: Duration::from_millis(20),
+        max: Duration::from_millis(30),
+    });
+
+    let stats = Stats::from_durations(&durations[..2]);
+    assert_eq!(stats.median, Duration::from_millis(20));
+}
+
+#[test]
+fn test_bench_sample() {
+    let inputs = read_lines("data/day_6_sample.txt").unwrap();
+    let solver = find_solver(6).unwrap();
+    let (parse, solve) = bench(solver.as_ref(), &inputs, Part::Two, 3).unwrap();
+
+    assert!(parse.min <= parse.median && parse.median <= parse.max);
+    assert!(solve.min <= solve.median && solve.median <= solve.max);
+}
@@ -0,0 +1,232 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+use advent_of_code_2021::{Answer, ParseError, Part, Solved, Solver, Value};
+#[cfg(test)]
+use advent_of_code_2021::{read_lines, registry};
+
+/// Answers stored on disk by day, part, version of the solution and hash of the puzzle input, so
+/// solving the same input again doesn't take any time. They're kept in an `answers` directory
+/// of their own, so clearing the cache can't touch anything else in the directory it was given.
+/// Parsed inputs aren't cached, as a cached answer already skips parsing along with solving.
+pub(crate) struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub(crate) fn new(dir: impl AsRef<Path>) -> Self {
+        Cache { dir: dir.as_ref().join("answers") }
+    }
+
+    fn entry_path(&self, solver: &dyn Solver, part: Part, inputs: &[String]) -> PathBuf {
+        let mut hasher = Sha256::new();
+        for line in inputs {
+            hasher.update(line.as_bytes());
+            hasher.update(b"\n");
+        }
+
+        let hash = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+
+        self.dir.join(format!("day_{}_part_{}_v{}_{}.toml", solver.day(), part, solver.version(), hash))
+    }
+
+    /// The cached answer to a part, if there is one, with how long it took to solve the first time
+    pub(crate) fn get(&self, solver: &dyn Solver, part: Part, inputs: &[String]) -> Option<Solved> {
+        let path = self.entry_path(solver, part, inputs);
+        let text = fs::read_to_string(&path).ok()?;
+
+        match parse_entry(&text) {
+            Ok((answer, elapsed)) => Some(Solved { day: solver.day(), part, answer, elapsed }),
+            Err(e) => {
+                warn!("Ignoring the cached answer in {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn put(&self, solver: &dyn Solver, inputs: &[String], solved: &Solved) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.entry_path(solver, solved.part, inputs), entry(&solved.answer, solved.elapsed))
+    }
+
+    /// Solves the parts that aren't cached yet and caches their answers, returning the answers to
+    /// all parts in the order they were asked for
+    pub(crate) fn solve(&self, solver: &dyn Solver, inputs: &[String], parts: &[Part]) -> Result<Vec<Solved>, ParseError> {
+        let cached = parts
+            .iter()
+            .filter_map(|&part| self.get(solver, part, inputs))
+            .collect::<Vec<_>>();
+        let uncached = parts
+            .iter()
+            .filter(|part| !cached.iter().any(|solved| solved.part == **part))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for solved in &cached {
+            info!("Day {} part {} is answered from the cache", solved.day, solved.part);
+        }
+
+        let solved = match uncached.is_empty() {
+            true => Vec::new(),
+            false => solver.solve(inputs, &uncached)?,
+        };
+        for solved in &solved {
+            if let Err(e) = self.put(solver, inputs, solved) {
+                warn!("Can't cache the answer to day {} part {}: {}", solved.day, solved.part, e);
+            }
+        }
+
+        let mut all = [cached, solved].concat();
+        all.sort_by_key(|solved| parts.iter().position(|part| *part == solved.part));
+        Ok(all)
+    }
+
+    /// Removes all cached answers, returning how many there were. Only files named like an entry
+    /// get removed, and the directory of answers too once that leaves it empty.
+    pub(crate) fn clear(&self) -> io::Result<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+
+        let mut removed = 0;
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_name().to_str().is_some_and(is_entry_name) && entry.file_type()?.is_file() {
+                fs::remove_file(entry.path())?;
+                removed += 1;
+            }
+        }
+
+        if fs::read_dir(&self.dir)?.next().is_none() {
+            fs::remove_dir(&self.dir)?;
+        }
+
+        Ok(removed)
+    }
+}
+
+/// Whether a file name is one `entry_path` would make: `day_{d}_part_{p}_v{v}_{sha256}.toml`
+fn is_entry_name(name: &str) -> bool {
+    let is_number = |text: &str| !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+    let fields = match name.strip_suffix(".toml") {
+        Some(stem) => stem.split('_').collect::<Vec<_>>(),
+        None => return false,
+    };
+
+    match fields.as_slice() {
+        ["day", day, "part", part, version, hash] => {
+            is_number(day)
+                && (*part == "1" || *part == "2")
+                && version.strip_prefix('v').is_some_and(is_number)
+                && hash.len() == 64
+                && hash.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+        }
+        _ => false,
+    }
+}
+
+fn entry(answer: &Answer, elapsed: Duration) -> String {
+    let mut table = toml::Table::new();
+    let value = match &answer.value {
+        Value::Number(number) => toml::Value::Integer(*number),
+        Value::Text(text) => toml::Value::String(text.clone()),
+    };
+    table.insert("answer".to_string(), value);
+    table.insert("report".to_string(), toml::Value::String(answer.report.clone()));
+    table.insert("elapsed".to_string(), toml::Value::Float(elapsed.as_secs_f64()));
+
+    table.to_string()
+}
+
+fn parse_entry(text: &str) -> Result<(Answer, Duration), String> {
+    let table = text.parse::<toml::Table>().map_err(|e| e.to_string())?;
+    let report = table.get("report").and_then(|report| report.as_str()).ok_or("expected a report")?;
+    let elapsed = table
+        .get("elapsed")
+        .and_then(|elapsed| elapsed.as_float())
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or("expected the seconds it took to solve as elapsed")?;
+
+    let answer = match table.get("answer") {
+        Some(toml::Value::Integer(number)) => Answer::new(Value::Number(*number), report),
+        Some(toml::Value::String(text)) => Answer::new(Value::Text(text.clone()), report),
+        _ => return Err("expected an answer that's an integer or a string".to_string()),
+    };
+
+    Ok((answer, elapsed))
+}
+
+#[cfg(test)]
+#[test]
+fn test_entry() {
+    let answer = Answer::new(Value::Text("#.#\n\"#\"".to_string()), "The code is:\n#.#");
+    let elapsed = Duration::from_micros(1234);
+    assert_eq!(parse_entry(&entry(&answer, elapsed)), Ok((answer, elapsed)));
+
+    let answer = Answer::new(Value::Number(-7), "Seven below");
+    assert_eq!(parse_entry(&entry(&answer, Duration::ZERO)), Ok((answer, Duration::ZERO)));
+
+    assert_eq!(
+        parse_entry("answer = 1.5\nreport = \"\"\nelapsed = 0.5"),
+        Err("expected an answer that's an integer or a string".to_string())
+    );
+    assert_eq!(parse_entry("answer = 1\nreport = \"\""), Err("expected the seconds it took to solve as elapsed".to_string()));
+    assert_eq!(parse_entry("answer = 1\nreport = \"\"\nelapsed = -1.0"), Err("expected the seconds it took to solve as elapsed".to_string()));
+}
+
+#[test]
+fn test_is_entry_name() {
+    let hash = "0123456789abcdef".repeat(4);
+    assert!(is_entry_name(&format!("day_1_part_2_v1_{}.toml", hash)));
+    assert!(is_entry_name(&format!("day_25_part_1_v12_{}.toml", hash)));
+
+    assert!(!is_entry_name("Cargo.toml"));
+    assert!(!is_entry_name("aoc.toml"));
+    assert!(!is_entry_name(&format!("day_1_part_3_v1_{}.toml", hash)));
+    assert!(!is_entry_name(&format!("day_1_part_2_1_{}.toml", hash)));
+    assert!(!is_entry_name(&format!("day_1_part_2_v1_{}.toml", &hash[1..])));
+    assert!(!is_entry_name(&format!("day_1_part_2_v1_{}.toml", hash.to_uppercase())));
+    assert!(!is_entry_name(&format!("day_1_part_2_v1_{}.txt", hash)));
+}
+
+#[test]
+fn test_cache() {
+    let dir = std::env::temp_dir().join(format!("advent_of_code_2021_cache_{}", std::process::id()));
+    let cache = Cache::new(&dir);
+    let solver = registry::find_solver(1).unwrap();
+    let inputs = read_lines("data/day_1_sample.txt").unwrap();
+
+    assert_eq!(cache.get(solver.as_ref(), Part::Two, &inputs), None);
+
+    let solved = cache.solve(solver.as_ref(), &inputs, &[Part::Two]).unwrap();
+    assert_eq!(solved[0].answer.value, Value::Number(5));
+    let cached = cache.get(solver.as_ref(), Part::Two, &inputs).unwrap();
+    assert_eq!((cached.answer, cached.elapsed), (solved[0].answer.clone(), solved[0].elapsed));
+
+    // A different input doesn't get the cached answer
+    assert_eq!(cache.get(solver.as_ref(), Part::Two, &inputs[1..]), None);
+
+    // Only part one still needs solving, but both come back in order
+    let solved = cache.solve(solver.as_ref(), &inputs, &[Part::One, Part::Two]).unwrap();
+    assert_eq!(solved.iter().map(|solved| solved.part).collect::<Vec<_>>(), vec![Part::One, Part::Two]);
+    assert_eq!(solved[1].elapsed, cached.elapsed);
+
+    // Clearing leaves other files alone, also those in the directory of answers
+    fs::write(dir.join("aoc.toml"), "").unwrap();
+    fs::write(dir.join("answers").join("notes.toml"), "").unwrap();
+    assert_eq!(cache.clear().unwrap(), 2);
+    assert_eq!(cache.get(solver.as_ref(), Part::Two, &inputs), None);
+    assert!(dir.join("aoc.toml").exists() && dir.join("answers").join("notes.toml").exists());
+
+    fs::remove_file(dir.join("answers").join("notes.toml")).unwrap();
+    assert_eq!(cache.clear().unwrap(), 0);
+    assert!(!dir.join("answers").exists());
+    assert_eq!(cache.clear().unwrap(), 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
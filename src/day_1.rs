@@ -1,21 +1,38 @@
-use advent_of_code_2021::read_lines;
+use log::debug;
+use crate::{parse_number, Answer, ParseError, Solution};
+#[cfg(test)]
+use crate::read_lines;
 
-pub(crate) fn run () {
-    let inputs = read_lines("data/day_1_input.txt");
+pub struct Day1;
 
-    let measurements: Vec<i32> = inputs
-        .iter()
-        .map(|m| m.parse::<i32>().unwrap())
-        .collect();
+impl Solution for Day1 {
+    const DAY: usize = 1;
+    type Parsed = Vec<i32>;
 
-    let mut increases = window_increases(measurements.clone(), 1);
-    println!("There are {} increases in depth for part 1", increases);
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        parse_measurements(inputs)
+    }
 
-    increases = window_increases(measurements, 3);
-    println!("There are {} increases in depth for part 2", increases)
+    fn part_one(&self, measurements: &Self::Parsed) -> Result<Answer, ParseError> {
+        let increases = window_increases(measurements, 1);
+        Ok(Answer::new(increases, format!("There are {} increases in depth for part 1", increases)))
+    }
+
+    fn part_two(&self, measurements: &Self::Parsed) -> Result<Answer, ParseError> {
+        let increases = window_increases(measurements, 3);
+        Ok(Answer::new(increases, format!("There are {} increases in depth for part 2", increases)))
+    }
 }
 
-pub(crate) fn window_increases(measurements: Vec<i32>, window_size: usize) -> usize {
+pub fn parse_measurements(inputs: &[String]) -> Result<Vec<i32>, ParseError> {
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(line_idx, m)| parse_number(m, line_idx, 0))
+        .collect()
+}
+
+pub fn window_increases(measurements: &[i32], window_size: usize) -> usize {
     let mut increases = 0;
     let mut previous_window_sum = -1;
 
@@ -23,7 +40,7 @@ pub(crate) fn window_increases(measurements: Vec<i32>, window_size: usize) -> us
         let window_sum = window_slice.iter().sum();
 
         if previous_window_sum == -1 {
-            println!("Skipping first measurement window: no window diff yet");
+            debug!("Skipping first measurement window: no window diff yet");
             previous_window_sum = window_sum;
         };
 
@@ -39,14 +56,17 @@ pub(crate) fn window_increases(measurements: Vec<i32>, window_size: usize) -> us
 #[cfg(test)]
 #[test]
 fn test_sliding_triplet_window() {
-    let measurements: Vec<_> = read_lines("data/day_1_sample.txt")
-        .iter()
-        .map(|m| m.parse::<i32>().unwrap())
-        .collect();
+    let measurements = parse_measurements(&read_lines("data/day_1_sample.txt").unwrap()).unwrap();
 
-    let increases = window_increases(measurements.clone(), 1);
+    let increases = window_increases(&measurements, 1);
     assert_eq!(increases, 7);
 
-    let increases = window_increases(measurements, 3);
+    let increases = window_increases(&measurements, 3);
     assert_eq!(increases, 5)
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_measurements() {
+    let inputs = vec!["199".to_string(), "".to_string()];
+    let error = parse_measurements(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 2, column 1: expected a number, found nothing");
+}
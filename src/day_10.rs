@@ -1,37 +1,57 @@
 use std::collections::HashMap;
-use advent_of_code_2021::read_lines;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use crate::{Answer, ParseError, Solution};
+#[cfg(test)]
+use crate::{read_lines, Value};
 use crate::day_10::Syntactical::{Correct, Incomplete, Incorrect};
 
-pub(crate) fn run() {
-    let inputs = read_lines("data/day_10_input.txt");
-    let token_lines = read_tokens(&inputs);
-    let incorrect_score = score_from_incorrect_tokens(&token_lines);
-    println!("Total score for incorrect tokens: {}", incorrect_score);
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: usize = 10;
+    type Parsed = Vec<Vec<String>>;
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        read_tokens(inputs)
+    }
 
-    let incomplete_score = score_from_incomplete_lines(&token_lines);
-    println!("Score for incomplete autocomplete: {}", incomplete_score);
+    fn part_one(&self, token_lines: &Self::Parsed) -> Result<Answer, ParseError> {
+        let incorrect_score = score_from_incorrect_tokens(token_lines);
+        Ok(Answer::new(incorrect_score, format!("Total score for incorrect tokens: {}", incorrect_score)))
+    }
+
+    fn part_two(&self, token_lines: &Self::Parsed) -> Result<Answer, ParseError> {
+        let incomplete_score = score_from_incomplete_lines(token_lines)?;
+        Ok(Answer::new(incomplete_score, format!("Score for incomplete autocomplete: {}", incomplete_score)))
+    }
 }
 
-pub(crate) fn read_tokens(inputs: &Vec<String>) -> Vec<Vec<String>> {
+pub fn read_tokens(inputs: &[String]) -> Result<Vec<Vec<String>>, ParseError> {
     inputs
         .iter()
-        .map(|line| {
+        .enumerate()
+        .map(|(line_idx, line)| {
             line
                 .chars()
-                .map(|c| c.to_string())
+                .enumerate()
+                .map(|(column, c)| match c {
+                    '(' | ')' | '[' | ']' | '{' | '}' | '<' | '>' => Ok(c.to_string()),
+                    _ => Err(ParseError::at(line_idx, column, format!("expected a bracket, found `{}`", c))),
+                })
                 .collect()
         })
         .collect()
 }
 
 #[derive(PartialEq, Debug)]
-pub(crate) enum Syntactical {
+pub enum Syntactical {
     Incomplete(String),
     Incorrect(String),
     Correct
 }
 
-pub(crate) fn syntax_check(line: &Vec<String>) -> Syntactical {
+pub fn syntax_check(line: &[String]) -> Syntactical {
     let closing_tokens = "}])>"
         .chars()
         .map(|c| c.to_string())
@@ -66,16 +86,16 @@ pub(crate) fn syntax_check(line: &Vec<String>) -> Syntactical {
         }
     }
 
-    if remaining.len() == 0 {
-        return Correct
+    if remaining.is_empty() {
+        Correct
     } else if let Some(closing_char) = first_remaining_closing_chars {
-        return Incorrect(closing_char)
+        Incorrect(closing_char)
     } else {
-        return Incomplete(remaining)
+        Incomplete(remaining)
     }
 }
 
-pub(crate) fn score_from_incorrect_tokens(token_lines: &Vec<Vec<String>>) -> usize {
+pub fn score_from_incorrect_tokens(token_lines: &[Vec<String>]) -> usize {
     let token_scores: HashMap<String, usize> = HashMap::from([
         (")".to_string(), 3),
         ("]".to_string(), 57),
@@ -83,18 +103,22 @@ pub(crate) fn score_from_incorrect_tokens(token_lines: &Vec<Vec<String>>) -> usi
         (">".to_string(), 25137),
     ]);
 
-    let mut score = 0;
+    // Lines get checked on their own, so they can all be checked at the same time
+    #[cfg(feature = "parallel")]
+    let lines = token_lines.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let lines = token_lines.iter();
 
-    for line in token_lines {
-        if let Incorrect(offending_token) = syntax_check(&line) {
-            score += token_scores[&offending_token];
-        }
-    }
-
-    score
+    lines
+        .map(|line| match syntax_check(line) {
+            Incorrect(offending_token) => token_scores[&offending_token],
+            _ => 0,
+        })
+        .sum()
 }
 
-pub(crate) fn score_from_incomplete_lines(token_lines: &Vec<Vec<String>>) -> usize {
+/// The middle score of the autocompleted incomplete lines, which needs an odd number of them
+pub fn score_from_incomplete_lines(token_lines: &[Vec<String>]) -> Result<usize, ParseError> {
     let token_scores = HashMap::from([
         (")".to_string(), 1),
         ("]".to_string(), 2),
@@ -102,30 +126,35 @@ pub(crate) fn score_from_incomplete_lines(token_lines: &Vec<Vec<String>>) -> usi
         (">".to_string(), 4),
     ]);
 
-    let mut scores = Vec::new();
-
-    for line in token_lines {
-        let mut score = 0;
-
-        if let Incomplete(tokens) = syntax_check(&line)  {
-            let completion_tokens = autocomplete(&tokens);
-
-            for token in completion_tokens {
-                score = score * 5 + token_scores[&token];
-            }
+    #[cfg(feature = "parallel")]
+    let lines = token_lines.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let lines = token_lines.iter();
+
+    let mut scores = lines
+        .enumerate()
+        .filter_map(|(line_idx, line)| match syntax_check(line) {
+            Incomplete(tokens) => Some(
+                autocomplete(&tokens)
+                    .iter()
+                    .try_fold(0_usize, |score, token| score.checked_mul(5)?.checked_add(token_scores[token]))
+                    .ok_or_else(|| ParseError::at(line_idx, 0, "the autocompletion score doesn't fit in 64 bits"))
+            ),
+            _ => None,
+        })
+        .collect::<Result<Vec<usize>, ParseError>>()?;
 
-            scores.push(score);
-        }
+    if scores.len() % 2 == 0 {
+        return Err(ParseError::input(format!(
+            "expected an odd number of incomplete lines, to have a middle score, found {}", scores.len()
+        )));
     }
 
-
     scores.sort();
-    let middle_idx = scores.len() / 2;  // Add one for correcting usize
-
-    scores[middle_idx]
+    Ok(scores[scores.len() / 2])
 }
 
-pub(crate) fn autocomplete(line: &String) -> Vec<String> {
+pub fn autocomplete(line: &str) -> Vec<String> {
     let closing_map: HashMap<String, String> = HashMap::from([
         ("[".to_string(), "]".to_string()),
         ("{".to_string(), "}".to_string()),
@@ -146,15 +175,15 @@ pub(crate) fn autocomplete(line: &String) -> Vec<String> {
 #[cfg(test)]
 #[test]
 fn test_read_input() {
-    let inputs = read_lines("data/day_10_sample.txt");
-    let token_lines = read_tokens(&inputs);
+    let inputs = read_lines("data/day_10_sample.txt").unwrap();
+    let token_lines = read_tokens(&inputs).unwrap();
     assert_eq!(token_lines[0].len(), 24)
 }
 
 #[test]
 fn test_syntax_check() {
-    let inputs = read_lines("data/day_10_sample.txt");
-    let token_lines = read_tokens(&inputs);
+    let inputs = read_lines("data/day_10_sample.txt").unwrap();
+    let token_lines = read_tokens(&inputs).unwrap();
 
     let first_line = token_lines[0].clone();
     assert_eq!(syntax_check(&first_line), Incomplete("[({([[{{".to_string()));
@@ -165,8 +194,8 @@ fn test_syntax_check() {
 
 #[test]
 fn test_score_incorrect_tokens() {
-    let inputs = read_lines("data/day_10_sample.txt");
-    let token_lines = read_tokens(&inputs);
+    let inputs = read_lines("data/day_10_sample.txt").unwrap();
+    let token_lines = read_tokens(&inputs).unwrap();
 
     let score = score_from_incorrect_tokens(&token_lines);
     assert_eq!(score, 26397);
@@ -174,8 +203,8 @@ fn test_score_incorrect_tokens() {
 
 #[test]
 fn test_score_incomplete_lines() {
-    let inputs = read_lines("data/day_10_sample.txt");
-    let token_lines = read_tokens(&inputs);
+    let inputs = read_lines("data/day_10_sample.txt").unwrap();
+    let token_lines = read_tokens(&inputs).unwrap();
 
     let first_line = token_lines[0].clone();
     if let Incomplete(opening_chars) = syntax_check(&first_line) {
@@ -190,5 +219,26 @@ fn test_score_incomplete_lines() {
     };
 
     let score = score_from_incomplete_lines(&token_lines);
-    assert_eq!(score, 288957);
-}
\ No newline at end of file
+    assert_eq!(score, Ok(288957));
+
+    // Without the last incomplete line there's no middle score, but part one still answers
+    let error = score_from_incomplete_lines(&token_lines[..9]).unwrap_err();
+    assert_eq!(error.to_string(), "expected an odd number of incomplete lines, to have a middle score, found 4");
+    let token_lines = Day10.parse(&inputs[..9]).unwrap();
+    assert_eq!(Day10.part_one(&token_lines).unwrap().value, Value::from(26397));
+    assert_eq!(Day10.part_two(&token_lines).unwrap_err(), error);
+}
+
+#[test]
+fn test_score_overflow() {
+    let inputs = vec!["[]".to_string(), "(".repeat(30)];
+    let token_lines = read_tokens(&inputs).unwrap();
+    let error = score_from_incomplete_lines(&token_lines).unwrap_err();
+    assert_eq!(error.to_string(), "line 2, column 1: the autocompletion score doesn't fit in 64 bits");
+}
+#[test]
+fn test_read_malformed_tokens() {
+    let inputs = vec!["[({(<(())[]>[[{[]{<()<>>".to_string(), "[(()[<>])]({[<{<x>".to_string()];
+    let error = read_tokens(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 2, column 17: expected a bracket, found `x`");
+}
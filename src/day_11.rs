@@ -1,64 +1,75 @@
-use advent_of_code_2021::read_lines;
+use crate::{parse_digit_grid, Answer, Grid, Neighbourhood, ParseError, Solution};
+#[cfg(test)]
+use crate::read_lines;
 
-pub(crate) fn run() {
-    let input = read_lines("data/day_11_input.txt");
-    let mut octopi = octopi_from_input(&input);
-    let mut flashes = 0;
+pub struct Day11;
 
-    for _ in 0..100 {
-        simple_energy_increase(&mut octopi);
-        flashes += flash_octopi(&mut octopi);
+/// How many steps part two waits for all octopi to flash at the same time
+pub const MAX_STEPS: usize = 500;
+
+impl Solution for Day11 {
+    const DAY: usize = 11;
+    type Parsed = OctopusGrid;
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        octopi_from_input(inputs)
     }
 
-    println!("After 100 iterations, there have been {} flashes", flashes);
+    fn part_one(&self, octopi: &Self::Parsed) -> Result<Answer, ParseError> {
+        let mut octopi = octopi.clone();
+        let mut flashes = 0;
 
-    // Reset
-    octopi = octopi_from_input(&input);
+        for _ in 0..100 {
+            simple_energy_increase(&mut octopi);
+            flashes += flash_octopi(&mut octopi);
+        }
 
-    for iter in 0..500 {
-        simple_energy_increase(&mut octopi);
-        let flash = flash_octopi(&mut octopi);
+        Ok(Answer::new(flashes, format!("After 100 iterations, there have been {} flashes", flashes)))
+    }
 
-        if flash == 100 {
-            println!("All octopi flashed after {} iterations!", iter + 1);
-            break;
-        };
+    fn part_two(&self, octopi: &Self::Parsed) -> Result<Answer, ParseError> {
+        let mut octopi = octopi.clone();
+        let octopi_count = octopi.width() * octopi.height();
+
+        for iter in 0..MAX_STEPS {
+            simple_energy_increase(&mut octopi);
+            let flash = flash_octopi(&mut octopi);
+
+            if flash == octopi_count {
+                return Ok(Answer::new(iter + 1, format!("All octopi flashed after {} iterations!", iter + 1)));
+            };
+        }
+
+        Err(ParseError::input(format!(
+            "the octopi don't all flash at the same time within {} iterations", MAX_STEPS
+        )))
     }
 }
 
-type OctopusGrid = Vec<Vec<usize>>;
-
-pub(crate) fn octopi_from_input(input: &Vec<String>) -> OctopusGrid {
-    input
-        .iter()
-        .map(|line| {
-            line.chars()
-                .map(|c| c.to_string().parse::<usize>().unwrap())
-                .collect()
-        })
-        .collect()
+pub type OctopusGrid = Grid<usize>;
+
+pub fn octopi_from_input(input: &[String]) -> Result<OctopusGrid, ParseError> {
+    parse_digit_grid(input)
 }
 
-pub(crate) fn simple_energy_increase(octopi: &mut OctopusGrid) {
-    for octopus_row in octopi {
-        for octopus in octopus_row {
-            *octopus += 1;
-        }
+pub fn simple_energy_increase(octopi: &mut OctopusGrid) {
+    for octopus in octopi.values_mut() {
+        *octopus += 1;
     }
 }
 
-pub(crate) fn flash_octopi(octopi: &mut OctopusGrid) -> usize {
+pub fn flash_octopi(octopi: &mut OctopusGrid) -> usize {
     let mut flashes = 0;
 
     let mut row = 0;
     let mut col = 0;
 
-    while row < octopi.len() {
+    while row < octopi.height() {
         let mut do_advance = true;
 
-        if octopi[row][col] > 9 {
+        if octopi[(row, col)] > 9 {
             flashes += 1;
-            octopi[row][col] = 0;
+            octopi[(row, col)] = 0;
             propagate_energy(octopi, row, col);
 
             // Go back 2 higher and to left if possible: the `match` below will advance one
@@ -69,7 +80,7 @@ pub(crate) fn flash_octopi(octopi: &mut OctopusGrid) -> usize {
         // Advance position along the grid unless we backtracked
         if ! do_advance { continue; }
 
-        match col < octopi[0].len() - 1 {
+        match col < octopi.width() - 1 {
             true => col += 1,
             false => {
                 col = 0;
@@ -81,65 +92,11 @@ pub(crate) fn flash_octopi(octopi: &mut OctopusGrid) -> usize {
     flashes
 }
 
-pub(crate) fn propagate_energy(octopi: &mut OctopusGrid, row: usize, col: usize) {
-    // Update upper left
-    let rows = octopi.len();
-    let cols = octopi[0].len();
-
-    let has_left = col > 0;
-    let has_above = row > 0;
-    let has_right = col < cols - 1;
-    let has_below = row < rows - 1;
-    let has_upper_left = has_above && has_left;
-    let has_upper_right = has_above && has_right;
-    let has_bottom_left = has_below && has_left;
-    let has_bottom_right = has_below && has_right;
-
-    if has_upper_left {
-        if octopi[row - 1][col - 1] > 0 {
-            octopi[row - 1][col - 1] += 1;
-        }
-    }
-
-    if has_above {
-        if octopi[row - 1][col] > 0 {
-            octopi[row - 1][col] += 1;
-        }
-    }
-
-    if has_upper_right {
-        if octopi[row - 1][col + 1] > 0 {
-            octopi[row - 1][col + 1] += 1;
-        }
-    }
-
-    if has_left {
-        if octopi[row][col - 1] > 0 {
-            octopi[row][col - 1] += 1;
-        }
-    }
-
-    if has_right {
-        if octopi[row][col + 1] > 0 {
-            octopi[row][col + 1] += 1;
-        }
-    }
-
-    if has_bottom_left {
-        if octopi[row + 1][col - 1] > 0 {
-            octopi[row + 1][col - 1] += 1;
-        }
-    }
-
-    if has_below {
-        if octopi[row + 1][col] > 0 {
-            octopi[row + 1][col] += 1;
-        }
-    }
-
-    if has_bottom_right {
-        if octopi[row + 1][col + 1] > 0 {
-            octopi[row + 1][col + 1] += 1;
+pub fn propagate_energy(octopi: &mut OctopusGrid, row: usize, col: usize) {
+    // Octopi that flashed this step stay at 0
+    for neighbour in octopi.neighbours((row, col), Neighbourhood::Moore) {
+        if octopi[neighbour] > 0 {
+            octopi[neighbour] += 1;
         }
     }
 }
@@ -147,32 +104,32 @@ pub(crate) fn propagate_energy(octopi: &mut OctopusGrid, row: usize, col: usize)
 #[cfg(test)]
 #[test]
 fn test_parse() {
-    let input = read_lines("data/day_11_sample.txt");
-    let octopi = octopi_from_input(&input);
-    assert_eq!(octopi.len(), 10);
-    assert_eq!(octopi[0].len(), 10);
+    let input = read_lines("data/day_11_sample.txt").unwrap();
+    let octopi = octopi_from_input(&input).unwrap();
+    assert_eq!(octopi.height(), 10);
+    assert_eq!(octopi.width(), 10);
 }
 
 #[test]
 fn test_increase() {
-    let input = read_lines("data/day_11_sample.txt");
-    let mut octopi = octopi_from_input(&input);
-    assert_eq!(octopi[0][0], 5);
-    assert_eq!(octopi[9][9], 6);
+    let input = read_lines("data/day_11_sample.txt").unwrap();
+    let mut octopi = octopi_from_input(&input).unwrap();
+    assert_eq!(octopi[(0, 0)], 5);
+    assert_eq!(octopi[(9, 9)], 6);
 
     simple_energy_increase(&mut octopi);
-    assert_eq!(octopi[0][0], 6);
-    assert_eq!(octopi[9][9], 7);
+    assert_eq!(octopi[(0, 0)], 6);
+    assert_eq!(octopi[(9, 9)], 7);
 }
 
 #[test]
 fn test_energy_increase() {
-    let input = read_lines("data/day_11_sample.txt");
-    let mut octopi = octopi_from_input(&input);
+    let input = read_lines("data/day_11_sample.txt").unwrap();
+    let mut octopi = octopi_from_input(&input).unwrap();
 
     // Once - nothing fancy happens yet
     simple_energy_increase(&mut octopi);
-    let expected = vec![
+    let expected = Grid::from_rows(vec![
         vec![6, 5, 9, 4, 2, 5, 4, 3, 3, 4],
         vec![3, 8, 5, 6, 9, 6, 5, 8, 2, 2],
         vec![6, 3, 7, 5, 6, 6, 7, 2, 8, 4],
@@ -183,14 +140,14 @@ fn test_energy_increase() {
         vec![7, 9, 9, 3, 9, 9, 2, 2, 4, 5],
         vec![5, 9, 5, 7, 9, 5, 9, 6, 6, 5],
         vec![6, 3, 9, 4, 8, 6, 2, 6, 3, 7],
-    ];
+    ]);
     assert_eq!(octopi, expected);
 }
 
 #[test]
 fn test_flash() {
-    let input = read_lines("data/day_11_sample.txt");
-    let mut octopi = octopi_from_input(&input);
+    let input = read_lines("data/day_11_sample.txt").unwrap();
+    let mut octopi = octopi_from_input(&input).unwrap();
 
     // Once - nothing fancy happens yet
     simple_energy_increase(&mut octopi);
@@ -199,7 +156,7 @@ fn test_flash() {
     // Twice - now flashes start happening
     simple_energy_increase(&mut octopi);
     assert_eq!(flash_octopi(&mut octopi), 35);
-    let expected = vec![
+    let expected = Grid::from_rows(vec![
         vec![8, 8, 0, 7, 4, 7, 6, 5, 5, 5],
         vec![5, 0, 8, 9, 0, 8, 7, 0, 5, 4],
         vec![8, 5, 9, 7, 8, 8, 9, 6, 0, 8],
@@ -210,14 +167,14 @@ fn test_flash() {
         vec![0, 0, 0, 0, 0, 0, 7, 4, 5, 6],
         vec![9, 0, 0, 0, 0, 0, 0, 8, 7, 6],
         vec![8, 7, 0, 0, 0, 0, 6, 8, 4, 8],
-    ];
+    ]);
     assert_eq!(octopi, expected);
 
     // Third iteration
     simple_energy_increase(&mut octopi);
     assert_eq!(
         flash_octopi(&mut octopi), 45);
-    let expected = vec![
+    let expected = Grid::from_rows(vec![
         vec![0, 0, 5, 0, 9, 0, 0, 8, 6, 6],
         vec![8, 5, 0, 0, 8, 0, 0, 5, 7, 5],
         vec![9, 9, 0, 0, 0, 0, 0, 0, 3, 9],
@@ -228,14 +185,14 @@ fn test_flash() {
         vec![2, 2, 1, 1, 1, 3, 0, 0, 0, 0],
         vec![0, 4, 2, 1, 1, 2, 5, 0, 0, 0],
         vec![0, 0, 2, 1, 1, 1, 9, 0, 0, 0],
-    ];
+    ]);
     assert_eq!(octopi, expected)
 }
 
 #[test]
 fn test_100_steps() {
-    let input = read_lines("data/day_11_sample.txt");
-    let mut octopi = octopi_from_input(&input);
+    let input = read_lines("data/day_11_sample.txt").unwrap();
+    let mut octopi = octopi_from_input(&input).unwrap();
     let mut flashes = 0;
 
     for _ in 0..100 {
@@ -243,4 +200,12 @@ fn test_100_steps() {
         flashes += flash_octopi(&mut octopi);
     }
     assert_eq!(flashes, 1656);
-}
\ No newline at end of file
+}
+#[test]
+fn test_never_all_flashing() {
+    // These two keep flashing out of step with each other
+    let octopi = Day11.parse(&["29".to_string()]).unwrap();
+    let error = Day11.part_two(&octopi).unwrap_err();
+    assert_eq!(error.to_string(), "the octopi don't all flash at the same time within 500 iterations");
+    assert!(Day11.part_one(&octopi).is_ok());
+}
@@ -1,35 +1,65 @@
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
-use advent_of_code_2021::read_lines;
+use log::debug;
+use crate::{split_pair, Answer, ParseError, Solution};
+#[cfg(test)]
+use crate::read_lines;
 
-pub(crate) fn run() {
-    let inputs = read_lines("data/day_12_input.txt");
-    let (_, cave_edges) = parse_cave_system(&inputs);
-    let paths = all_paths(&cave_edges, 1);
-    println!("There are {} valid paths out of the caves with one small cave visit", paths.len());
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: usize = 12;
+    type Parsed = EdgeArray;
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        let (_, cave_edges) = parse_cave_system(inputs)?;
+        Ok(cave_edges)
+    }
+
+    fn part_one(&self, cave_edges: &Self::Parsed) -> Result<Answer, ParseError> {
+        let paths = all_paths(cave_edges, 1);
+        Ok(Answer::new(paths.len(), format!(
+            "There are {} valid paths out of the caves with one small cave visit", paths.len()
+        )))
+    }
 
-    let (_, edges) = parse_cave_system(&inputs);
-    let paths = all_paths(&edges, 2);
-    println!("There are {} valid paths with a small cave visit max twice", paths.len());
+    fn part_two(&self, cave_edges: &Self::Parsed) -> Result<Answer, ParseError> {
+        let paths = all_paths(cave_edges, 2);
+        Ok(Answer::new(paths.len(), format!(
+            "There are {} valid paths with a small cave visit max twice", paths.len()
+        )))
+    }
 }
 
-type NodeArray = Vec<String>;
-type EdgeArray = Vec<(String, String)>;
-type Paths = HashMap<String, Vec<String>>;
+pub type NodeArray = Vec<String>;
+pub type EdgeArray = Vec<(String, String)>;
+pub type Paths = HashMap<String, Vec<String>>;
 
-pub(crate) fn parse_cave_system(inputs: &Vec<String>) -> (NodeArray, EdgeArray) {
+pub fn parse_cave_system(inputs: &[String]) -> Result<(NodeArray, EdgeArray), ParseError> {
     let mut nodes = Vec::new();
     let mut edges = Vec::new();
 
-    for line in inputs {
-        let node_a_b = line.split("-").collect::<Vec<_>>();
+    for (line_idx, line) in inputs.iter().enumerate() {
+        let ((_, node_a), (node_b_column, node_b)) = split_pair(line, "-", line_idx)?;
+        if node_a.is_empty() {
+            return Err(ParseError::at(line_idx, 0, "expected a cave name"));
+        }
+        if node_b.is_empty() || node_b.contains('-') {
+            return Err(ParseError::at(line_idx, node_b_column, "expected a single cave name"));
+        }
+        // Paths could go back and forth between two big caves forever
+        if node_a.to_lowercase() != node_a && node_b.to_lowercase() != node_b {
+            return Err(ParseError::at(
+                line_idx, 0, format!("expected a small cave at one end of the tunnel at least, found big caves `{}` and `{}`", node_a, node_b)
+            ));
+        }
 
-        let from = node_a_b[0].to_string();
+        let from = node_a.to_string();
         if !nodes.contains(&from) {
             nodes.push(from.clone());
         }
 
-        let to = node_a_b[1].to_string();
+        let to = node_b.to_string();
         if !nodes.contains(&to) {
             nodes.push(to.clone());
         }
@@ -40,10 +70,10 @@ pub(crate) fn parse_cave_system(inputs: &Vec<String>) -> (NodeArray, EdgeArray)
 
     }
 
-    (nodes, edges)
+    Ok((nodes, edges))
 }
 
-pub(crate) fn all_paths(caves: &EdgeArray, max_single_small_cave_visits: usize) -> Paths {
+pub fn all_paths(caves: &[(String, String)], max_single_small_cave_visits: usize) -> Paths {
     // Begin paths from just the start node
     let mut paths = HashMap::new();
     let start = "start".to_string();
@@ -52,11 +82,11 @@ pub(crate) fn all_paths(caves: &EdgeArray, max_single_small_cave_visits: usize)
 
     loop {
         for (path_id, _) in paths.clone() {
-            expand_paths(&caves, &mut paths, &path_id, max_single_small_cave_visits);
+            expand_paths(caves, &mut paths, &path_id, max_single_small_cave_visits);
         }
 
         if paths.len() == paths_len {
-            println!("Paths not modified in last iteration, returning paths");
+            debug!("Paths not modified in last iteration, returning paths");
             let ending_paths = paths
                 .iter()
                 .filter(|(key, _)| key.ends_with(&"end".to_string()))
@@ -69,13 +99,13 @@ pub(crate) fn all_paths(caves: &EdgeArray, max_single_small_cave_visits: usize)
     }
 }
 
-pub(crate) fn expand_paths(
-    caves: &EdgeArray,
+pub fn expand_paths(
+    caves: &[(String, String)],
     paths: &mut Paths,
-    path_id: &String,
+    path_id: &str,
     max_single_small_cave_visits: usize
 ) {
-    let last_cave = paths[&path_id.clone()].last().unwrap();
+    let last_cave = paths[path_id].last().unwrap();
 
     // If the path is already complete: no need to process further
     if last_cave == &"end".to_string() {
@@ -95,8 +125,8 @@ pub(crate) fn expand_paths(
         // continue to the next cave
 
         if (cave.to_lowercase() == cave)
-            && paths[&path_id.clone()].contains(&cave)
-            && small_cave_visits_already_at_max(&paths[&path_id.clone()], max_single_small_cave_visits) {
+            && paths[path_id].contains(&cave)
+            && small_cave_visits_already_at_max(&paths[path_id], max_single_small_cave_visits) {
             continue;
         }
 
@@ -106,11 +136,11 @@ pub(crate) fn expand_paths(
         }
 
         // Otherwise: add the node id to the path
-        add_cave_to_path(paths, &path_id, cave);
+        add_cave_to_path(paths, path_id, cave);
     }
 }
 
-fn add_cave_to_path(paths: &mut Paths, path_id: &String, cave: String) {
+fn add_cave_to_path(paths: &mut Paths, path_id: &str, cave: String) {
     // Add a new "branch" to the list of paths
     let mut new_path = paths[path_id].clone();
     // Get rid of the last node index: it was added in the modify-in-place pass
@@ -119,7 +149,7 @@ fn add_cave_to_path(paths: &mut Paths, path_id: &String, cave: String) {
     paths.insert(key.clone(), new_path);
 }
 
-pub(crate) fn small_cave_visits_already_at_max(path_as_strings: &Vec<String>, max_single_small_cave_visits: usize) -> bool {
+pub fn small_cave_visits_already_at_max(path_as_strings: &[String], max_single_small_cave_visits: usize) -> bool {
     let mut small_cave_counts = HashMap::new();
 
     for cave_name in path_as_strings {
@@ -133,7 +163,7 @@ pub(crate) fn small_cave_visits_already_at_max(path_as_strings: &Vec<String>, ma
 
             *entry += 1;
 
-            if entry.clone() >= max_single_small_cave_visits {
+            if *entry >= max_single_small_cave_visits {
                 return true;
             }
         }
@@ -147,13 +177,13 @@ pub(crate) fn small_cave_visits_already_at_max(path_as_strings: &Vec<String>, ma
 fn test_lowercase_comp() {
     let uppercase = "BLA".to_string();
     let is_equal = uppercase == uppercase.to_lowercase();
-    assert_eq!(is_equal, false);
+    assert!(!is_equal);
 }
 
 #[test]
 fn test_single_loop_iteration_paths_expansion() {
-    let inputs = read_lines("data/day_12_sample.txt");
-    let (_, edges) = parse_cave_system(&inputs);
+    let inputs = read_lines("data/day_12_sample.txt").unwrap();
+    let (_, edges) = parse_cave_system(&inputs).unwrap();
 
     let mut paths = HashMap::new();
     let start = "start".to_string();
@@ -173,16 +203,16 @@ fn test_single_loop_iteration_paths_expansion() {
 
 #[test]
 fn test_simple_graph_parse() {
-    let inputs = read_lines("data/day_12_sample.txt");
-    let (nodes, edges) = parse_cave_system(&inputs);
+    let inputs = read_lines("data/day_12_sample.txt").unwrap();
+    let (nodes, edges) = parse_cave_system(&inputs).unwrap();
     assert_eq!(nodes.len(), 6);
     assert_eq!(edges.len(), inputs.len() * 2);
 }
 
 #[test]
 fn test_all_valid_paths_small_sample() {
-    let inputs = read_lines("data/day_12_sample.txt");
-    let (_, cave_edges) = parse_cave_system(&inputs);
+    let inputs = read_lines("data/day_12_sample.txt").unwrap();
+    let (_, cave_edges) = parse_cave_system(&inputs).unwrap();
 
     let paths = all_paths(&cave_edges, 1);
     assert_eq!(paths.len(), 10);
@@ -193,8 +223,8 @@ fn test_all_valid_paths_small_sample() {
 
 #[test]
 fn test_valid_paths_larger_sample() {
-    let inputs = read_lines("data/day_12_larger_sample.txt");
-    let (_, cave_edges) = parse_cave_system(&inputs);
+    let inputs = read_lines("data/day_12_larger_sample.txt").unwrap();
+    let (_, cave_edges) = parse_cave_system(&inputs).unwrap();
 
     let paths = all_paths(&cave_edges, 1);
     assert_eq!(paths.len(), 19);
@@ -205,12 +235,25 @@ fn test_valid_paths_larger_sample() {
 
 #[test]
 fn test_largest_sample() {
-    let inputs = read_lines("data/day_12_even_larger_sample.txt");
-    let (_, cave_edges) = parse_cave_system(&inputs);
+    let inputs = read_lines("data/day_12_even_larger_sample.txt").unwrap();
+    let (_, cave_edges) = parse_cave_system(&inputs).unwrap();
 
     let paths = all_paths(&cave_edges, 1);
     assert_eq!(paths.len(), 226);
 
     let paths = all_paths(&cave_edges, 2);
     assert_eq!(paths.len(), 3509);
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_malformed_cave_system() {
+    let inputs = vec!["start-A".to_string(), "A-b-end".to_string()];
+    let error = parse_cave_system(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 2, column 3: expected a single cave name");
+
+    let inputs = vec!["start A".to_string()];
+    assert!(parse_cave_system(&inputs).is_err());
+
+    let inputs = vec!["start-A".to_string(), "A-end".to_string(), "A-B".to_string()];
+    let error = parse_cave_system(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 3, column 1: expected a small cave at one end of the tunnel at least, found big caves `A` and `B`");
+}
@@ -1,34 +1,39 @@
-use ndarray::{Array2};
-use advent_of_code_2021::read_lines;
+use crate::{parse_number, split_pair, Answer, Grid, ParseError, Solution};
+#[cfg(test)]
+use crate::read_lines;
 use crate::day_13::Fold::{Left, Up};
 
-pub(crate) fn run() {
-    let inputs = read_lines("data/day_13_input.txt");
-    let (mut grid, folds) = parse_grid(&inputs);
+pub struct Day13;
 
-    fold_grid(&mut grid, &folds[0]);
-    let dots = count_dots(&grid);
-    println!("After the first (left) fold, there are {} dots", dots);
+/// The furthest out a dot can be, either way, which sizes the sheet of dots
+pub const MAX_COORDINATE: usize = 5_000;
 
-    // Execute other folds
-    for fold in folds[1..].iter() {
-        fold_grid(&mut grid, fold);
+impl Solution for Day13 {
+    const DAY: usize = 13;
+    type Parsed = (Grid<bool>, Vec<Fold>);
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        parse_grid(inputs)
     }
 
-    let xs = 0..50;
-    let ys = 0..10;
+    fn part_one(&self, (grid, folds): &Self::Parsed) -> Result<Answer, ParseError> {
+        let mut grid = grid.clone();
+        fold_grid(&mut grid, &folds[0]);
+        let dots = count_dots(&grid);
 
-    for y in ys {
-        for x in xs.clone() {
-            if grid[x][y] == true {
-                print!("#")
-            } else {
-                print!(".")
-            }
+        Ok(Answer::new(dots, format!("After the first (left) fold, there are {} dots", dots)))
+    }
+
+    fn part_two(&self, (grid, folds): &Self::Parsed) -> Result<Answer, ParseError> {
+        let mut grid = grid.clone();
+
+        for fold in folds {
+            fold_grid(&mut grid, fold);
         }
-        print!("\n");
+
+        let code = render_grid(&grid, folds);
+        Ok(Answer::new(code.clone(), format!("After all the folds, the code is:\n{}", code)))
     }
-    // println!("After all the folds, the code is: {:?}", grid);
 }
 
 #[derive(Debug, PartialEq)]
@@ -37,71 +42,80 @@ pub enum Fold {
     Up(usize),
 }
 
-pub(crate) fn parse_grid(inputs: &Vec<String>) -> (Vec<Vec<bool>>, Vec<Fold>) {
-    let mut grid: Vec<Vec<bool>> = Vec::new();
+/// Parses the dots into a grid with `y` as the row and `x` as the column, along with the folds
+pub fn parse_grid(inputs: &[String]) -> Result<(Grid<bool>, Vec<Fold>), ParseError> {
+    let mut dots = Vec::new();
     let mut fold_instructions = Vec::new();
 
-    for input in inputs {
+    for (line_idx, input) in inputs.iter().enumerate() {
         if input == &"".to_string() { continue; }
 
         // Handle fold instructions
-        if input.starts_with("fold along ") {
-            let direction_amount = input[11..]
-                .split("=")
-                .collect::<Vec<&str>>();
-
-            let direction = direction_amount[0].clone();
-            let amount = direction_amount[1].parse::<usize>().unwrap();
-
-            if direction == "y" {
-                fold_instructions.push(Up(amount));
-            } else {
-                fold_instructions.push(Left(amount));
+        if let Some(fold_instruction) = input.strip_prefix("fold along ") {
+            let instruction_column = input.len() - fold_instruction.len();
+            let ((_, direction), (amount_column, amount)) = split_pair(fold_instruction, "=", line_idx)?;
+            let amount = parse_number::<usize>(amount, line_idx, instruction_column + amount_column)?;
+
+            match direction {
+                "y" => fold_instructions.push(Up(amount)),
+                "x" => fold_instructions.push(Left(amount)),
+                _ => return Err(ParseError::at(
+                    line_idx, instruction_column, format!("expected to fold along x or y, found `{}`", direction)
+                )),
             }
 
             continue;
         }
 
-        // Otherwise: the row is a set of coordinates
-        let coords = input.split(",").collect::<Vec<_>>();
-        let x = coords[0].parse::<usize>().unwrap();
-        let y = coords[1].parse::<usize>().unwrap();
+        if !fold_instructions.is_empty() {
+            return Err(ParseError::at(line_idx, 0, "expected a fold instruction after the first one"));
+        }
 
-        // Expand if necessary
-        if x >= grid.len() {
-            let cloned_first_row: Vec<bool> = grid
-                .get(0)
-                .unwrap_or(&vec![]).clone();
+        // Otherwise: the row is a set of coordinates
+        let ((x_column, x), (y_column, y)) = split_pair(input, ",", line_idx)?;
+        let x = parse_number::<usize>(x, line_idx, x_column)?;
+        let y = parse_number::<usize>(y, line_idx, y_column)?;
+        if let Some(&(column, coordinate)) = [(x_column, x), (y_column, y)]
+            .iter()
+            .find(|&&(_, coordinate)| coordinate > MAX_COORDINATE)
+        {
+            return Err(ParseError::at(
+                line_idx, column, format!("expected a coordinate up to {}, found {}", MAX_COORDINATE, coordinate)
+            ));
+        }
 
-            let extra_rows = vec![vec![false; cloned_first_row.len()]; x + 1 - grid.len()];
+        dots.push((x, y));
+    }
 
-            grid.extend(extra_rows);
-        }
+    if dots.is_empty() {
+        return Err(ParseError::input("expected the coordinates of at least one dot"));
+    }
+    if fold_instructions.is_empty() {
+        return Err(ParseError::input("expected at least one fold instruction after the dots"));
+    }
 
-        if y >= grid[0].len() {
-            let extra_xs = vec![false; y + 1 - grid[0].len()];
-            for x_idx in 0..grid.len() {
-                grid[x_idx].extend(extra_xs.clone());
-            }
-        }
+    // Size the grid to fit the dots furthest out
+    let width = dots.iter().map(|&(x, _)| x + 1).max().unwrap();
+    let height = dots.iter().map(|&(_, y)| y + 1).max().unwrap();
+    let mut grid = Grid::new(width, height, false);
 
-        // Mark position
-        grid[x][y] = true;
+    for (x, y) in dots {
+        grid[(y, x)] = true;
     }
 
-    (grid, fold_instructions)
+    Ok((grid, fold_instructions))
 }
 
-pub(crate) fn fold_grid(grid: &mut Vec<Vec<bool>>, fold: &Fold) {
+pub fn fold_grid(grid: &mut Grid<bool>, fold: &Fold) {
     match fold {
         Up(fold_position) => {
             // Take all row indices below the fold
             // The example folds at row 7, so the row idxs are 8 thru 15
-            let y_idxs_below_fold = (fold_position + 1)..grid[0].len();
+            let y_idxs_below_fold = (fold_position + 1)..grid.height();
 
             // We need all the column indices: they all get mirrored to the other side of the
             // fold
-            let x_idxs = 0..grid.len();
+            let x_idxs = 0..grid.width();
 
             // Iterate over the range instead of the rows themselves, otherwise we get into bad
             // borrow territory
@@ -110,30 +124,30 @@ pub(crate) fn fold_grid(grid: &mut Vec<Vec<bool>>, fold: &Fold) {
                     // We mirror to the opposite side of the fold
                     // So row 8 copies to row 6, which is the fold row 7 minus the difference
                     // between the fold row and the row below
-                    let mut mirrored_y = fold_position.clone();
+                    let mut mirrored_y = *fold_position;
                     mirrored_y *= 2;
 
                     // Check if the fold is before half the page. Can't copy over values beyond double half
                     if mirrored_y >= y_below_fold_idx {
-                        mirrored_y = mirrored_y - y_below_fold_idx;
+                        mirrored_y -= y_below_fold_idx;
 
                         // Copy over only `true` values by OR-ing
-                        grid[x_idx][mirrored_y] = grid[x_idx][y_below_fold_idx] | grid[x_idx][mirrored_y];
+                        grid[(mirrored_y, x_idx)] |= grid[(y_below_fold_idx, x_idx)];
                     }
 
                     // Empty the stuff beyond the fold
-                    grid[x_idx][y_below_fold_idx] = false;
+                    grid[(y_below_fold_idx, x_idx)] = false;
                 }
             }
         }
         Left(fold_position) => {
             // Take all x indices to the right of the fold
             // The example folds at x = 5, so the x to the right idxs are 5 thru 11
-            let x_idxs_right_of_fold = (fold_position + 1)..grid.len();
+            let x_idxs_right_of_fold = (fold_position + 1)..grid.width();
 
             // We need all the y indices: they all get mirrored to the other side of the
             // fold
-            let y_idxs = 0..grid[0].len();
+            let y_idxs = 0..grid.height();
 
             // Iterate over the range instead of the rows themselves, otherwise we get into bad
             // borrow territory
@@ -142,42 +156,53 @@ pub(crate) fn fold_grid(grid: &mut Vec<Vec<bool>>, fold: &Fold) {
                     // We mirror to the opposite side of the fold
                     // So col 8 copies to row 6, which is the fold col 7 minus the difference
                     // between the fold col and the col to the right
-                    let mut mirrored_x = fold_position.clone();
+                    let mut mirrored_x = *fold_position;
                     mirrored_x *= 2;
 
                     if x_right_of_fold_idx <= mirrored_x {
-                        mirrored_x = mirrored_x - x_right_of_fold_idx;
+                        mirrored_x -= x_right_of_fold_idx;
                         // Copy over only `true` values by OR-ing
-                        grid[mirrored_x][y_idx] = grid[x_right_of_fold_idx][y_idx] | grid[mirrored_x][y_idx];
+                        grid[(y_idx, mirrored_x)] |= grid[(y_idx, x_right_of_fold_idx)];
                     }
 
                     // Empty the stuff beyond the fold
-                    grid[x_right_of_fold_idx][y_idx] = false;
+                    grid[(y_idx, x_right_of_fold_idx)] = false;
                 }
             }
         }
     }
 }
 
-pub(crate) fn count_dots(grid: &Vec<Vec<bool>>) -> usize {
-    let mut count = 0;
+pub fn count_dots(grid: &Grid<bool>) -> usize {
+    grid.values().filter(|&&dot| dot).count()
+}
+
+/// Renders the part of the grid that is left over after folding as lines of `#` and `.`
+pub fn render_grid(grid: &Grid<bool>, folds: &[Fold]) -> String {
+    let mut width = grid.width();
+    let mut height = grid.height();
 
-    for row in grid {
-        for entry in row {
-            if *entry { count += 1; }
+    for fold in folds {
+        match fold {
+            Left(x) => width = width.min(*x),
+            Up(y) => height = height.min(*y),
         }
     }
 
-    count
+    grid.view(0, 0, height, width)
+        .to_grid()
+        .map(|&dot| if dot { '#' } else { '.' })
+        .to_string()
 }
+
 #[cfg(test)]
 #[test]
 fn test_parse_grid() {
-    let inputs = read_lines("data/day_13_sample.txt");
-    let (grid, fold_instructions) = parse_grid(&inputs);
-    assert_eq!(grid.len(), 11);
-    assert_eq!(grid[0].len(), 15);
-    
+    let inputs = read_lines("data/day_13_sample.txt").unwrap();
+    let (grid, fold_instructions) = parse_grid(&inputs).unwrap();
+    assert_eq!(grid.width(), 11);
+    assert_eq!(grid.height(), 15);
+
     let expected = vec![
         false, false, false, true, false, false, true, false, false, true, false,
         false, false, false, false, true, false, false, false, false, false, false,
@@ -195,15 +220,15 @@ fn test_parse_grid() {
         true, false, false, false, false, false, false, false, false, false, false,
         true, false, true, false, false, false, false, false, false, false, false,
     ];
-    let array = Array2::from_shape_vec((11, 15), expected).unwrap();
-    assert_eq!(array.shape(), [11, 15]);
+    let expected = expected.chunks(11).map(|row| row.to_vec()).collect();
+    assert_eq!(grid, Grid::from_rows(expected));
     assert_eq!(fold_instructions, vec![Up(7), Left(5)]);
 }
 
 #[test]
 fn test_fold() {
-    let inputs = read_lines("data/day_13_sample.txt");
-    let (mut grid, fold_instructions) = parse_grid(&inputs);
+    let inputs = read_lines("data/day_13_sample.txt").unwrap();
+    let (mut grid, fold_instructions) = parse_grid(&inputs).unwrap();
 
     fold_grid(&mut grid, &fold_instructions[0]);
     let dots = count_dots(&grid);
@@ -212,4 +237,43 @@ fn test_fold() {
     fold_grid(&mut grid, &fold_instructions[1]);
     let dots = count_dots(&grid);
     assert_eq!(dots, 16);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_render_folded() {
+    let inputs = read_lines("data/day_13_sample.txt").unwrap();
+    let (mut grid, fold_instructions) = parse_grid(&inputs).unwrap();
+
+    for fold in &fold_instructions {
+        fold_grid(&mut grid, fold);
+    }
+
+    let rendered = render_grid(&grid, &fold_instructions);
+    assert_eq!(rendered, "#####\n#...#\n#...#\n#...#\n#####\n.....\n.....");
+}
+
+#[test]
+fn test_parse_malformed_grid() {
+    let inputs = vec!["6,10".to_string(), "".to_string(), "fold along z=7".to_string()];
+    let error = parse_grid(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 3, column 12: expected to fold along x or y, found `z`");
+
+    let inputs = vec!["6,10".to_string(), "".to_string(), "fold along y=seven".to_string()];
+    let error = parse_grid(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 3, column 14: expected a number, found `seven`");
+
+    let inputs = vec!["6,10".to_string(), "0,14".to_string()];
+    let error = parse_grid(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "expected at least one fold instruction after the dots");
+}
+
+#[test]
+fn test_coordinates_out_of_range() {
+    let inputs = vec!["1000000,1000000".to_string(), String::new(), "fold along y=7".to_string()];
+    let error = parse_grid(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 1: expected a coordinate up to 5000, found 1000000");
+
+    let inputs = vec!["5000,5001".to_string(), String::new(), "fold along y=7".to_string()];
+    let error = parse_grid(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 6: expected a coordinate up to 5000, found 5001");
+}
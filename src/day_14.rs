@@ -1,66 +1,109 @@
 use std::collections::HashMap;
-use advent_of_code_2021::read_lines;
+use crate::{split_pair, Answer, ParseError, Solution};
+#[cfg(test)]
+use crate::property::check_property;
+#[cfg(test)]
+use crate::{read_lines, Value};
 
-pub(crate) fn run() {
-    let inputs = read_lines("data/day_14_input.txt");
-    let (mut template, rules) = parse_inputs(&inputs);
+pub struct Day14;
 
-    for _ in 0..10 {
-        expand_polymer(&mut template, &rules);
+impl Solution for Day14 {
+    const DAY: usize = 14;
+    type Parsed = (Vec<String>, Vec<InsertRule>);
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        parse_inputs(inputs)
     }
 
-    let (min, max) = count_elems(&template);
-    println!("The max {} minus min {} is {} after 10 iterations", max, min, max - min);
+    fn part_one(&self, (template, rules): &Self::Parsed) -> Result<Answer, ParseError> {
+        let mut template = template.clone();
 
-    let (template, rules) = parse_inputs(&inputs);
-    let rules_map = rules_as_map(&rules);
-    let (pair_counts, start) = even_faster_expand_iter(&template, &rules_map, 40);
-    let (min, max) = min_max_from_pairs(&pair_counts, start);
-    println!("The max {} minus min {} is {} after 40 iterations", max, min, max - min);
+        for _ in 0..10 {
+            expand_polymer(&mut template, rules);
+        }
+
+        let (min, max) = count_elems(&template);
+        Ok(Answer::new(max - min, format!(
+            "The max {} minus min {} is {} after 10 iterations", max, min, max - min
+        )))
+    }
+
+    fn part_two(&self, (template, rules): &Self::Parsed) -> Result<Answer, ParseError> {
+        let rules_map = rules_as_map(rules);
+        let (pair_counts, start) = even_faster_expand_iter(template, &rules_map, 40);
+        let (min, max) = min_max_from_pairs(&pair_counts, start);
+        Ok(Answer::new(max - min, format!(
+            "The max {} minus min {} is {} after 40 iterations", max, min, max - min
+        )))
+    }
 }
 
-#[derive(Clone)]
-pub(crate) struct InsertRule {
+/// A pair insertion rule: the element to insert between the two elements of a matching pair
+#[derive(Clone, Debug, PartialEq)]
+pub struct InsertRule {
     first_match: String,
     adjacent_match: String,
     to_insert: String,
 }
 
-pub(crate) fn parse_inputs(inputs: &Vec<String>) -> (Vec<String>, Vec<InsertRule>) {
+impl InsertRule {
+    pub fn new(first_match: &str, adjacent_match: &str, to_insert: &str) -> Self {
+        InsertRule {
+            first_match: first_match.to_string(),
+            adjacent_match: adjacent_match.to_string(),
+            to_insert: to_insert.to_string(),
+        }
+    }
+
+    pub fn first_match(&self) -> &str {
+        &self.first_match
+    }
+
+    pub fn adjacent_match(&self) -> &str {
+        &self.adjacent_match
+    }
+
+    pub fn to_insert(&self) -> &str {
+        &self.to_insert
+    }
+}
+
+pub fn parse_inputs(inputs: &[String]) -> Result<(Vec<String>, Vec<InsertRule>), ParseError> {
     let mut rules = Vec::new();
 
-    let template = inputs[0]
+    let template = inputs
+        .first()
+        .filter(|template| !template.is_empty())
+        .ok_or_else(|| ParseError::input("expected the polymer template on the first line"))?
         .split("")
         .map(|c| c.to_string())
         .filter(|s| s != &"".to_string())
         .collect();
 
-    for instruction in inputs[2..].iter() {
-        let rule_parts = instruction.split(" -> ").collect::<Vec<_>>();
-
-        let first_match = rule_parts[0]
-            .chars()
-            .collect::<Vec<_>>()[0]
-            .to_string();
+    if inputs.get(1).map(|line| !line.is_empty()).unwrap_or(true) {
+        return Err(ParseError::at(1, 0, "expected a blank line between the template and the rules"));
+    }
 
-        let adjacent_match = rule_parts[0]
-            .chars()
-            .collect::<Vec<_>>()[1]
-            .to_string();
+    for (line_idx, instruction) in inputs.iter().enumerate().skip(2) {
+        let ((_, pair), (insert_column, to_insert)) = split_pair(instruction, " -> ", line_idx)?;
 
-        let to_insert = rule_parts[1].to_string();
+        let pair_chars = pair.chars().collect::<Vec<_>>();
+        if pair_chars.len() != 2 {
+            return Err(ParseError::at(line_idx, 0, format!("expected a pair of elements, found `{}`", pair)));
+        }
+        if to_insert.chars().count() != 1 {
+            return Err(ParseError::at(
+                line_idx, insert_column, format!("expected a single element to insert, found `{}`", to_insert)
+            ));
+        }
 
-        rules.push(InsertRule {
-            first_match,
-            adjacent_match,
-            to_insert,
-        });
+        rules.push(InsertRule::new(&pair_chars[0].to_string(), &pair_chars[1].to_string(), to_insert));
     }
 
-    (template, rules)
+    Ok((template, rules))
 }
 
-pub(crate) fn rules_as_map(rules: &Vec<InsertRule>) -> HashMap<String, String> {
+pub fn rules_as_map(rules: &[InsertRule]) -> HashMap<String, String> {
     let mut rules_map = HashMap::new();
 
     for rule in rules {
@@ -72,14 +115,14 @@ pub(crate) fn rules_as_map(rules: &Vec<InsertRule>) -> HashMap<String, String> {
     rules_map
 }
 
-pub(crate) fn expand_polymer(template: &mut Vec<String>, rules: &Vec<InsertRule>) {
+pub fn expand_polymer(template: &mut Vec<String>, rules: &[InsertRule]) {
     // Create a set of insert positions that will contain all the indexes of where the new
     // element is to be inserted
     let mut insert_positions_per_rule = vec![Vec::new(); rules.len()];
 
     // First, find all the matching positions before mutation
     for (rule_idx, rule) in rules.iter().enumerate() {
-        let positions = find_matches(&template, &rule);
+        let positions = find_matches(template, rule);
         insert_positions_per_rule[rule_idx] = positions;
     }
 
@@ -108,7 +151,9 @@ pub(crate) fn expand_polymer(template: &mut Vec<String>, rules: &Vec<InsertRule>
     }
 }
 
-pub(crate) fn fast_expand(template: &mut Vec<String>, rules_map: &HashMap<String, String>) {
+// Superseded by `even_faster_expand`, but kept to check it against
+#[allow(dead_code)]
+pub fn fast_expand(template: &mut Vec<String>, rules_map: &HashMap<String, String>) {
     // Look behind: cursor starts with 1 and finds a look-behind match in the rules map
     let mut cursor = 1;
     let mut template_size = template.len();
@@ -128,15 +173,16 @@ pub(crate) fn fast_expand(template: &mut Vec<String>, rules_map: &HashMap<String
     }
 }
 
-pub(crate) fn even_faster_expand_iter(
-    template: &Vec<String>,
+pub fn even_faster_expand_iter(
+    template: &[String],
     rules_map: &HashMap<String, String>,
     iterations: usize,
 ) -> (HashMap<String, usize>, String) {
     let mut pair_counts: HashMap<String, usize> = HashMap::new();
 
-    // Start pair is needed for the count
-    let mut start_pair = template[0].clone() + &template[1];
+    // Start pair is needed for the count. A template of one element has no pairs, so it's just
+    // that element, which never matches a rule
+    let mut start_pair = template.iter().take(2).cloned().collect::<String>();
 
     // Build initial hashmap
     for elem_idx in 0..template.len() - 1 {
@@ -163,7 +209,7 @@ pub fn even_faster_expand(
     let mut is_start_modified = false;
 
     for (unmodified_pair, unmodified_count) in unmodified_counts {
-        if rules_map.get(&unmodified_pair) == None { continue; }
+        if rules_map.get(&unmodified_pair).is_none() { continue; }
         if unmodified_count == 0 { continue; }
 
         let elem_to_insert = rules_map.get(&unmodified_pair).unwrap();
@@ -179,7 +225,7 @@ pub fn even_faster_expand(
         let first_elem = pair_chars[0].to_string();
         let second_elem = pair_chars[1].to_string();
 
-        let new_pair_to_left = first_elem + &elem_to_insert;
+        let new_pair_to_left = first_elem + elem_to_insert;
         let new_pair_to_right = elem_to_insert.to_string() + &second_elem;
 
         let entry_to_left = pair_counts.entry(new_pair_to_left.clone()).or_insert(0);
@@ -196,35 +242,40 @@ pub fn even_faster_expand(
     }
 }
 
-pub(crate) fn min_max_from_pairs(pair_counts: &HashMap<String, usize>, start_pair: String) -> (usize, usize) {
+/// Counts how often each element occurs in the polymer described by the pair counts
+pub fn elem_counts_from_pairs(pair_counts: &HashMap<String, usize>, start_pair: &str) -> HashMap<String, usize> {
     let mut elem_counts: HashMap<String, usize> = HashMap::new();
 
     for (pair, count) in pair_counts {
         if *count == 0 { continue; }
 
-        let first_elem = pair.chars().collect::<Vec<_>>()[0].to_string();
         let last_elem = pair.chars().collect::<Vec<_>>()[1].to_string();
 
         let elem_count = elem_counts.entry(last_elem.clone()).or_insert(0);
         *elem_count += *count;
-
-        // Except for the start pair: it has a non-overlapping first element
-        if pair == &start_pair {
-            let elem_count = elem_counts.entry(first_elem).or_insert(0);
-            *elem_count += *count;
-        }
     }
 
+    // Except for the start pair: it has a non-overlapping first element, which only occurs once
+    // even when the same pair shows up further along the polymer as well
+    let first_elem = start_pair.chars().collect::<Vec<_>>()[0].to_string();
+    *elem_counts.entry(first_elem).or_insert(0) += 1;
+
+    elem_counts
+}
+
+pub fn min_max_from_pairs(pair_counts: &HashMap<String, usize>, start_pair: String) -> (usize, usize) {
+    let elem_counts = elem_counts_from_pairs(pair_counts, &start_pair);
+
     let mut counts = elem_counts.values().collect::<Vec<_>>();
     counts.sort();
 
-    let min = *counts.first().unwrap().clone();
-    let max = *counts.last().unwrap().clone();
+    let min = **counts.first().unwrap();
+    let max = **counts.last().unwrap();
 
     (min, max)
 }
 
-pub(crate) fn find_matches(template: &Vec<String>, rule: &InsertRule) -> Vec<usize> {
+pub fn find_matches(template: &[String], rule: &InsertRule) -> Vec<usize> {
     let mut matches = Vec::new();
 
     let elem_idxs = 0..template.len() - 1;
@@ -237,7 +288,7 @@ pub(crate) fn find_matches(template: &Vec<String>, rule: &InsertRule) -> Vec<usi
     matches
 }
 
-pub(crate) fn count_elems(template: &Vec<String>) -> (usize, usize) {
+pub fn count_elems(template: &[String]) -> (usize, usize) {
     let mut counts = HashMap::new();
 
     for elem in template {
@@ -254,17 +305,18 @@ pub(crate) fn count_elems(template: &Vec<String>) -> (usize, usize) {
 #[cfg(test)]
 #[test]
 fn test_parse() {
-    let inputs = read_lines("data/day_14_sample.txt");
-    let (template, rules) = parse_inputs(&inputs);
+    let inputs = read_lines("data/day_14_sample.txt").unwrap();
+    let (template, rules) = parse_inputs(&inputs).unwrap();
 
     assert_eq!(template, vec!["N", "N", "C", "B"]);
-    assert_eq!(rules.len(), 16)
+    assert_eq!(rules.len(), 16);
+    assert_eq!(rules[0], InsertRule::new("C", "H", "B"));
 }
 
 #[test]
 fn test_matching_seqs() {
-    let inputs = read_lines("data/day_14_sample.txt");
-    let (template, rules) = parse_inputs(&inputs);
+    let inputs = read_lines("data/day_14_sample.txt").unwrap();
+    let (template, rules) = parse_inputs(&inputs).unwrap();
 
     let n_n = rules[7].clone();
     let positions = find_matches(&template, &n_n);
@@ -273,8 +325,8 @@ fn test_matching_seqs() {
 
 #[test]
 fn test_manual_iterate() {
-    let inputs = read_lines("data/day_14_sample.txt");
-    let (mut template, rules) = parse_inputs(&inputs);
+    let inputs = read_lines("data/day_14_sample.txt").unwrap();
+    let (mut template, rules) = parse_inputs(&inputs).unwrap();
 
     expand_polymer(&mut template, &rules);
     // Starts from       vec!["N", "N", "C", "B"]; cursor = 1
@@ -294,8 +346,8 @@ fn test_manual_iterate() {
 
 #[test]
 fn test_manual_fast_iterate() {
-    let inputs = read_lines("data/day_14_sample.txt");
-    let (mut template, rules) = parse_inputs(&inputs);
+    let inputs = read_lines("data/day_14_sample.txt").unwrap();
+    let (mut template, rules) = parse_inputs(&inputs).unwrap();
     let rules_map = rules_as_map(&rules);
 
     fast_expand(&mut template, &rules_map);
@@ -311,8 +363,8 @@ fn test_manual_fast_iterate() {
 
 #[test]
 fn test_even_faster_iterate() {
-    let inputs = read_lines("data/day_14_sample.txt");
-    let (template, rules) = parse_inputs(&inputs);
+    let inputs = read_lines("data/day_14_sample.txt").unwrap();
+    let (template, rules) = parse_inputs(&inputs).unwrap();
     let rules_map = rules_as_map(&rules);
 
     // NCNBCHB
@@ -385,8 +437,8 @@ fn test_even_faster_iterate() {
 
 #[test]
 fn test_count() {
-    let inputs = read_lines("data/day_14_sample.txt");
-    let (mut template, rules) = parse_inputs(&inputs);
+    let inputs = read_lines("data/day_14_sample.txt").unwrap();
+    let (mut template, rules) = parse_inputs(&inputs).unwrap();
 
     for _ in 0..10 {
         expand_polymer(&mut template, &rules);
@@ -398,11 +450,96 @@ fn test_count() {
 
 #[test]
 fn test_pair_count_hashmap_count() {
-    let inputs = read_lines("data/day_14_sample.txt");
-    let (template, rules) = parse_inputs(&inputs);
+    let inputs = read_lines("data/day_14_sample.txt").unwrap();
+    let (template, rules) = parse_inputs(&inputs).unwrap();
     let rules_map = rules_as_map(&rules);
 
     let (pair_counts, start) = even_faster_expand_iter(&template, &rules_map, 40);
     let (min, max) = min_max_from_pairs(&pair_counts, start);
     assert_eq!(max - min, 2188189693529);
 }
+
+#[test]
+fn test_elem_counts_from_pairs() {
+    let inputs = read_lines("data/day_14_sample.txt").unwrap();
+    let (template, rules) = parse_inputs(&inputs).unwrap();
+    let rules_map = rules_as_map(&rules);
+
+    let (pair_counts, start) = even_faster_expand_iter(&template, &rules_map, 10);
+    assert_eq!(elem_counts_from_pairs(&pair_counts, &start), HashMap::from([
+        ("B".to_string(), 1749),
+        ("C".to_string(), 298),
+        ("H".to_string(), 161),
+        ("N".to_string(), 865),
+    ]));
+}
+
+#[test]
+fn test_parse_malformed_inputs() {
+    let inputs = vec!["NNCB".to_string(), "CH -> B".to_string()];
+    let error = parse_inputs(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 2, column 1: expected a blank line between the template and the rules");
+
+    let inputs = vec!["NNCB".to_string(), "".to_string(), "CH -> B".to_string(), "HHH -> N".to_string()];
+    let error = parse_inputs(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 4, column 1: expected a pair of elements, found `HHH`");
+
+    let inputs = vec!["NNCB".to_string(), "".to_string(), "CH -> ".to_string()];
+    let error = parse_inputs(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 3, column 7: expected a single element to insert, found ``");
+}
+
+#[test]
+fn test_single_element_template() {
+    // There are no pairs to insert anything between, so the polymer stays as it is
+    let inputs = vec!["N".to_string(), "".to_string(), "NN -> C".to_string()];
+    let parsed = Day14.parse(&inputs).unwrap();
+    let (pair_counts, start) = even_faster_expand_iter(&parsed.0, &rules_as_map(&parsed.1), 40);
+    assert_eq!(elem_counts_from_pairs(&pair_counts, &start), HashMap::from([("N".to_string(), 1)]));
+    assert_eq!(Day14.part_one(&parsed).unwrap().value, Value::from(0));
+    assert_eq!(Day14.part_two(&parsed).unwrap().value, Value::from(0));
+}
+
+#[test]
+fn test_expansions_agree() {
+    const ELEMENTS: [&str; 4] = ["B", "C", "H", "N"];
+
+    check_property(200, |rng| {
+        let template = (0..rng.between(1, 8)).map(|_| rng.choose(&ELEMENTS).to_string()).collect::<Vec<_>>();
+        let mut rules = Vec::new();
+        for first in ELEMENTS {
+            for second in ELEMENTS {
+                // Leave out some of the rules, so not every pair grows
+                if !rng.one_in(4) {
+                    let to_insert = *rng.choose(&ELEMENTS);
+                    rules.push(InsertRule::new(first, second, to_insert));
+                }
+            }
+        }
+        let rules_map = rules_as_map(&rules);
+        let steps = rng.between(0, 6);
+
+        let mut expanded = template.clone();
+        let mut fast_expanded = template.clone();
+        for _ in 0..steps {
+            expand_polymer(&mut expanded, &rules);
+            fast_expand(&mut fast_expanded, &rules_map);
+        }
+        if expanded != fast_expanded {
+            return Err(format!(
+                "{} became {} with expand_polymer, but {} with fast_expand",
+                template.concat(), expanded.concat(), fast_expanded.concat()
+            ));
+        }
+
+        let (pair_counts, start) = even_faster_expand_iter(&template, &rules_map, steps);
+        let counted = min_max_from_pairs(&pair_counts, start);
+        match count_elems(&expanded) == counted {
+            true => Ok(()),
+            false => Err(format!(
+                "{} became {}, with min and max {:?}, but even_faster_expand counts {:?}",
+                template.concat(), expanded.concat(), count_elems(&expanded), counted
+            )),
+        }
+    });
+}
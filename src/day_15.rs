@@ -1,155 +1,102 @@
-use petgraph::{Directed, Graph};
-use petgraph::algo::{all_simple_paths, astar};
-use petgraph::graph::NodeIndex;
-use advent_of_code_2021::{find_node, read_lines};
-
-pub(crate) fn run() {
-    {
-        let inputs = read_lines("data/day_15_input.txt");
-        let grid = parse_grid(&inputs);
-        let graph = parse_graph(&grid);
-
-        let start_node = find_node(&graph, &(0, 0)).unwrap();
-        let last_row_idx = grid.len() - 1;
-        let last_col_idx = grid[0].len() - 1;
-        println!("The map is {} rows by {} cols", last_row_idx + 1, last_col_idx + 1);
-
-        let finish_node = find_node(&graph, &(last_row_idx, last_col_idx)).unwrap();
-        let cheapest = astar(
-            &graph, start_node,
-            |n| n == finish_node,
-            |e| *e.weight(),
-            |_| 0,
-        ).unwrap();
-
-        println!("The cheapest route costs {}", cheapest.0);
-    }
+use petgraph::algo::astar;
+use crate::{parse_digit_grid, Answer, Coordinate, Grid, GridGraph, Neighbourhood, ParseError, Solution};
+#[cfg(test)]
+use crate::read_lines;
 
-    {
-        let inputs = read_lines("data/day_15_input.txt");
-        let grid = parse_grid(&inputs);
-        let expanded = expand_grid(&grid);
-        let full_map_graph = parse_graph(&expanded);
+pub struct Day15;
 
-        let start_node = find_node(&full_map_graph, &(0, 0)).unwrap();
-        let last_row_idx = expanded.len() - 1;
-        let last_col_idx = expanded[0].len() - 1;
-        println!("The full map is {} rows by {} cols", last_row_idx + 1, last_col_idx + 1);
+impl Solution for Day15 {
+    const DAY: usize = 15;
+    type Parsed = Grid<usize>;
 
-        let finish_node = find_node(&full_map_graph, &(last_row_idx, last_col_idx)).unwrap();
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        parse_grid(inputs)
+    }
 
-        let cheapest = astar(
-            &full_map_graph, start_node,
-            |n| n == finish_node,
-            |e| *e.weight(),
-            |_| 0,
-        ).unwrap();
+    fn part_one(&self, grid: &Self::Parsed) -> Result<Answer, ParseError> {
+        let graph = parse_graph(grid);
+        let cheapest = cheapest_route(&graph, grid);
 
-        println!("The cheapest route for the entire map costs {}", cheapest.0);
+        Ok(Answer::new(cheapest, format!(
+            "The map is {} rows by {} cols\nThe cheapest route costs {}",
+            grid.height(), grid.width(), cheapest
+        )))
     }
-}
-
-pub(crate) fn parse_grid(inputs: &Vec<String>) -> Vec<Vec<usize>> {
-    inputs.iter()
-        .map(|line| {
-            line.chars()
-                .map(|c| c.to_string().parse::<usize>().unwrap())
-                .collect::<Vec<usize>>()
-        })
-        .collect()
-}
 
-pub(crate) fn parse_graph(grid: &Vec<Vec<usize>>) -> Graph<(usize, usize), usize, Directed>{
-    let mut graph = Graph::new();
+    fn part_two(&self, grid: &Self::Parsed) -> Result<Answer, ParseError> {
+        let expanded = expand_grid(grid);
+        let full_map_graph = parse_graph(&expanded);
+        let cheapest = cheapest_route(&full_map_graph, &expanded);
 
-    for row_idx in 0..grid.len() {
-        for col_idx in 0..grid[1].len() {
-            let from_node_idx;
+        Ok(Answer::new(cheapest, format!(
+            "The full map is {} rows by {} cols\nThe cheapest route for the entire map costs {}",
+            expanded.height(), expanded.width(), cheapest
+        )))
+    }
+}
 
-            if let Some(n) = find_node(&graph, &(row_idx, col_idx)) {
-                from_node_idx = n;
-            } else {
-                from_node_idx = graph.add_node((row_idx, col_idx))
-            }
+/// Finds the total risk of the cheapest route from the top left to the bottom right of the grid
+pub fn cheapest_route(graph: &RiskGraph, grid: &Grid<usize>) -> usize {
+    cheapest_path(graph, grid).0
+}
 
-            if row_idx > 0 {
-                add_edge_to_above(grid, &mut graph, from_node_idx);
-            }
+/// Finds the cheapest route from the top left to the bottom right of the grid, as its total risk
+/// along with the positions it passes
+pub fn cheapest_path(graph: &RiskGraph, grid: &Grid<usize>) -> (usize, Vec<Coordinate>) {
+    let start_node = graph.node((0, 0)).unwrap();
+    let last_row_idx = grid.height() - 1;
+    let last_col_idx = grid.width() - 1;
+    let finish_node = graph.node((last_row_idx, last_col_idx)).unwrap();
 
-            if col_idx > 0 {
-                add_edge_to_left(grid, &mut graph, from_node_idx);
-            }
-        }
-    }
+    let (cheapest, path) = astar(
+        graph.graph(), start_node,
+        |n| n == finish_node,
+        |e| *e.weight(),
+        |_| 0,
+    ).unwrap();
 
-    graph
+    (cheapest, path.into_iter().map(|node| graph.graph()[node]).collect())
 }
 
-// Add edge to entry above
-fn add_edge_to_above(
-    grid: &Vec<Vec<usize>>,
-    graph: &mut Graph<(usize, usize), usize, Directed>,
-    from_node_idx: NodeIndex
-) {
-    let (row_idx, col_idx) = graph[from_node_idx];
-    let row_above_idx = row_idx - 1;
-    let risk_to_above = grid[row_above_idx][col_idx];
-    let risk_from_above = grid[row_idx][col_idx];
-    let node_above_idx;
-
-    if let Some(n) = find_node(&graph, &(row_above_idx, col_idx)) {
-        node_above_idx = n;
-    } else {
-        node_above_idx = graph.add_node((row_above_idx, col_idx))
-    }
-
-    graph.add_edge(from_node_idx, node_above_idx, risk_to_above);
-    graph.add_edge(node_above_idx, from_node_idx, risk_from_above);
+pub fn parse_grid(inputs: &[String]) -> Result<Grid<usize>, ParseError> {
+    parse_digit_grid(inputs)
 }
 
-// Add edge to entry to the left
-pub(crate) fn add_edge_to_left(grid: &Vec<Vec<usize>>, graph: &mut Graph<(usize, usize), usize, Directed>, from_node_idx: NodeIndex) {
-    let (row_idx, col_idx) = graph[from_node_idx];
-    let col_to_left_idx = col_idx - 1;
-    let risk_to_left = grid[row_idx][col_to_left_idx];
-    let risk_from_left = grid[row_idx][col_idx];
-    let node_to_left;
-
-    if let Some(n) = find_node(&graph, &(row_idx, col_to_left_idx)) {
-        node_to_left = n;
-    } else {
-        node_to_left = graph.add_node((row_idx, col_to_left_idx))
-    }
+/// A graph of the positions on the map, where entering a position costs its risk
+pub type RiskGraph = GridGraph<Coordinate, usize>;
 
-    graph.add_edge(from_node_idx, node_to_left, risk_to_left);
-    graph.add_edge(node_to_left, from_node_idx, risk_from_left);
+pub fn parse_graph(grid: &Grid<usize>) -> RiskGraph {
+    GridGraph::from_grid(
+        grid,
+        Neighbourhood::VonNeumann,
+        |coordinate, _| coordinate,
+        |_, (_, risk)| Some(*risk),
+    )
 }
 
-pub(crate) fn expand_grid(grid: &Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+pub fn expand_grid(grid: &Grid<usize>) -> Grid<usize> {
     // Initialize as zeroes
-    let mut expanded = vec![vec![0; grid[0].len() * 5]; grid.len() * 5];
+    let mut expanded = Grid::new(grid.width() * 5, grid.height() * 5, 0);
 
     for expand_hor in 0..5 {
         for expand_ver in 0..5 {
-            fill_increase(&mut expanded, &grid, expand_hor, expand_ver);
+            fill_increase(&mut expanded, grid, expand_hor, expand_ver);
         }
     }
 
     expanded
 }
 
-pub(crate) fn fill_increase(expanded: &mut Vec<Vec<usize>>, base: &Vec<Vec<usize>>, hor: usize, ver: usize) {
-    let col_offset = base[0].len() * ver;
-    let row_offset = base.len() * hor;
+pub fn fill_increase(expanded: &mut Grid<usize>, base: &Grid<usize>, hor: usize, ver: usize) {
+    let col_offset = base.width() * ver;
+    let row_offset = base.height() * hor;
     let increase = hor + ver;
 
-    for (row_idx, row) in base.iter().enumerate() {
-        for (col_idx, entry) in row.iter().enumerate() {
-            let mut increased = *entry + increase;
-            if increased > 9 { increased -= 9; }
+    for ((row_idx, col_idx), entry) in base.iter() {
+        let mut increased = *entry + increase;
+        if increased > 9 { increased -= 9; }
 
-            expanded[row_idx + row_offset][col_idx + col_offset] = increased;
-        }
+        expanded[(row_idx + row_offset, col_idx + col_offset)] = increased;
     }
 
 }
@@ -157,49 +104,49 @@ pub(crate) fn fill_increase(expanded: &mut Vec<Vec<usize>>, base: &Vec<Vec<usize
 #[cfg(test)]
 #[test]
 fn test_parse_grid() {
-    let inputs = read_lines("data/day_15_sample.txt");
-    let grid = parse_grid(&inputs);
-    assert_eq!(grid.len(), 10);
-    assert_eq!(grid[0].len(), 10);
+    let inputs = read_lines("data/day_15_sample.txt").unwrap();
+    let grid = parse_grid(&inputs).unwrap();
+    assert_eq!(grid.height(), 10);
+    assert_eq!(grid.width(), 10);
 }
 
 #[test]
 fn test_parse_graph() {
-    let inputs = read_lines("data/day_15_sample.txt");
-    let grid = parse_grid(&inputs);
+    let inputs = read_lines("data/day_15_sample.txt").unwrap();
+    let grid = parse_grid(&inputs).unwrap();
     let graph = parse_graph(&grid);
 
-    let from = find_node(&graph, &(1, 1)).unwrap();
-    let to = find_node(&graph, &(0, 0)).unwrap();
-    assert_eq!(graph.contains_edge(from, to), false);
+    let from = graph.node((1, 1)).unwrap();
+    let to = graph.node((0, 0)).unwrap();
+    assert!(!graph.graph().contains_edge(from, to));
 
-    let from = find_node(&graph, &(0, 1)).unwrap();
-    let to = find_node(&graph, &(0, 0)).unwrap();
-    assert_eq!(graph.contains_edge(from, to), true);
+    let from = graph.node((0, 1)).unwrap();
+    let to = graph.node((0, 0)).unwrap();
+    assert!(graph.graph().contains_edge(from, to));
 
-    let from = find_node(&graph, &(1, 0)).unwrap();
-    let to = find_node(&graph, &(0, 0)).unwrap();
-    assert_eq!(graph.contains_edge(from, to), true);
+    let from = graph.node((1, 0)).unwrap();
+    let to = graph.node((0, 0)).unwrap();
+    assert!(graph.graph().contains_edge(from, to));
 
-    let from = find_node(&graph, &(0, 0)).unwrap();
-    let to = find_node(&graph, &(0, 1)).unwrap();
-    assert_eq!(graph.contains_edge(from, to), true);
+    let from = graph.node((0, 0)).unwrap();
+    let to = graph.node((0, 1)).unwrap();
+    assert!(graph.graph().contains_edge(from, to));
 
-    let from = find_node(&graph, &(0, 0)).unwrap();
-    let to = find_node(&graph, &(1, 0)).unwrap();
-    assert_eq!(graph.contains_edge(from, to), true);
+    let from = graph.node((0, 0)).unwrap();
+    let to = graph.node((1, 0)).unwrap();
+    assert!(graph.graph().contains_edge(from, to));
 }
 
 #[test]
 fn test_cheapest_path() {
-    let inputs = read_lines("data/day_15_sample.txt");
-    let grid = parse_grid(&inputs);
+    let inputs = read_lines("data/day_15_sample.txt").unwrap();
+    let grid = parse_grid(&inputs).unwrap();
     let graph = parse_graph(&grid);
 
-    let start_node = find_node(&graph, &(0, 0)).unwrap();
-    let finish_node = find_node(&graph, &(9, 9)).unwrap();
+    let start_node = graph.node((0, 0)).unwrap();
+    let finish_node = graph.node((9, 9)).unwrap();
     let cheapest = astar(
-        &graph, start_node,
+        graph.graph(), start_node,
         |n| n == finish_node,
         |e| *e.weight(),
         |_| 0
@@ -207,40 +154,48 @@ fn test_cheapest_path() {
     println!("cheapest: {:?}", cheapest);
     println!("Route:");
     for node in cheapest.1 {
-        print!("{:?} ", graph[node], )
+        print!("{:?} ", graph.graph()[node], )
     }
     assert_eq!(cheapest.0, 40);
 }
 
 #[test]
 fn test_expand_grid() {
-    let inputs = read_lines("data/day_15_sample.txt");
-    let grid = parse_grid(&inputs);
+    let inputs = read_lines("data/day_15_sample.txt").unwrap();
+    let grid = parse_grid(&inputs).unwrap();
     let expanded = expand_grid(&grid);
     let graph = parse_graph(&expanded);
 
-    let grid_rows = grid.len();
-    let grid_cols = grid[0].len();
+    let grid_rows = grid.height();
+    let grid_cols = grid.width();
 
-    assert_eq!(expanded.len(), grid_rows * 5);
-    assert_eq!(expanded[0].len(), grid_cols * 5);
+    assert_eq!(expanded.height(), grid_rows * 5);
+    assert_eq!(expanded.width(), grid_cols * 5);
 
-    let expected_inputs = read_lines("data/day_15_expanded_sample.txt");
-    let expected_grid = parse_grid(&expected_inputs);
-    // for row_idx in 0..expanded.len() {
-    //     println!("e{} {:?}", row_idx, expected_grid[row_idx]);
-    //     println!("a{} {:?}", row_idx, expanded[row_idx]);
-    //     assert_eq!(expected_grid[row_idx], expanded[row_idx]);
-    // }
+    let expected_inputs = read_lines("data/day_15_expanded_sample.txt").unwrap();
+    let expected_grid = parse_grid(&expected_inputs).unwrap();
     assert_eq!(expanded, expected_grid);
 
-    let start_node = find_node(&graph, &(0, 0)).unwrap();
-    let finish_node = find_node(&graph, &(49, 49)).unwrap();
+    let start_node = graph.node((0, 0)).unwrap();
+    let finish_node = graph.node((49, 49)).unwrap();
     let cheapest = astar(
-        &graph, start_node,
+        graph.graph(), start_node,
         |n| n == finish_node,
         |e| *e.weight(),
         |_| 0
     ).unwrap();
     assert_eq!(cheapest.0, 315);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_cheapest_path_positions() {
+    let inputs = read_lines("data/day_15_sample.txt").unwrap();
+    let grid = parse_grid(&inputs).unwrap();
+    let graph = parse_graph(&grid);
+
+    let (cheapest, path) = cheapest_path(&graph, &grid);
+    assert_eq!(cheapest, 40);
+    assert_eq!(path.first(), Some(&(0, 0)));
+    assert_eq!(path.last(), Some(&(9, 9)));
+    assert_eq!(path.iter().skip(1).map(|&position| grid[position]).sum::<usize>(), 40);
+}
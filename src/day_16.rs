@@ -1,27 +1,392 @@
-use advent_of_code_2021::read_lines;
+use std::convert::TryFrom;
+use crate::{Answer, ParseError, Solution};
+#[cfg(test)]
+use crate::read_lines;
+
+pub struct Day16;
+
+/// How many operator packets can nest inside each other, which keeps decoding and evaluating
+/// them from running out of stack
+pub const MAX_DEPTH: usize = 100;
+
+impl Solution for Day16 {
+    const DAY: usize = 16;
+    type Parsed = Vec<Packet>;
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        let transmission = inputs
+            .first()
+            .ok_or_else(|| ParseError::input("the input is empty, expected a hexadecimal transmission"))?;
+        parse_packets(transmission)
+    }
+
+    fn part_one(&self, packets: &Self::Parsed) -> Result<Answer, ParseError> {
+        let sum_versions: usize = packets.iter().map(|p| p.version).sum();
+        Ok(Answer::new(sum_versions, format!("The sum of all packet versions is {}", sum_versions)))
+    }
+
+    fn part_two(&self, packets: &Self::Parsed) -> Result<Answer, ParseError> {
+        // The outermost packet comes first, and contains all the others
+        let value = evaluate(&packets[0])?;
+        let value = i64::try_from(value)
+            .map_err(|_| ParseError::input(format!("the transmission evaluates to {}, which is too big an answer", value)))?;
+        Ok(Answer::new(value, format!("The transmission evaluates to {}", value)))
+    }
+}
+
+/// What an operator packet does with the values of its sub-packets
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operator {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    GreaterThan,
+    LessThan,
+    EqualTo,
+}
+
+impl Operator {
+    /// The operator of a packet type ID, which is every type ID but the 4 of literal values
+    pub fn from_type_id(type_id: usize) -> Option<Self> {
+        match type_id {
+            0 => Some(Operator::Sum),
+            1 => Some(Operator::Product),
+            2 => Some(Operator::Minimum),
+            3 => Some(Operator::Maximum),
+            5 => Some(Operator::GreaterThan),
+            6 => Some(Operator::LessThan),
+            7 => Some(Operator::EqualTo),
+            _ => None,
+        }
+    }
 
-pub(crate) fn run() {
+    pub fn type_id(self) -> usize {
+        match self {
+            Operator::Sum => 0,
+            Operator::Product => 1,
+            Operator::Minimum => 2,
+            Operator::Maximum => 3,
+            Operator::GreaterThan => 5,
+            Operator::LessThan => 6,
+            Operator::EqualTo => 7,
+        }
+    }
 
+    /// Comparisons take exactly two sub-packets, the other operators at least one
+    fn takes(self, sub_packet_count: usize) -> bool {
+        match self {
+            Operator::GreaterThan | Operator::LessThan | Operator::EqualTo => sub_packet_count == 2,
+            _ => sub_packet_count > 0,
+        }
+    }
 }
 
-pub(crate) struct Packet {
+#[derive(Clone, Debug, PartialEq)]
+pub enum Contents {
+    Literal(usize),
+    Operator(Operator, Vec<Packet>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Packet {
     version: usize,
+    contents: Contents,
+}
+
+impl Packet {
+    /// Makes a packet, as long as its operator can take the number of sub-packets it's given
+    pub fn new(version: usize, contents: Contents) -> Result<Self, ParseError> {
+        check_sub_packet_count(&contents).map_err(ParseError::input)?;
+        Ok(Packet { version, contents })
+    }
+
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    pub fn type_id(&self) -> usize {
+        match &self.contents {
+            Contents::Literal(_) => 4,
+            Contents::Operator(operator, _) => operator.type_id(),
+        }
+    }
+
+    pub fn contents(&self) -> &Contents {
+        &self.contents
+    }
+}
+
+fn check_sub_packet_count(contents: &Contents) -> Result<(), String> {
+    match contents {
+        Contents::Operator(operator, sub_packets) if !operator.takes(sub_packets.len()) => Err(format!(
+            "operator type {} can't take {} sub-packets", operator.type_id(), sub_packets.len()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the bits of a transmission front to back
+struct BitReader {
+    bits: Vec<u8>,
+    position: usize,
 }
 
-pub(crate) fn parse_packets(input: &String) -> Vec<Packet>{
-    let packets = Vec::new();
-    let bytes = hex::decode(input).unwrap();
-    println!("{:?}", bytes);
+impl BitReader {
+    fn from_hex(input: &str) -> Result<BitReader, ParseError> {
+        if !input.len().is_multiple_of(2) {
+            return Err(ParseError::at(0, input.len(), "expected an even number of hexadecimal digits"));
+        }
+
+        let digits = input
+            .chars()
+            .enumerate()
+            .map(|(column, c)| {
+                c.to_digit(16)
+                    .ok_or_else(|| ParseError::at(0, column, format!("expected a hexadecimal digit, found `{}`", c)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let bits = digits
+            .iter()
+            .flat_map(|digit| (0..4).rev().map(move |shift| ((digit >> shift) & 1) as u8))
+            .collect();
+
+        Ok(BitReader { bits, position: 0 })
+    }
+
+    fn read(&mut self, amount: usize) -> Result<usize, ParseError> {
+        if self.position + amount > self.bits.len() {
+            // Four bits to a hexadecimal digit
+            return Err(ParseError::at(0, self.position / 4, "the transmission ends in the middle of a packet"));
+        }
+
+        let mut number = 0;
+
+        for bit in &self.bits[self.position..self.position + amount] {
+            number = (number << 1) | *bit as usize;
+        }
+
+        self.position += amount;
+        Ok(number)
+    }
+}
+
+/// Parses the outermost packet of a hexadecimal transmission, including all its sub-packets
+pub fn parse_transmission(input: &str) -> Result<Packet, ParseError> {
+    let mut reader = BitReader::from_hex(input)?;
+    read_packet(&mut reader, 0)
+}
+
+/// Parses a hexadecimal transmission into a flat list of all the packets in it, outermost first
+pub fn parse_packets(input: &str) -> Result<Vec<Packet>, ParseError> {
+    Ok(flatten_packets(&parse_transmission(input)?))
+}
+
+pub fn flatten_packets(packet: &Packet) -> Vec<Packet> {
+    let mut packets = vec![packet.clone()];
+
+    if let Contents::Operator(_, sub_packets) = &packet.contents {
+        for sub_packet in sub_packets {
+            packets.extend(flatten_packets(sub_packet));
+        }
+    }
 
     packets
 }
 
+fn read_packet(reader: &mut BitReader, depth: usize) -> Result<Packet, ParseError> {
+    let start = reader.position;
+    let version = reader.read(3)?;
+    let type_id = reader.read(3)?;
+
+    // Type 4 is a literal value, made up of groups of four bits prefixed by a continuation bit
+    if type_id == 4 {
+        let mut value: usize = 0;
+
+        loop {
+            let is_last_group = reader.read(1)? == 0;
+            if value.leading_zeros() < 4 {
+                return Err(ParseError::at(0, start / 4, "literal value doesn't fit in 64 bits"));
+            }
+            value = (value << 4) | reader.read(4)?;
+            if is_last_group { break; }
+        }
+
+        return Ok(Packet { version, contents: Contents::Literal(value) });
+    }
+
+    // Anything else is an operator, with a length type ID telling how its sub-packets are counted
+    let operator = Operator::from_type_id(type_id)
+        .ok_or_else(|| ParseError::at(0, start / 4, format!("packet type {} doesn't exist", type_id)))?;
+    if depth == MAX_DEPTH {
+        return Err(ParseError::at(0, start / 4, format!("operators can't nest more than {} deep", MAX_DEPTH)));
+    }
+    let mut sub_packets = Vec::new();
+
+    if reader.read(1)? == 0 {
+        let bit_length = reader.read(15)?;
+        let end = reader.position + bit_length;
+
+        while reader.position < end {
+            sub_packets.push(read_packet(reader, depth + 1)?);
+        }
+    } else {
+        let packet_count = reader.read(11)?;
+
+        for _ in 0..packet_count {
+            sub_packets.push(read_packet(reader, depth + 1)?);
+        }
+    }
+
+    let contents = Contents::Operator(operator, sub_packets);
+    check_sub_packet_count(&contents).map_err(|e| ParseError::at(0, start / 4, e))?;
+
+    Ok(Packet { version, contents })
+}
+
+/// Calculates the value of a packet by applying its operator to the values of its sub-packets,
+/// unless a sum or product gets too big for 64 bits
+pub fn evaluate(packet: &Packet) -> Result<usize, ParseError> {
+    let (operator, sub_packets) = match &packet.contents {
+        Contents::Literal(value) => return Ok(*value),
+        Contents::Operator(operator, sub_packets) => (*operator, sub_packets),
+    };
+
+    // Packets always have as many sub-packets as their operator takes, see `Operator::takes`
+    let values = sub_packets.iter().map(evaluate).collect::<Result<Vec<_>, _>>()?;
+    let mut values = values.into_iter();
+    let overflow = |operation| ParseError::input(format!("the {} of the sub-packets doesn't fit in 64 bits", operation));
+
+    match operator {
+        Operator::Sum => values.try_fold(0_usize, usize::checked_add).ok_or_else(|| overflow("sum")),
+        Operator::Product => values.try_fold(1_usize, usize::checked_mul).ok_or_else(|| overflow("product")),
+        Operator::Minimum => Ok(values.fold(usize::MAX, usize::min)),
+        Operator::Maximum => Ok(values.fold(0, usize::max)),
+        Operator::GreaterThan => Ok((values.next() > values.next()) as usize),
+        Operator::LessThan => Ok((values.next() < values.next()) as usize),
+        Operator::EqualTo => Ok((values.next() == values.next()) as usize),
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn test_parse_shortest() {
-    let input = read_lines("data/day_16_shortest_sample.txt");
-    let packets = parse_packets(&input[0]);
+    let input = read_lines("data/day_16_shortest_sample.txt").unwrap();
+    let packets = parse_packets(&input[0]).unwrap();
 
     let sum_versions: usize = packets.iter().map(|p| p.version).sum();
     assert_eq!(sum_versions, 16);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_parse_literal() {
+    let packet = parse_transmission("D2FE28").unwrap();
+    assert_eq!(packet, Packet::new(6, Contents::Literal(2021)).unwrap());
+    assert_eq!(packet.type_id(), 4);
+}
+
+#[test]
+fn test_sum_versions_longer_samples() {
+    let expected_sums = vec![
+        ("data/day_16_short_sample.txt", 12),
+        ("data/day_16_longer_sample.txt", 23),
+        ("data/day_16_longest_sample.txt", 31),
+    ];
+
+    for (sample, expected_sum) in expected_sums {
+        let input = read_lines(sample).unwrap();
+        let packets = parse_packets(&input[0]).unwrap();
+
+        let sum_versions: usize = packets.iter().map(|p| p.version).sum();
+        assert_eq!(sum_versions, expected_sum);
+    }
+}
+
+#[test]
+fn test_evaluate() {
+    assert_eq!(evaluate(&parse_transmission("C200B40A82").unwrap()), Ok(3));
+    assert_eq!(evaluate(&parse_transmission("04005AC33890").unwrap()), Ok(54));
+    assert_eq!(evaluate(&parse_transmission("880086C3E88112").unwrap()), Ok(7));
+    assert_eq!(evaluate(&parse_transmission("CE00C43D881120").unwrap()), Ok(9));
+    assert_eq!(evaluate(&parse_transmission("D8005AC2A8F0").unwrap()), Ok(1));
+    assert_eq!(evaluate(&parse_transmission("F600BC2D8F").unwrap()), Ok(0));
+    assert_eq!(evaluate(&parse_transmission("9C005AC2A8F0").unwrap()), Ok(0));
+    assert_eq!(evaluate(&parse_transmission("9C0141080250320F1802104A08").unwrap()), Ok(1));
+}
+
+#[test]
+fn test_parse_malformed_transmission() {
+    let error = parse_transmission("D2FG28").unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 4: expected a hexadecimal digit, found `G`");
+
+    let error = parse_transmission("D2FE2").unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 6: expected an even number of hexadecimal digits");
+
+    // A literal cut off after its first group of bits
+    let error = parse_transmission("D2").unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 2: the transmission ends in the middle of a packet");
+
+    // A minimum operator counting zero sub-packets in 11 bits
+    let error = parse_transmission("4A0000").unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 1: operator type 2 can't take 0 sub-packets");
+
+    // Literals of 2^64 - 1 and 2^64
+    assert_eq!(parse_transmission("13FFFFFFFFFFFFFFFFFFBC").unwrap(), Packet::new(0, Contents::Literal(usize::MAX)).unwrap());
+    let error = parse_transmission("123084210842108421084000").unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 1: literal value doesn't fit in 64 bits");
+}
+
+#[test]
+fn test_nesting_depth() {
+    // Sums of a single sub-packet, counted in 11 bits, around a literal 7
+    let nested = |depth: usize| {
+        let mut bits = "000000100000000001".repeat(depth) + "00010000111";
+        while !bits.len().is_multiple_of(8) {
+            bits.push('0');
+        }
+        bits.as_bytes()
+            .chunks(4)
+            .map(|nibble| format!("{:X}", u8::from_str_radix(std::str::from_utf8(nibble).unwrap(), 2).unwrap()))
+            .collect::<String>()
+    };
+
+    let packet = parse_transmission(&nested(MAX_DEPTH)).unwrap();
+    assert_eq!(evaluate(&packet), Ok(7));
+    assert_eq!(flatten_packets(&packet).len(), MAX_DEPTH + 1);
+
+    // The innermost operator starts 18 bits further along for each operator around it
+    let error = parse_transmission(&nested(MAX_DEPTH + 1)).unwrap_err();
+    assert_eq!(error.to_string(), format!("line 1, column {}: operators can't nest more than 100 deep", MAX_DEPTH * 18 / 4 + 1));
+    assert!(parse_transmission(&nested(100_000)).is_err());
+}
+
+#[test]
+fn test_evaluate_overflow() {
+    let literal = |value| Packet::new(0, Contents::Literal(value)).unwrap();
+    let operation = |operator, value| Packet::new(0, Contents::Operator(operator, vec![literal(value), literal(value)])).unwrap();
+
+    assert_eq!(evaluate(&operation(Operator::Product, 1 << 31)), Ok(1 << 62));
+    let error = evaluate(&operation(Operator::Product, 1 << 40)).unwrap_err();
+    assert_eq!(error.to_string(), "the product of the sub-packets doesn't fit in 64 bits");
+    let error = evaluate(&operation(Operator::Sum, usize::MAX)).unwrap_err();
+    assert_eq!(error.to_string(), "the sum of the sub-packets doesn't fit in 64 bits");
+
+    // A literal of 2^63 + 5 fits in 64 bits, but not in an answer
+    let packets = Day16.parse(&["1310842108421084210814".to_string()]).unwrap();
+    let error = Day16.part_two(&packets).unwrap_err();
+    assert_eq!(error.to_string(), "the transmission evaluates to 9223372036854775813, which is too big an answer");
+}
+
+#[test]
+fn test_new_packet() {
+    let one = Packet::new(1, Contents::Literal(1)).unwrap();
+
+    let error = Packet::new(0, Contents::Operator(Operator::Maximum, Vec::new())).unwrap_err();
+    assert_eq!(error.to_string(), "operator type 3 can't take 0 sub-packets");
+    let error = Packet::new(0, Contents::Operator(Operator::EqualTo, vec![one.clone()])).unwrap_err();
+    assert_eq!(error.to_string(), "operator type 7 can't take 1 sub-packets");
+
+    let packet = Packet::new(0, Contents::Operator(Operator::Minimum, vec![one.clone(), one])).unwrap();
+    assert_eq!(evaluate(&packet), Ok(1));
+    assert_eq!(Operator::from_type_id(4), None);
+}
@@ -1,91 +1,191 @@
-use advent_of_code_2021::read_lines;
-
-pub(crate) fn run() {
-    let descriptions = read_lines("data/day_2_input.txt");
-    let movements = parse_movements(&descriptions);
-    let (horizontal, depth) = calculate_position(&movements);
-    println!("Horizontal position is at {}, depth at {} for part 1", horizontal, depth);
-    println!("These two multiplied is {} for part 1", horizontal * depth);
-
-    let (horizontal, depth) = calculate_position_with_aim(&movements);
-    println!("Horizontal position is at {}, depth at {} for part 2", horizontal, depth);
-    println!("These two multiplied is {} for part 2", horizontal * depth);
+use std::convert::TryFrom;
+use crate::{parse_number, split_pair, Answer, ParseError, Solution};
+#[cfg(test)]
+use crate::read_lines;
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: usize = 2;
+    type Parsed = Vec<Movement>;
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        parse_movements(inputs)
+    }
+
+    fn part_one(&self, movements: &Self::Parsed) -> Result<Answer, ParseError> {
+        let (horizontal, depth) = calculate_position(movements)?;
+        let product = multiply_position(horizontal, depth)?;
+        Ok(Answer::new(product, format!(
+            "Horizontal position is at {}, depth at {} for part 1\n\
+             These two multiplied is {} for part 1",
+            horizontal, depth, product
+        )))
+    }
+
+    fn part_two(&self, movements: &Self::Parsed) -> Result<Answer, ParseError> {
+        let (horizontal, depth) = calculate_position_with_aim(movements)?;
+        let product = multiply_position(horizontal, depth)?;
+        Ok(Answer::new(product, format!(
+            "Horizontal position is at {}, depth at {} for part 2\n\
+             These two multiplied is {} for part 2",
+            horizontal, depth, product
+        )))
+    }
 }
 
-pub(crate) struct Movement {
-    direction: String,
-    amount: usize,
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Up,
+    Down,
 }
 
-pub(crate) fn parse_movements(descriptions: &Vec<String>) -> Vec<Movement> {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Movement {
+    direction: Direction,
+    amount: i64,
+}
+
+impl Movement {
+    pub fn new(direction: Direction, amount: i64) -> Self {
+        Movement { direction, amount }
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn amount(&self) -> i64 {
+        self.amount
+    }
+}
+
+pub fn parse_movements(descriptions: &[String]) -> Result<Vec<Movement>, ParseError> {
     descriptions
         .iter()
-        .map(|descr| {
-            let parts: Vec<_> = descr.split(" ").collect();
-            let direction = parts[0];
-            let amount = parts[1];
-            Movement{
-                direction: String::from(direction),
-                amount: amount.parse::<usize>().unwrap() }
+        .enumerate()
+        .map(|(line_idx, descr)| {
+            let ((_, direction), (amount_column, amount)) = split_pair(descr, " ", line_idx)?;
+            let direction = match direction {
+                "forward" => Direction::Forward,
+                "up" => Direction::Up,
+                "down" => Direction::Down,
+                _ => return Err(ParseError::at(
+                    line_idx, 0, format!("expected forward, up or down, found `{}`", direction)
+                )),
+            };
+
+            let amount = parse_number::<u64>(amount, line_idx, amount_column)?;
+            let amount = i64::try_from(amount).map_err(|_| ParseError::at(
+                line_idx, amount_column, format!("expected an amount of at most {}, found {}", i64::MAX, amount)
+            ))?;
+
+            Ok(Movement::new(direction, amount))
         })
         .collect()
 }
 
-pub(crate) fn calculate_position(movements: &Vec<Movement>) -> (i32, i32) {
-    let mut horizontal: i32 = 0;
-    let mut depth: i32 = 0;
-
-    for movement in movements {
-        match movement.direction.as_str() {
-            "forward" => horizontal += movement.amount as i32,
-            "up" => depth -= movement.amount as i32,
-            "down" => depth += movement.amount as i32,
-            _ => { unreachable!("Direction was not recognized: {}", movement.direction)},
-        }
+pub fn calculate_position(movements: &[Movement]) -> Result<(i64, i64), ParseError> {
+    let mut horizontal: i64 = 0;
+    let mut depth: i64 = 0;
+
+    for (line_idx, movement) in movements.iter().enumerate() {
+        let moved = match movement.direction {
+            Direction::Forward => horizontal.checked_add(movement.amount).map(|h| horizontal = h),
+            Direction::Up => depth.checked_sub(movement.amount).map(|d| depth = d),
+            Direction::Down => depth.checked_add(movement.amount).map(|d| depth = d),
+        };
+        moved.ok_or_else(|| out_of_range(line_idx))?;
     }
 
-    (horizontal, depth)
+    Ok((horizontal, depth))
 }
 
-pub(crate) fn calculate_position_with_aim(movements: &Vec<Movement>) -> (i32, i32) {
-    let mut horizontal: i32 = 0;
-    let mut depth: i32 = 0;
-
-    let mut aim: i32 = 0;
-
-    for movement in movements {
-        match movement.direction.as_str() {
-            "forward" => {
-                horizontal += movement.amount as i32;
-                depth += aim * movement.amount as i32;
-            },
-            "up" => aim -= movement.amount as i32,
-            "down" => aim += movement.amount as i32,
-            _ => { unreachable!("Direction was not recognized: {}", movement.direction)},
-        }
+pub fn calculate_position_with_aim(movements: &[Movement]) -> Result<(i64, i64), ParseError> {
+    let mut horizontal: i64 = 0;
+    let mut depth: i64 = 0;
+
+    let mut aim: i64 = 0;
+
+    for (line_idx, movement) in movements.iter().enumerate() {
+        let moved = match movement.direction {
+            Direction::Forward => horizontal
+                .checked_add(movement.amount)
+                .zip(aim.checked_mul(movement.amount).and_then(|dive| depth.checked_add(dive)))
+                .map(|(h, d)| { horizontal = h; depth = d; }),
+            Direction::Up => aim.checked_sub(movement.amount).map(|a| aim = a),
+            Direction::Down => aim.checked_add(movement.amount).map(|a| aim = a),
+        };
+        moved.ok_or_else(|| out_of_range(line_idx))?;
     }
 
-    (horizontal, depth)
+    Ok((horizontal, depth))
+}
+
+fn out_of_range(line_idx: usize) -> ParseError {
+    ParseError::at(line_idx, 0, "this movement takes the submarine further than 64 bits can track")
+}
+
+fn multiply_position(horizontal: i64, depth: i64) -> Result<i64, ParseError> {
+    horizontal.checked_mul(depth).ok_or_else(|| ParseError::input(format!(
+        "the horizontal position {} times the depth {} doesn't fit in 64 bits", horizontal, depth
+    )))
 }
 
 #[cfg(test)]
 #[test]
 fn test_movement() {
-    let movement_descriptions = read_lines("data/day_2_sample.txt");
-    let movements = parse_movements(&movement_descriptions);
+    let movement_descriptions = read_lines("data/day_2_sample.txt").unwrap();
+    let movements = parse_movements(&movement_descriptions).unwrap();
     assert_eq!(movements.len(), 6);
+    assert_eq!(movements[0], Movement::new(Direction::Forward, 5));
 
-    let (horizontal, depth) = calculate_position(&movements);
+    let (horizontal, depth) = calculate_position(&movements).unwrap();
     assert_eq!(horizontal, 15);
     assert_eq!(depth, 10);
 }
 
 #[test]
 fn test_movement_with_aim() {
-    let movement_descriptions = read_lines("data/day_2_sample.txt");
-    let movements = parse_movements(&movement_descriptions);
+    let movement_descriptions = read_lines("data/day_2_sample.txt").unwrap();
+    let movements = parse_movements(&movement_descriptions).unwrap();
 
-    let (horizontal, depth) = calculate_position_with_aim(&movements);
+    let (horizontal, depth) = calculate_position_with_aim(&movements).unwrap();
     assert_eq!(horizontal, 15);
     assert_eq!(depth, 60);
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_malformed_movements() {
+    let descriptions = vec!["forward 5".to_string(), "backward 2".to_string()];
+    let error = parse_movements(&descriptions).unwrap_err();
+    assert_eq!(error.to_string(), "line 2, column 1: expected forward, up or down, found `backward`");
+
+    let descriptions = vec!["down x".to_string()];
+    let error = parse_movements(&descriptions).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 6: expected a number, found `x`");
+}
+
+#[test]
+fn test_movements_out_of_range() {
+    let descriptions = vec!["forward 18446744073709551615".to_string()];
+    let error = parse_movements(&descriptions).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 9: expected an amount of at most 9223372036854775807, found 18446744073709551615");
+
+    let descriptions = vec!["forward 9223372036854775807".to_string(), "forward 1".to_string()];
+    let movements = parse_movements(&descriptions).unwrap();
+    let error = calculate_position(&movements).unwrap_err();
+    assert_eq!(error.to_string(), "line 2, column 1: this movement takes the submarine further than 64 bits can track");
+
+    let descriptions = vec!["down 4294967296".to_string(), "forward 2147483648".to_string()];
+    let movements = parse_movements(&descriptions).unwrap();
+    let error = calculate_position_with_aim(&movements).unwrap_err();
+    assert_eq!(error.to_string(), "line 2, column 1: this movement takes the submarine further than 64 bits can track");
+
+    // The positions fit, but not what they multiply to
+    let descriptions = vec!["forward 2000000000".to_string(), "forward 2000000000".to_string(), "down 4000000000".to_string()];
+    let movements = parse_movements(&descriptions).unwrap();
+    assert_eq!(calculate_position(&movements).unwrap(), (4000000000, 4000000000));
+    let error = Day2.part_one(&movements).unwrap_err();
+    assert_eq!(error.to_string(), "the horizontal position 4000000000 times the depth 4000000000 doesn't fit in 64 bits");
+}
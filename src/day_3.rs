@@ -1,21 +1,69 @@
 use std::collections::HashMap;
-use advent_of_code_2021::read_lines;
+use crate::{Answer, ParseError, Solution};
+#[cfg(test)]
+use crate::read_lines;
 
-pub(crate) fn run() {
-    let inputs = read_lines("data/day_3_input.txt");
-    let (gamma, epsilon) = calculate_gamma_epsilon(&inputs);
-    println!("Gamma is {}, epsilon is {}", gamma, epsilon);
-    println!("These multiplied by each other is {}", gamma * epsilon);
+pub struct Day3;
 
-    let o2_entry = filter_o2_input(&inputs);
-    let co2_entry = filter_co2_input(&inputs);
-    let o2_rating = usize_from_binary_string(o2_entry);
-    let co2_rating = usize_from_binary_string(co2_entry);
-    println!("O2 rating is {}, CO2 rating is {}", &o2_rating, &co2_rating);
-    println!("These multiplied is {}", o2_rating * co2_rating);
+impl Solution for Day3 {
+    const DAY: usize = 3;
+    type Parsed = Vec<String>;
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        parse_diagnostics(inputs)
+    }
+
+    fn part_one(&self, inputs: &Self::Parsed) -> Result<Answer, ParseError> {
+        let (gamma, epsilon) = calculate_gamma_epsilon(inputs);
+        Ok(Answer::new(gamma * epsilon, format!(
+            "Gamma is {}, epsilon is {}\nThese multiplied by each other is {}",
+            gamma, epsilon, gamma * epsilon
+        )))
+    }
+
+    fn part_two(&self, inputs: &Self::Parsed) -> Result<Answer, ParseError> {
+        let o2_entry = filter_o2_input(inputs);
+        let co2_entry = filter_co2_input(inputs);
+        let o2_rating = usize_from_binary_string(o2_entry);
+        let co2_rating = usize_from_binary_string(co2_entry);
+        Ok(Answer::new(o2_rating * co2_rating, format!(
+            "O2 rating is {}, CO2 rating is {}\nThese multiplied is {}",
+            o2_rating, co2_rating, o2_rating * co2_rating
+        )))
+    }
+}
+
+/// The most bits a diagnostic number can have
+pub const MAX_BITS: usize = 32;
+
+/// Checks the diagnostic report is made up of binary numbers that are all equally long
+pub fn parse_diagnostics(inputs: &[String]) -> Result<Vec<String>, ParseError> {
+    let width = inputs
+        .first()
+        .map(|input| input.len())
+        .ok_or_else(|| ParseError::input("the input is empty, expected binary numbers"))?;
+
+    for (line_idx, input) in inputs.iter().enumerate() {
+        if let Some((column, c)) = input.chars().enumerate().find(|(_, c)| *c != '0' && *c != '1') {
+            return Err(ParseError::at(line_idx, column, format!("expected a 0 or 1, found `{}`", c)));
+        }
+
+        if input.len() != width || width == 0 {
+            return Err(ParseError::at(
+                line_idx, input.len(), format!("expected {} bits, found {}", width.max(1), input.len())
+            ));
+        }
+    }
+
+    // The gamma and epsilon rates multiplied by each other have to fit in a number
+    if width > MAX_BITS {
+        return Err(ParseError::at(0, MAX_BITS, format!("expected at most {} bits, found {}", MAX_BITS, width)));
+    }
+
+    Ok(inputs.to_vec())
 }
 
-pub(crate) fn calculate_gamma_epsilon(inputs: &Vec<String>) -> (usize, usize) {
+pub fn calculate_gamma_epsilon(inputs: &[String]) -> (usize, usize) {
     let mut gamma = 0;
     let mut epsilon= 0;
     let mut bit_counts = bits_column_sum(inputs);
@@ -27,16 +75,16 @@ pub(crate) fn calculate_gamma_epsilon(inputs: &Vec<String>) -> (usize, usize) {
 
     for (idx, count) in bit_counts.iter().enumerate() {
         if count > half_of_inputs {
-            gamma += 2_i32.pow(idx as u32);
+            gamma += 1_usize << idx;
         } else {
-            epsilon += 2_i32.pow(idx as u32);
+            epsilon += 1_usize << idx;
         };
     }
 
-    (gamma as usize, epsilon as usize)
+    (gamma, epsilon)
 }
 
-pub(crate) fn bits_column_sum(inputs: &Vec<String>) -> Vec<usize> {
+pub fn bits_column_sum(inputs: &[String]) -> Vec<usize> {
     let mut bit_counts: Vec::<usize> = vec![0; inputs[0].len()];
 
     for input in inputs {
@@ -47,11 +95,11 @@ pub(crate) fn bits_column_sum(inputs: &Vec<String>) -> Vec<usize> {
     bit_counts
 }
 
-fn filter_o2_input(inputs: &Vec<String>) -> String {
+fn filter_o2_input(inputs: &[String]) -> String {
     let mut input_map = hashmap_from_inputs(inputs);
 
     let mut correct_input_to_return = "".to_string();
-    let input_0_len = inputs.get(0).unwrap().len();
+    let input_0_len = inputs.first().unwrap().len();
 
     // advance one position in the input length a time to filter values
     for pos in 0..input_0_len {
@@ -71,7 +119,7 @@ fn filter_o2_input(inputs: &Vec<String>) -> String {
         // );
 
         filter_to_last_at_pos(&mut input_map, &mut correct_input_to_return, &pos, &most_common_at_pos);
-        if correct_input_to_return != "".to_string() {
+        if !correct_input_to_return.is_empty() {
             break;
         }
     }
@@ -117,10 +165,10 @@ fn filter_to_last_at_pos(
     }
 }
 
-pub(crate) fn filter_co2_input(inputs: &Vec<String>) -> String {
+pub fn filter_co2_input(inputs: &[String]) -> String {
     let mut input_map = hashmap_from_inputs(inputs);
     let mut correct_input_to_return = "".to_string();
-    let input_0_len = inputs.get(0).unwrap().len();
+    let input_0_len = inputs.first().unwrap().len();
 
     // advance one position in the input length a time to filter values
     for pos in 0..input_0_len {
@@ -145,7 +193,7 @@ pub(crate) fn filter_co2_input(inputs: &Vec<String>) -> String {
     correct_input_to_return
 }
 
-pub(crate) fn hashmap_from_inputs(inputs: &Vec<String>) -> HashMap<&String, Vec<usize>> {
+pub fn hashmap_from_inputs(inputs: &[String]) -> HashMap<&String, Vec<usize>> {
     let mut input_map = HashMap::new();
 
     // Create hashmap for easier removal manipulation
@@ -159,12 +207,12 @@ pub(crate) fn hashmap_from_inputs(inputs: &Vec<String>) -> HashMap<&String, Vec<
     input_map
 }
 
-fn most_common_bit_for_pos(inputs: &Vec<String>, position: usize) -> usize {
-    let half_of_inputs= inputs.len() as f32 / 2 as f32;
+fn most_common_bit_for_pos(inputs: &[String], position: usize) -> usize {
+    let half_of_inputs= inputs.len() as f32 / 2_f32;
     let bit_counts = bits_column_sum(inputs);
     let count_for_position = bit_counts.get(position).unwrap();
 
-    if *count_for_position as f32 >= *&half_of_inputs {
+    if *count_for_position as f32 >= half_of_inputs {
         // println!("Ones count {} at position {} over half ({}) of {}",
         //          &count_for_position, position, &half_of_inputs, &inputs.len());
         1
@@ -173,12 +221,12 @@ fn most_common_bit_for_pos(inputs: &Vec<String>, position: usize) -> usize {
     }
 }
 
-fn least_common_bit_for_pos(inputs: &Vec<String>, position: usize) -> usize {
-    let half_of_inputs= inputs.len() as f32 / 2 as f32;
+fn least_common_bit_for_pos(inputs: &[String], position: usize) -> usize {
+    let half_of_inputs= inputs.len() as f32 / 2_f32;
     let bit_counts = bits_column_sum(inputs);
     let count_for_position = bit_counts.get(position).unwrap();
 
-    if *count_for_position as f32 >= *&half_of_inputs {
+    if *count_for_position as f32 >= half_of_inputs {
         // println!("Ones count {} at position {} over half ({}) of {}",
         //          &count_for_position, position, &half_of_inputs, &inputs.len());
         0
@@ -187,8 +235,8 @@ fn least_common_bit_for_pos(inputs: &Vec<String>, position: usize) -> usize {
     }
 }
 
-pub(crate) fn usize_from_binary_string(input: String) -> usize {
-    let mut number = 0 as usize;
+pub fn usize_from_binary_string(input: String) -> usize {
+    let mut number = 0_usize;
 
     for (idx, char) in input.chars().enumerate() {
         let reversed_bit_order_idx = input.len() - 1 - idx;
@@ -202,7 +250,7 @@ pub(crate) fn usize_from_binary_string(input: String) -> usize {
 #[cfg(test)]
 #[test]
 fn test_bit_counting() {
-    let inputs = read_lines("data/day_3_sample.txt");
+    let inputs = read_lines("data/day_3_sample.txt").unwrap();
     let counts = bits_column_sum(&inputs);
     assert_eq!(counts, vec![7, 5, 8, 7, 5]);
 
@@ -225,7 +273,7 @@ fn test_bit_counting() {
 
 #[test]
 fn test_gamma_calculation() {
-    let inputs = read_lines("data/day_3_sample.txt");
+    let inputs = read_lines("data/day_3_sample.txt").unwrap();
     let (gamma, epsilon) = calculate_gamma_epsilon(&inputs);
     assert_eq!(gamma, 22);
     assert_eq!(epsilon, 9);
@@ -233,7 +281,7 @@ fn test_gamma_calculation() {
 
 #[test]
 fn test_bit_filter() {
-    let inputs = read_lines("data/day_3_sample.txt");
+    let inputs = read_lines("data/day_3_sample.txt").unwrap();
     let most_common_bit_1 = most_common_bit_for_pos(&inputs, 0);
     assert_eq!(most_common_bit_1, 1);
 
@@ -251,4 +299,23 @@ fn test_bit_filter() {
     let co2 = usize_from_binary_string(co2_entry);
     assert_eq!(o2, 23);
     assert_eq!(co2, 10);
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_malformed_diagnostics() {
+    let inputs = vec!["00100".to_string(), "1102".to_string()];
+    let error = parse_diagnostics(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 2, column 4: expected a 0 or 1, found `2`");
+
+    let inputs = vec!["00100".to_string(), "110".to_string()];
+    let error = parse_diagnostics(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 2, column 4: expected 5 bits, found 3");
+
+    let inputs = vec!["1".repeat(33), "0".repeat(33)];
+    let error = parse_diagnostics(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 33: expected at most 32 bits, found 33");
+
+    // The largest numbers still multiply without overflowing
+    let inputs = vec!["1".repeat(16) + &"0".repeat(16); 3];
+    let (gamma, epsilon) = calculate_gamma_epsilon(&parse_diagnostics(&inputs).unwrap());
+    assert_eq!((gamma, epsilon), (0xffff0000, 0xffff));
+}
@@ -1,48 +1,78 @@
-use advent_of_code_2021::read_lines;
+use log::debug;
+use crate::{fields, parse_number, whitespace_fields, Answer, ParseError, Solution};
+#[cfg(test)]
+use crate::{read_lines, Value};
+
+pub struct Day4;
 
-pub(crate) fn run() {
-    let inputs = read_lines("data/day_4_input.txt");
-    let (numbers, mut boards) = parse_bingo_data(&inputs);
-    let (last_number, board) = mark_until_bingo(numbers, &mut boards).unwrap();
-    let sum = sum_of_unmarked(&board);
+impl Solution for Day4 {
+    const DAY: usize = 4;
+    type Parsed = (Vec<usize>, Vec<Board>);
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        parse_bingo_data(inputs)
+    }
 
-    println!("Winning board has sum {} on last number {}", sum, last_number);
-    println!("Multiplied, this is {}", sum * last_number);
+    fn part_one(&self, (numbers, boards): &Self::Parsed) -> Result<Answer, ParseError> {
+        let mut boards = boards.clone();
+        let (last_number, board) = mark_until_bingo(numbers.clone(), &mut boards)
+            .ok_or_else(|| ParseError::input("expected a board to get a bingo from the numbers called, but none do"))?;
+        let sum = sum_of_unmarked(&board);
 
-    // Part 2
-    let (numbers, mut boards) = parse_bingo_data(&inputs);
-    let (last_number, board) = mark_until_last_bingo(numbers, &mut boards).unwrap();
-    let sum = sum_of_unmarked(&board);
+        Ok(Answer::new(sum * last_number, format!(
+            "Winning board has sum {} on last number {}\nMultiplied, this is {}",
+            sum, last_number, sum * last_number
+        )))
+    }
 
-    println!("Last winning board has sum {} on last number {}", sum, last_number);
-    println!("Multiplied, this is {}", sum * last_number);
+    fn part_two(&self, (numbers, boards): &Self::Parsed) -> Result<Answer, ParseError> {
+        let (last_number, board) = match mark_until_last_bingo(numbers.clone(), &mut boards.clone()) {
+            Some(last_bingo) => last_bingo,
+            None => {
+                let remaining = boards_without_bingo(numbers.clone(), &mut boards.clone());
+                return Err(ParseError::input(format!(
+                    "expected every board to get a bingo from the numbers called, but {} of {} never do",
+                    remaining, boards.len()
+                )));
+            }
+        };
+        let sum = sum_of_unmarked(&board);
 
+        Ok(Answer::new(sum * last_number, format!(
+            "Last winning board has sum {} on last number {}\nMultiplied, this is {}",
+            sum, last_number, sum * last_number
+        )))
+    }
 }
 
-type Board = Vec<Vec<Option<usize>>>;
+pub type Board = Vec<Vec<Option<usize>>>;
 
 /// Parses the bingo data, consisting of a first line of bingo number calls,
 /// followed by blank-line separated bingo boards
-pub(crate) fn parse_bingo_data(inputs: &Vec<String>) -> (Vec<usize>, Vec<Board>) {
-    let numbers = inputs.iter().next().unwrap();
-    let number_calls = numbers
-        .split(",")
-        .map(|num| num.parse::<usize>().unwrap())
-        .collect::<Vec<_>>();
+pub fn parse_bingo_data(inputs: &[String]) -> Result<(Vec<usize>, Vec<Board>), ParseError> {
+    let numbers = inputs
+        .first()
+        .ok_or_else(|| ParseError::input("the input is empty, expected the bingo numbers to call"))?;
+    let number_calls = fields(numbers, ",")
+        .map(|(column, num)| parse_number::<usize>(num, 0, column))
+        .collect::<Result<Vec<_>, _>>()?;
 
     let mut boards = Vec::new();
     let mut board = Vec::new();
 
-    for line in inputs[2..].iter() {
+    for (line_idx, line) in inputs.iter().enumerate().skip(1) {
         // Skip empty lines
-        if line.to_string() == "".to_string() { continue; }
-
-        let numbers = &mut line
-            .split_ascii_whitespace()
-            .map(|n| Some(n.parse::<usize>().unwrap()))
-            .collect::<Vec<Option<usize>>>();
-        // println!("Appending {:?}", &numbers);
-        board.push(numbers.clone());
+        if line.is_empty() { continue; }
+
+        let numbers = whitespace_fields(line)
+            .map(|(column, n)| parse_number::<usize>(n, line_idx, column).map(Some))
+            .collect::<Result<Vec<Option<usize>>, _>>()?;
+        if numbers.len() != 5 {
+            return Err(ParseError::at(
+                line_idx, 0, format!("expected a board row of 5 numbers, found {}", numbers.len())
+            ));
+        }
+        board.push(numbers);
 
         if board.len() == 5 {
             boards.push(board);
@@ -50,10 +80,19 @@ pub(crate) fn parse_bingo_data(inputs: &Vec<String>) -> (Vec<usize>, Vec<Board>)
         }
     }
 
-    (number_calls, boards)
+    if !board.is_empty() {
+        return Err(ParseError::at(
+            inputs.len() - 1, 0, format!("expected a board of 5 rows, found {}", board.len())
+        ));
+    }
+    if boards.is_empty() {
+        return Err(ParseError::input("expected at least one bingo board after the numbers to call"));
+    }
+
+    Ok((number_calls, boards))
 }
 
-pub(crate) fn mark_number(boards: &mut Vec<Board>, number: usize) {
+pub fn mark_number(boards: &mut Vec<Board>, number: usize) {
     for board in boards {
         for row in board {
             for number_entry in row {
@@ -68,12 +107,12 @@ pub(crate) fn mark_number(boards: &mut Vec<Board>, number: usize) {
 }
 
 /// Mark numbers on all boards until we have a bingo, which in that case returns Some(Board)
-pub(crate) fn mark_until_bingo(numbers: Vec<usize>, boards: &mut Vec<Board>) -> Option<(usize, Board)> {
+pub fn mark_until_bingo(numbers: Vec<usize>, boards: &mut Vec<Board>) -> Option<(usize, Board)> {
     // The default option: None
     for number in numbers {
         mark_number(boards, number);
-        if let Some(board_idx) = bingo(&boards) {
-            println!("Bingo on board index {}", board_idx);
+        if let Some(board_idx) = bingo(boards) {
+            debug!("Bingo on board index {}", board_idx);
             return Some((number, boards.get(board_idx).unwrap().clone()));
         }
     }
@@ -81,7 +120,7 @@ pub(crate) fn mark_until_bingo(numbers: Vec<usize>, boards: &mut Vec<Board>) ->
     None
 }
 
-pub(crate) fn mark_until_last_bingo(numbers: Vec<usize>, boards: &mut Vec<Board>) -> Option<(usize, Board)> {
+pub fn mark_until_last_bingo(numbers: Vec<usize>, boards: &mut Vec<Board>) -> Option<(usize, Board)> {
     let mut remaining_boards_idxs: Vec<usize> = (0..boards.len()).collect::<Vec<usize>>();
 
     for number in numbers {
@@ -93,16 +132,14 @@ pub(crate) fn mark_until_last_bingo(numbers: Vec<usize>, boards: &mut Vec<Board>
                 .position(|b| b == &board_idx);
 
             if let Some(winning_board_idxs_pos) = remaining_board_idxs_pos_option{
-                let transposed = transpose(&board);
+                let transposed = transpose(board);
 
-                if row_bingo(&board){
-                    remaining_boards_idxs.remove(winning_board_idxs_pos);
-                } else if row_bingo(&transposed) {
+                if row_bingo(board) || row_bingo(&transposed) {
                     remaining_boards_idxs.remove(winning_board_idxs_pos);
                 }
             }
 
-            if remaining_boards_idxs.len() == 0 {
+            if remaining_boards_idxs.is_empty() {
                 let remaining_board = board.clone();
                 return Some((number, remaining_board))
             }
@@ -112,7 +149,19 @@ pub(crate) fn mark_until_last_bingo(numbers: Vec<usize>, boards: &mut Vec<Board>
     None
 }
 
-pub(crate) fn bingo(boards: &Vec<Board>) -> Option<usize> {
+/// Marks all numbers on all boards, returning how many boards never got a bingo
+pub fn boards_without_bingo(numbers: Vec<usize>, boards: &mut Vec<Board>) -> usize {
+    for number in numbers {
+        mark_number(boards, number);
+    }
+
+    boards
+        .iter()
+        .filter(|board| !row_bingo(board) && !row_bingo(&transpose(board)))
+        .count()
+}
+
+pub fn bingo(boards: &[Board]) -> Option<usize> {
     for (board_idx, board) in boards.iter().enumerate() {
         if row_bingo(board){ return Some(board_idx); };
 
@@ -131,7 +180,7 @@ fn transpose(board: &Board) -> Board {
     for (r_idx, row) in board.iter().enumerate() {
         for (e_idx, entry) in row.iter().enumerate() {
             if let Some(value) = entry {
-                transposed[e_idx][r_idx] = Some(value.clone());
+                transposed[e_idx][r_idx] = Some(*value);
             }
         }
     }
@@ -145,7 +194,7 @@ fn row_bingo(board: &Board) -> bool {
         let mut all_none = true;
 
         for entry in row {
-            if let Some(_) = entry { all_none = false; }
+            if entry.is_some() { all_none = false; }
         }
 
         if all_none {
@@ -157,14 +206,12 @@ fn row_bingo(board: &Board) -> bool {
     false
 }
 
-fn sum_of_unmarked(board: &Board) -> usize {
+pub fn sum_of_unmarked(board: &Board) -> usize {
     let mut total = 0_usize;
 
     for row in board {
-        for entry in row {
-            if let Some(number) = entry {
-                total += number;
-            }
+        for number in row.iter().flatten() {
+            total += number;
         }
     }
 
@@ -174,8 +221,8 @@ fn sum_of_unmarked(board: &Board) -> usize {
 #[cfg(test)]
 #[test]
 fn test_bingo_data_parser() {
-    let inputs = read_lines("data/day_4_sample.txt");
-    let (number_calls, boards) = parse_bingo_data(&inputs);
+    let inputs = read_lines("data/day_4_sample.txt").unwrap();
+    let (number_calls, boards) = parse_bingo_data(&inputs).unwrap();
     assert_eq!(
         number_calls,
         vec![7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1]
@@ -195,8 +242,8 @@ fn test_bingo_data_parser() {
 
 #[test]
 fn test_mark_number_on_board() {
-    let inputs = read_lines("data/day_4_sample.txt");
-    let (_, mut boards) = parse_bingo_data(&inputs);
+    let inputs = read_lines("data/day_4_sample.txt").unwrap();
+    let (_, mut boards) = parse_bingo_data(&inputs).unwrap();
     mark_number(&mut boards, 22);
 
     // Validate that the very first number on the first board is now crossed off
@@ -212,8 +259,8 @@ fn test_mark_number_on_board() {
 
 #[test]
 fn test_bingo() {
-    let inputs = read_lines("data/day_4_sample.txt");
-    let (number_calls, mut boards) = parse_bingo_data(&inputs);
+    let inputs = read_lines("data/day_4_sample.txt").unwrap();
+    let (number_calls, mut boards) = parse_bingo_data(&inputs).unwrap();
 
     // The 13th draw should result in bingo on the third board
     for number in number_calls[..13].iter() {
@@ -225,13 +272,10 @@ fn test_bingo() {
 
 #[test]
 fn test_mark_until_bingo() {
-    let inputs = read_lines("data/day_4_sample.txt");
-    let (number_calls, mut boards) = parse_bingo_data(&inputs);
+    let inputs = read_lines("data/day_4_sample.txt").unwrap();
+    let (number_calls, mut boards) = parse_bingo_data(&inputs).unwrap();
 
-    let first_13_numbers = number_calls[..13]
-        .iter()
-        .map(|n| *n)
-        .collect();
+    let first_13_numbers = number_calls[..13].to_vec();
     let (_, maybe_bingo) = mark_until_bingo(first_13_numbers, &mut boards).unwrap();
     let first_board_entry = *maybe_bingo.first().unwrap().first().unwrap();
     // The first entry on the winning board was marked in one of the called numbers!
@@ -240,8 +284,8 @@ fn test_mark_until_bingo() {
 
 #[test]
 fn test_sum_of_unmarked() {
-    let inputs = read_lines("data/day_4_sample.txt");
-    let (number_calls, mut boards) = parse_bingo_data(&inputs);
+    let inputs = read_lines("data/day_4_sample.txt").unwrap();
+    let (number_calls, mut boards) = parse_bingo_data(&inputs).unwrap();
 
     let (last_number, winning_board) = mark_until_bingo(number_calls, &mut boards).unwrap();
     assert_eq!(last_number, 24);
@@ -252,12 +296,50 @@ fn test_sum_of_unmarked() {
 
 #[test]
 fn test_mark_until_last_bingo() {
-    let inputs = read_lines("data/day_4_sample.txt");
-    let (number_calls, mut boards) = parse_bingo_data(&inputs);
+    let inputs = read_lines("data/day_4_sample.txt").unwrap();
+    let (number_calls, mut boards) = parse_bingo_data(&inputs).unwrap();
 
     let (last_number, last_winning_board) = mark_until_last_bingo(number_calls, &mut boards).unwrap();
     assert_eq!(last_number, 13);
 
     let sum = sum_of_unmarked(&last_winning_board);
     assert_eq!(sum, 148)
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_malformed_bingo_data() {
+    let mut inputs = read_lines("data/day_4_sample.txt").unwrap();
+    inputs[3] = "8  2 23  4".to_string();
+    let error = parse_bingo_data(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 4, column 1: expected a board row of 5 numbers, found 4");
+
+    inputs.truncate(4);
+    inputs[3] = " 8  2 23  4 24".to_string();
+    let error = parse_bingo_data(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 4, column 1: expected a board of 5 rows, found 2");
+}
+
+#[test]
+fn test_board_without_bingo() {
+    let mut inputs = read_lines("data/day_4_sample.txt").unwrap();
+    let (number_calls, mut boards) = parse_bingo_data(&inputs).unwrap();
+    assert_eq!(boards_without_bingo(number_calls, &mut boards), 0);
+
+    // Only the first 5 numbers get called, which aren't enough for any bingo
+    inputs[0] = "7,4,9,5,11".to_string();
+    let (number_calls, mut boards) = parse_bingo_data(&inputs).unwrap();
+    assert_eq!(mark_until_bingo(number_calls.clone(), &mut boards.clone()), None);
+    assert_eq!(boards_without_bingo(number_calls, &mut boards), 3);
+
+    let parsed = Day4.parse(&inputs).unwrap();
+    let error = Day4.part_one(&parsed).unwrap_err();
+    assert_eq!(error.to_string(), "expected a board to get a bingo from the numbers called, but none do");
+    let error = Day4.part_two(&parsed).unwrap_err();
+    assert_eq!(error.to_string(), "expected every board to get a bingo from the numbers called, but 3 of 3 never do");
+
+    // The third board wins on 4, but the others never do, which is enough for part one only
+    inputs[0] = "14,21,17,24,4".to_string();
+    let parsed = Day4.parse(&inputs).unwrap();
+    assert_eq!(Day4.part_one(&parsed).unwrap().value, Value::from(4 * (325 - 14 - 21 - 17 - 24 - 4)));
+    let error = Day4.part_two(&parsed).unwrap_err();
+    assert_eq!(error.to_string(), "expected every board to get a bingo from the numbers called, but 2 of 3 never do");
+}
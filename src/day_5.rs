@@ -1,69 +1,97 @@
-use advent_of_code_2021::read_lines;
+use crate::{parse_number, split_pair, Answer, Grid, ParseError, Solution};
+#[cfg(test)]
+use crate::read_lines;
 
-pub(crate) fn run() {
-    let inputs = read_lines("data/day_5_input.txt");
-    let (hor_lines, ver_lines, dia_lines) = parse_lines(&inputs, true);
-    let mut all_lines = hor_lines;
-    all_lines.extend(ver_lines);
-    let mut grid = grid_sum_from_lines(&all_lines);
-    let mut score = hotspots_count(&grid);
+pub struct Day5;
 
-    println!("The total number of crossing horizontal and vertical lines in the grid is {}", &score);
+/// The furthest out a line can reach, either way, which sizes the grid of lines
+pub const MAX_COORDINATE: usize = 2_000;
 
-    all_lines.extend(dia_lines);
-    grid = grid_sum_from_lines(&all_lines);
-    score = hotspots_count(&grid);
-    println!("The total number of all crossing lines in the grid is {}", &score);
+impl Solution for Day5 {
+    const DAY: usize = 5;
+    type Parsed = Lines;
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        parse_lines(inputs, true)
+    }
+
+    fn part_one(&self, (hor_lines, ver_lines, _): &Self::Parsed) -> Result<Answer, ParseError> {
+        let mut all_lines = hor_lines.clone();
+        all_lines.extend(ver_lines.clone());
+        let grid = grid_sum_from_lines(&all_lines);
+        let score = hotspots_count(&grid);
+
+        Ok(Answer::new(score, format!(
+            "The total number of crossing horizontal and vertical lines in the grid is {}", score
+        )))
+    }
+
+    fn part_two(&self, (hor_lines, ver_lines, dia_lines): &Self::Parsed) -> Result<Answer, ParseError> {
+        let mut all_lines = hor_lines.clone();
+        all_lines.extend(ver_lines.clone());
+        all_lines.extend(dia_lines.clone());
+        let grid = grid_sum_from_lines(&all_lines);
+        let score = hotspots_count(&grid);
+
+        Ok(Answer::new(score, format!("The total number of all crossing lines in the grid is {}", score)))
+    }
 }
 
-type Point = (usize, usize);
-type Line = Vec<Point>;
-type Grid = Vec<Vec<usize>>;
+pub type Point = (usize, usize);
+pub type Line = Vec<Point>;
+// Horizontal, vertical and diagonal lines
+pub type Lines = (Vec<Line>, Vec<Line>, Vec<Line>);
 
-pub(crate) fn parse_lines(inputs: &Vec<String>, parse_diagonals: bool) -> (Vec<Line>, Vec<Line>, Vec<Line>) {
-    let mut hor_lines = Vec::new();
-    let mut ver_lines = Vec::new();
-    let mut dia_lines = Vec::new();
+/// Parses a point written as `x,y`, starting at a zero-based line and column
+fn parse_point(field: &str, line_idx: usize, column: usize) -> Result<Point, ParseError> {
+    let ((x_column, x), (y_column, y)) = split_pair(field, ",", line_idx)
+        .map_err(|_| ParseError::at(line_idx, column, format!("expected a point like 0,9, found `{}`", field)))?;
+
+    Ok((parse_coordinate(x, line_idx, column + x_column)?, parse_coordinate(y, line_idx, column + y_column)?))
+}
 
-    for input in inputs {
-        let start_end = input
-            .split(" -> ")
-            .collect::<Vec<_>>();
+fn parse_coordinate(field: &str, line_idx: usize, column: usize) -> Result<usize, ParseError> {
+    let coordinate = parse_number(field, line_idx, column)?;
+    if coordinate > MAX_COORDINATE {
+        return Err(ParseError::at(
+            line_idx, column, format!("expected a coordinate up to {}, found {}", MAX_COORDINATE, coordinate)
+        ));
+    }
 
-        let start_str = *start_end.get(0).unwrap();
-        let end_str = *start_end.get(1).unwrap();
+    Ok(coordinate)
+}
 
-        let start_point = start_str
-            .split(",")
-            .map(|n| n.parse::<usize>().unwrap())
-            .collect::<Vec<_>>();
+pub fn parse_lines(inputs: &[String], parse_diagonals: bool) -> Result<Lines, ParseError> {
+    let mut hor_lines = Vec::new();
+    let mut ver_lines = Vec::new();
+    let mut dia_lines = Vec::new();
 
-        let end_point = end_str
-            .split(",")
-            .map(|n| n.parse::<usize>().unwrap())
-            .collect::<Vec<_>>();
+    for (line_idx, input) in inputs.iter().enumerate() {
+        let ((start_column, start_str), (end_column, end_str)) = split_pair(input, " -> ", line_idx)?;
+        let start_point = parse_point(start_str, line_idx, start_column)?;
+        let end_point = parse_point(end_str, line_idx, end_column)?;
 
-        if start_point[0] == end_point[0] {
+        if start_point.0 == end_point.0 {
             // println!("Line is vertical");
             // Range over y positions according to direction. Is it away from the origin?
-            let range = match end_point[1] > start_point[1] {
-                true => start_point[1]..end_point[1] + 1,
-                false => end_point[1]..start_point[1] + 1,
+            let range = match end_point.1 > start_point.1 {
+                true => start_point.1..end_point.1 + 1,
+                false => end_point.1..start_point.1 + 1,
             };
 
             let line = (range)
-                .map(|y| (start_point[0], y))
+                .map(|y| (start_point.0, y))
                 .collect::<Line>();
             ver_lines.push(line);
-        } else if start_point[1] == end_point[1] {
+        } else if start_point.1 == end_point.1 {
             // println!("Line is horizontal");
-            let range = match end_point[0] > start_point[0] {
-                true => start_point[0]..end_point[0] + 1,
-                false => end_point[0]..start_point[0] + 1,
+            let range = match end_point.0 > start_point.0 {
+                true => start_point.0..end_point.0 + 1,
+                false => end_point.0..start_point.0 + 1,
             };
 
             let line = (range)
-                .map(|x| (x, start_point[1]))
+                .map(|x| (x, start_point.1))
                 .collect::<Line>();
             hor_lines.push(line);
         } else {
@@ -71,16 +99,18 @@ pub(crate) fn parse_lines(inputs: &Vec<String>, parse_diagonals: bool) -> (Vec<L
             // Skip if we're on part 1
             if !parse_diagonals { continue; }
 
-            let x_increases = end_point[0] > start_point[0];
-            let x_range;
+            if start_point.0.abs_diff(end_point.0) != start_point.1.abs_diff(end_point.1) {
+                return Err(ParseError::at(line_idx, 0, "expected a horizontal, vertical or 45 degree diagonal line"));
+            }
 
-            match x_increases {
-                true => x_range = start_point[0]..end_point[0] + 1,
-                false => x_range = end_point[0]..start_point[0] + 1,
+            let x_increases = end_point.0 > start_point.0;
+            let x_range = match x_increases {
+                true => start_point.0..end_point.0 + 1,
+                false => end_point.0..start_point.0 + 1,
             };
 
-            let start_y = start_point[1];
-            let end_y = end_point[1];
+            let start_y = start_point.1;
+            let end_y = end_point.1;
 
             let y_increases = end_y > start_y;
             let mut y_range = match y_increases {
@@ -96,55 +126,32 @@ pub(crate) fn parse_lines(inputs: &Vec<String>, parse_diagonals: bool) -> (Vec<L
         }
     }
 
-    (hor_lines, ver_lines, dia_lines)
+    Ok((hor_lines, ver_lines, dia_lines))
 }
 
-pub(crate) fn grid_sum_from_lines(lines: &Vec<Line>) -> Grid {
-    let mut grid_size = 10;
-    let mut all_points = Vec::new();
-
-    // Calculate grid size from points max
-    // And gather all points in a simpler vec
-    for line in lines {
-        for &point in line {
-            all_points.push(point);
+/// Counts how many lines cover each point, with the y coordinate as the row and x as the column
+pub fn grid_sum_from_lines(lines: &[Line]) -> Grid<usize> {
+    // Size the grid to fit the points furthest out
+    let width = lines.iter().flatten().map(|&(x, _)| x + 1).max().unwrap_or(0);
+    let height = lines.iter().flatten().map(|&(_, y)| y + 1).max().unwrap_or(0);
+    let mut grid = Grid::new(width, height, 0_usize);
 
-            if point.0 > grid_size {
-                grid_size = point.0;
-            } else if point.1 > grid_size {
-                grid_size = point.1;
-            }
-        }
-    }
-
-    let mut grid: Grid = vec![vec![0_usize; grid_size + 1]; grid_size + 1];
-
-    for point in all_points {
-        grid[point.0][point.1] += 1;
+    for &(x, y) in lines.iter().flatten() {
+        grid[(y, x)] += 1;
     }
 
     grid
 }
 
-pub(crate) fn hotspots_count(grid: &Grid) -> usize {
-    let mut score = 0;
-
-    for line in grid {
-        for entry in line {
-            if *entry > 1 {
-                score += 1;
-            }
-        }
-    }
-
-    score
+pub fn hotspots_count(grid: &Grid<usize>) -> usize {
+    grid.values().filter(|&&entry| entry > 1).count()
 }
 
 #[cfg(test)]
 #[test]
 fn test_straight_line_parser() {
-    let inputs = read_lines("data/day_5_sample.txt");
-    let (hor_lines, ver_lines, dia_lines) = parse_lines(&inputs, false);
+    let inputs = read_lines("data/day_5_sample.txt").unwrap();
+    let (hor_lines, ver_lines, dia_lines) = parse_lines(&inputs, false).unwrap();
 
     assert_eq!(hor_lines.len(), 4);
     assert_eq!(ver_lines.len(), 2);
@@ -156,19 +163,19 @@ fn test_straight_line_parser() {
 
 #[test]
 fn test_grid_sum_from_lines() {
-    let inputs = read_lines("data/day_5_sample.txt");
-    let (hor_lines, ver_lines, _) = parse_lines(&inputs, false);
+    let inputs = read_lines("data/day_5_sample.txt").unwrap();
+    let (hor_lines, ver_lines, _) = parse_lines(&inputs, false).unwrap();
     let mut all_lines = hor_lines;
     all_lines.extend(ver_lines);
     let grid = grid_sum_from_lines(&all_lines);
-    assert_eq!(grid[0][0], 0);
-    assert_eq!(grid[3][4], 2);
+    assert_eq!(grid[(0, 0)], 0);
+    assert_eq!(grid[(4, 3)], 2);
 }
 
 #[test]
 fn test_number_of_points_where_at_least_two_straight_lines_overlap() {
-    let inputs = read_lines("data/day_5_sample.txt");
-    let (hor_lines, ver_lines, _) = parse_lines(&inputs, false);
+    let inputs = read_lines("data/day_5_sample.txt").unwrap();
+    let (hor_lines, ver_lines, _) = parse_lines(&inputs, false).unwrap();
     let mut all_lines = hor_lines;
     all_lines.extend(ver_lines);
     let grid = grid_sum_from_lines(&all_lines);
@@ -179,8 +186,8 @@ fn test_number_of_points_where_at_least_two_straight_lines_overlap() {
 
 #[test]
 fn test_diagonal_line_parser() {
-    let inputs = read_lines("data/day_5_sample.txt");
-    let (_, _, dia_lines) = parse_lines(&inputs, true);
+    let inputs = read_lines("data/day_5_sample.txt").unwrap();
+    let (_, _, dia_lines) = parse_lines(&inputs, true).unwrap();
 
     assert_eq!(dia_lines.len(), 4);
 
@@ -200,8 +207,8 @@ fn test_diagonal_line_parser() {
 
 #[test]
 fn test_straight_and_diagonal_grid_hotspot_counts() {
-    let inputs = read_lines("data/day_5_sample.txt");
-    let (hor_lines, ver_lines, dia_lines) = parse_lines(&inputs, true);
+    let inputs = read_lines("data/day_5_sample.txt").unwrap();
+    let (hor_lines, ver_lines, dia_lines) = parse_lines(&inputs, true).unwrap();
     let mut all_lines = hor_lines;
     all_lines.extend(ver_lines);
     all_lines.extend(dia_lines);
@@ -211,4 +218,28 @@ fn test_straight_and_diagonal_grid_hotspot_counts() {
 
     assert_eq!(num_hotspots, 12);
 
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_malformed_lines() {
+    let inputs = vec!["0,9 -> 5,9".to_string(), "8,0 -> 0;8".to_string()];
+    let error = parse_lines(&inputs, true).unwrap_err();
+    assert_eq!(error.to_string(), "line 2, column 8: expected a point like 0,9, found `0;8`");
+
+    let inputs = vec!["0,9 - 5,9".to_string()];
+    assert_eq!(parse_lines(&inputs, true).unwrap_err().line(), Some(1));
+
+    let inputs = vec!["0,0 -> 2,1".to_string()];
+    assert!(parse_lines(&inputs, true).is_err());
+    assert!(parse_lines(&inputs, false).is_ok());
+}
+
+#[test]
+fn test_coordinates_out_of_range() {
+    let inputs = vec!["0,0 -> 100000000,100000000".to_string()];
+    let error = parse_lines(&inputs, true).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 8: expected a coordinate up to 2000, found 100000000");
+
+    let inputs = vec!["2000,0 -> 2000,2001".to_string()];
+    let error = parse_lines(&inputs, false).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 16: expected a coordinate up to 2000, found 2001");
+}
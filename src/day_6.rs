@@ -1,32 +1,54 @@
-use advent_of_code_2021::{parse_vec_usize, read_lines};
+use log::trace;
+use crate::{fields, parse_number, parse_vec_usize, Answer, ParseError, Solution};
+#[cfg(test)]
+use crate::property::check_property;
+#[cfg(test)]
+use crate::read_lines;
 
-pub(crate) fn run() {
-    let inputs = read_lines("data/day_6_input.txt");
-    let mut school = parse_vec_usize(&inputs);
-    procreate_for_days(&mut school, 80);
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: usize = 6;
+    type Parsed = (Vec<usize>, Vec<usize>);
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        Ok((parse_vec_usize(inputs)?, parse_smart_school(inputs)?))
+    }
 
-    println!("School size after 80 days is {}", school.len());
+    fn part_one(&self, (school, _): &Self::Parsed) -> Result<Answer, ParseError> {
+        let mut school = school.clone();
+        procreate_for_days(&mut school, 80);
 
-    let mut school_bins = parse_smart_school(&inputs);
-    smart_procreate_for_days(&mut school_bins, 256);
-    let school_size = school_bins.iter().sum::<usize>();
-    println!("School size after 256 days is {}", school_size);
+        Ok(Answer::new(school.len(), format!("School size after 80 days is {}", school.len())))
+    }
+
+    fn part_two(&self, (_, school_bins): &Self::Parsed) -> Result<Answer, ParseError> {
+        let mut school_bins = school_bins.clone();
+        smart_procreate_for_days(&mut school_bins, 256);
+        let school_size = school_bins.iter().sum::<usize>();
+
+        Ok(Answer::new(school_size, format!("School size after 256 days is {}", school_size)))
+    }
 }
 
-pub(crate) fn parse_smart_school(inputs: &Vec<String>) -> Vec<usize> {
+pub fn parse_smart_school(inputs: &[String]) -> Result<Vec<usize>, ParseError> {
     let mut age_population = vec![0; 9];
+    let ages = inputs
+        .first()
+        .ok_or_else(|| ParseError::input("the input is empty, expected comma separated fish ages"))?;
+
+    for (column, f) in fields(ages, ",") {
+        let fish_age = parse_number::<usize>(f, 0, column)?;
+        if fish_age >= age_population.len() {
+            return Err(ParseError::at(0, column, format!("expected a fish age up to 8, found {}", fish_age)));
+        }
+        age_population[fish_age] += 1;
+    }
 
-    inputs[0]
-        .split(",")
-        .for_each(|f| {
-            let fish_age = f.parse::<usize>().unwrap();
-            age_population[fish_age] += 1;
-        });
-
-    age_population
+    Ok(age_population)
 }
 
-pub(crate) fn procreate(school: &mut Vec<usize>) {
+pub fn procreate(school: &mut Vec<usize>) {
     let mut new_fish = Vec::new();
 
     for fish_procreate_countdown in &mut *school {
@@ -42,22 +64,22 @@ pub(crate) fn procreate(school: &mut Vec<usize>) {
     school.extend(new_fish);
 }
 
-pub(crate) fn smart_procreate(school_bins: &mut Vec<usize>) {
+pub fn smart_procreate(school_bins: &mut [usize]) {
     school_bins.rotate_left(1);
     school_bins[6] += school_bins[8];
 }
 
-pub(crate) fn procreate_for_days(school: &mut Vec<usize>, days: usize) {
+pub fn procreate_for_days(school: &mut Vec<usize>, days: usize) {
     for day in 0..days {
         procreate(school);
         if day % 10 == 0 {
             let school_size = school.len();
-            println!("Procreated day {}, school size: {}", day, school_size);
+            trace!("Procreated day {}, school size: {}", day, school_size);
         }
     }
 }
 
-pub(crate) fn smart_procreate_for_days(school_bins: &mut Vec<usize>, days: usize) {
+pub fn smart_procreate_for_days(school_bins: &mut [usize], days: usize) {
     for _ in 0..days {
         smart_procreate(school_bins);
     }
@@ -66,8 +88,8 @@ pub(crate) fn smart_procreate_for_days(school_bins: &mut Vec<usize>, days: usize
 #[cfg(test)]
 #[test]
 fn test_procreate() {
-    let inputs = read_lines("data/day_6_sample.txt");
-    let mut school = parse_vec_usize(&inputs);
+    let inputs = read_lines("data/day_6_sample.txt").unwrap();
+    let mut school = parse_vec_usize(&inputs).unwrap();
     procreate(&mut school);
     assert_eq!(school, vec![2, 3, 2, 0, 1]);
 
@@ -77,8 +99,8 @@ fn test_procreate() {
 
 #[test]
 fn test_procreate_for_80_days() {
-    let inputs = read_lines("data/day_6_sample.txt");
-    let mut school = parse_vec_usize(&inputs);
+    let inputs = read_lines("data/day_6_sample.txt").unwrap();
+    let mut school = parse_vec_usize(&inputs).unwrap();
 
     procreate_for_days(&mut school, 80);
     assert_eq!(school.len(), 5934);
@@ -86,15 +108,15 @@ fn test_procreate_for_80_days() {
 
 #[test]
 fn test_parse_smart_school() {
-    let inputs = read_lines("data/day_6_sample.txt");
-    let school = parse_smart_school(&inputs);
+    let inputs = read_lines("data/day_6_sample.txt").unwrap();
+    let school = parse_smart_school(&inputs).unwrap();
     assert_eq!(school, vec![0, 1, 1, 2, 1, 0, 0, 0, 0])
 }
 
 #[test]
 fn test_count_smart_school_procreation() {
-    let inputs = read_lines("data/day_6_sample.txt");
-    let mut school_bins = parse_smart_school(&inputs);
+    let inputs = read_lines("data/day_6_sample.txt").unwrap();
+    let mut school_bins = parse_smart_school(&inputs).unwrap();
     assert_eq!(school_bins, vec![0, 1, 1, 2, 1, 0, 0, 0, 0]);
 
     smart_procreate(&mut school_bins);
@@ -107,3 +129,33 @@ fn test_count_smart_school_procreation() {
     smart_procreate_for_days(&mut school_bins, 80 - 2);
     assert_eq!(school_bins.iter().sum::<usize>(), 5934)
 }
+
+#[test]
+fn test_parse_malformed_school() {
+    let inputs = vec!["3,4,9,1".to_string()];
+    let error = parse_smart_school(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 5: expected a fish age up to 8, found 9");
+}
+
+#[test]
+fn test_procreate_matches_smart_procreate() {
+    check_property(200, |rng| {
+        let ages = (0..rng.between(1, 20)).map(|_| rng.between(0, 8).to_string()).collect::<Vec<_>>();
+        let inputs = vec![ages.join(",")];
+        let mut school = parse_vec_usize(&inputs).unwrap();
+        let mut school_bins = parse_smart_school(&inputs).unwrap();
+        let days = rng.between(0, 60);
+
+        procreate_for_days(&mut school, days);
+        smart_procreate_for_days(&mut school_bins, days);
+
+        let mut naive_bins = vec![0; 9];
+        for fish in school {
+            naive_bins[fish] += 1;
+        }
+        match naive_bins == school_bins {
+            true => Ok(()),
+            false => Err(format!("after {} days procreate gave ages {:?}, smart_procreate {:?}", days, naive_bins, school_bins)),
+        }
+    });
+}
@@ -3,83 +3,113 @@ use ndarray_stats::interpolate::Nearest;
 use ndarray_stats::QuantileExt;
 use noisy_float::types::n64;
 
-use advent_of_code_2021::{parse_vec_usize, read_lines};
+use log::{debug, trace};
+use crate::{fields, parse_vec_usize, Answer, ParseError, Solution};
+#[cfg(test)]
+use crate::property::check_property;
+#[cfg(test)]
+use crate::{read_lines, Value};
+
+pub struct Day7;
 
-pub(crate) fn run() {
-    let inputs = read_lines("data/day_7_input.txt");
-    let positions = parse_vec_usize(&inputs);
-    let alignment = cheapest_alignment(&positions);
-    println!("Alignment median: {}", &alignment);
+/// The furthest position a crab can be at
+pub const MAX_POSITION: usize = 1_000_000;
 
-    let consumption = fuel_total_for_alignment(&positions, alignment);
-    println!("Total fuel consumption: {}", consumption);
+impl Solution for Day7 {
+    const DAY: usize = 7;
+    type Parsed = Vec<usize>;
 
-    let mean_alignment = cheapest_expensive_alignment(&positions);
-    println!("Alignment mean: {}", &mean_alignment);
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        let positions = parse_vec_usize(inputs)?;
 
-    // Start guessing fuel consumption for some positions
-    let mut lowest = 99999999999999_usize;
+        // Keep the fuel totals of moving the crabs from overflowing
+        if let Some(((column, _), position)) = fields(&inputs[0], ",")
+            .zip(&positions)
+            .find(|(_, &position)| position > MAX_POSITION)
+        {
+            return Err(ParseError::at(
+                0, column, format!("expected a position up to {}, found {}", MAX_POSITION, position)
+            ));
+        }
 
-    for position in mean_alignment - 10..mean_alignment + 10 {
-        let consumption = fuel_total_for_expensive_alignment(&positions, position);
-        println!("Total fuel consumption for position {}: {}", position, consumption);
-        if consumption < lowest { lowest = consumption; }
+        Ok(positions)
     }
 
-    println!("Lowest consumption: {}", lowest);
+    fn part_one(&self, positions: &Self::Parsed) -> Result<Answer, ParseError> {
+        let alignment = cheapest_alignment(positions);
+        let consumption = fuel_total_for_alignment(positions, alignment);
+
+        Ok(Answer::new(consumption, format!(
+            "Alignment median: {}\nTotal fuel consumption: {}", alignment, consumption
+        )))
+    }
+
+    fn part_two(&self, positions: &Self::Parsed) -> Result<Answer, ParseError> {
+        let mean_alignment = cheapest_expensive_alignment(positions);
+
+        // Start guessing fuel consumption for some positions
+        let mut lowest = 99999999999999_usize;
+
+        for position in mean_alignment.saturating_sub(10)..mean_alignment + 10 {
+            let consumption = fuel_total_for_expensive_alignment(positions, position);
+            trace!("Total fuel consumption for position {}: {}", position, consumption);
+            if consumption < lowest { lowest = consumption; }
+        }
+
+        Ok(Answer::new(lowest, format!("Alignment mean: {}\nLowest consumption: {}", mean_alignment, lowest)))
+    }
 }
 
-pub(crate) fn fuel_total_for_alignment(positions: &Vec<usize>, align_position: usize) -> usize {
+pub fn fuel_total_for_alignment(positions: &[usize], align_position: usize) -> usize {
     let mut fuel_total = 0;
 
     for position in positions {
-        fuel_total += (align_position as isize - *position as isize).abs() as usize;
+        fuel_total += (align_position as isize - *position as isize).unsigned_abs();
     }
 
     fuel_total
 }
 
-pub(crate) fn fuel_total_for_expensive_alignment(positions: &Vec<usize>, align_position: usize) -> usize {
+pub fn fuel_total_for_expensive_alignment(positions: &[usize], align_position: usize) -> usize {
     let mut fuel_total = 0;
 
     for position in positions {
-        let distance = (align_position as isize - *position as isize).abs() as usize;
-        let stepped_increase: usize = (1..distance + 1).sum();
-        fuel_total += stepped_increase;
+        let distance = (align_position as isize - *position as isize).unsigned_abs();
+        // Each step costs one more than the last: 1 + 2 + ... + distance
+        fuel_total += distance * (distance + 1) / 2;
     }
 
     fuel_total
 }
 
-pub(crate) fn cheapest_alignment(positions: &Vec<usize>) -> usize {
-    let mut vector = Array1::from_vec(positions.clone());
+pub fn cheapest_alignment(positions: &[usize]) -> usize {
+    let mut vector = Array1::from_vec(positions.to_vec());
     let axis = Axis(0);
     let median = vector
         .quantile_axis_mut(axis, n64(0.5), &Nearest)
         .unwrap();
 
-    println!("median: {}", &median);
+    debug!("median: {}", &median);
     median.as_slice().unwrap()[0]
 }
 
-pub(crate) fn cheapest_expensive_alignment(positions: &Vec<usize>) -> usize {
+pub fn cheapest_expensive_alignment(positions: &[usize]) -> usize {
     let positions_as_floats = positions
-        .clone()
         .iter()
-        .map(|p| f64::from(*p as i16))
+        .map(|&p| p as f64)
         .collect::<Vec<f64>>();
     let vector = Array1::from_vec(positions_as_floats);
     let mean = vector.mean().unwrap();
 
-    println!("mean: {}", &mean);
+    debug!("mean: {}", &mean);
     mean.round() as usize
 }
 
 #[cfg(test)]
 #[test]
 fn test_parse_sub_positions() {
-    let inputs = read_lines("data/day_7_sample.txt");
-    let hor_positions = parse_vec_usize(&inputs);
+    let inputs = read_lines("data/day_7_sample.txt").unwrap();
+    let hor_positions = parse_vec_usize(&inputs).unwrap();
     let fuel_consumption = fuel_total_for_alignment(&hor_positions, 2);
 
     assert_eq!(fuel_consumption, 37);
@@ -87,18 +117,60 @@ fn test_parse_sub_positions() {
 
 #[test]
 fn test_cheapest_alignment() {
-    let inputs = read_lines("data/day_7_sample.txt");
-    let hor_positions = parse_vec_usize(&inputs);
+    let inputs = read_lines("data/day_7_sample.txt").unwrap();
+    let hor_positions = parse_vec_usize(&inputs).unwrap();
     let alignment = cheapest_alignment(&hor_positions);
     assert_eq!(alignment, 2)
 }
 
 #[test]
 fn test_stepped_increase_consumption() {
-    let inputs = read_lines("data/day_7_sample.txt");
-    let hor_positions = parse_vec_usize(&inputs);
+    let inputs = read_lines("data/day_7_sample.txt").unwrap();
+    let hor_positions = parse_vec_usize(&inputs).unwrap();
     let fuel_consumption = fuel_total_for_expensive_alignment(&hor_positions, 5);
 
     assert_eq!(fuel_consumption, 168);
     assert_eq!(cheapest_expensive_alignment(&hor_positions), 5)
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_far_positions() {
+    // Positions that don't fit in 16 bits
+    let positions = Day7.parse(&["100000,100002,100010".to_string()]).unwrap();
+    assert_eq!(cheapest_expensive_alignment(&positions), 100004);
+    assert_eq!(Day7.part_two(&positions).unwrap().value, Value::from(10 + 3 + 21));
+
+    let error = Day7.parse(&["16,1,2000000".to_string()]).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 6: expected a position up to 1000000, found 2000000");
+}
+
+#[test]
+fn test_alignments_match_brute_force() {
+    check_property(200, |rng| {
+        let positions = (0..rng.between(1, 30)).map(|_| rng.below(100)).collect::<Vec<_>>();
+        let furthest = *positions.iter().max().unwrap();
+
+        // Try aligning on every position in between
+        let cheapest = (0..=furthest)
+            .map(|position| fuel_total_for_alignment(&positions, position))
+            .min()
+            .unwrap();
+        let cheapest_expensive = (0..=furthest)
+            .map(|position| fuel_total_for_expensive_alignment(&positions, position))
+            .min()
+            .unwrap();
+
+        let aligned = fuel_total_for_alignment(&positions, cheapest_alignment(&positions));
+        if aligned != cheapest {
+            return Err(format!("{:?} aligns for {} fuel, but can do with {}", positions, aligned, cheapest));
+        }
+
+        let aligned_expensive = Day7.part_two(&positions).unwrap().value;
+        match aligned_expensive == Value::from(cheapest_expensive) {
+            true => Ok(()),
+            false => Err(format!(
+                "{:?} aligns for {} expensive fuel, but can do with {}", positions, aligned_expensive, cheapest_expensive
+            )),
+        }
+    });
+}
@@ -1,46 +1,135 @@
-use std::usize;
-use advent_of_code_2021::read_lines;
-
-pub(crate) fn run() {
-    let inputs = read_lines("data/day_8_input.txt");
-    let segment_sets = parse_input_output_signals(&inputs);
-    let easy_count = count_easy_segments(&segment_sets);
-    println!("The total of easy digits is {}", easy_count);
-
-    let full_signals = parse_full_signals(&segment_sets);
-    let total = sum_outputs(&full_signals);
-    println!("The total of the display numbers is {}", total);
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use crate::{fields, split_pair, Answer, ParseError, Solution};
+#[cfg(test)]
+use crate::read_lines;
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: usize = 8;
+    type Parsed = Vec<SegmentPatternSet>;
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        parse_input_output_signals(inputs)
+    }
+
+    fn part_one(&self, segment_sets: &Self::Parsed) -> Result<Answer, ParseError> {
+        let easy_count = count_easy_segments(segment_sets);
+        Ok(Answer::new(easy_count, format!("The total of easy digits is {}", easy_count)))
+    }
+
+    fn part_two(&self, segment_sets: &Self::Parsed) -> Result<Answer, ParseError> {
+        let full_signals = parse_full_signals(segment_sets);
+        let total = sum_outputs(&full_signals);
+        Ok(Answer::new(total, format!("The total of the display numbers is {}", total)))
+    }
 }
 
-type SegmentPatternSet = (Vec<String>, Vec<String>);
-type Signal = Vec<u8>;
-type SegmentSignalSet = (Vec<Signal>, Vec<Signal>);
+/// The segments lighting up for each digit, on a display with its wires the right way round
+pub const DIGIT_SEGMENTS: [&str; 10] = ["abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg"];
 
-pub(crate) fn parse_input_output_signals(inputs_outputs: &Vec<String>) -> Vec<SegmentPatternSet> {
+pub type SegmentPatternSet = (Vec<String>, Vec<String>);
+pub type Signal = Vec<u8>;
+pub type SegmentSignalSet = (Vec<Signal>, Vec<Signal>);
+
+pub fn parse_input_output_signals(inputs_outputs: &[String]) -> Result<Vec<SegmentPatternSet>, ParseError> {
     let mut segment_sets = Vec::new();
 
-    for i_o in inputs_outputs {
-        let pipe_split = i_o.split(" | ")
-            .map(|pattern| pattern.to_string() )
-            .collect::<Vec<String>>();
+    for (line_idx, i_o) in inputs_outputs.iter().enumerate() {
+        let ((_, inputs), (outputs_column, outputs)) = split_pair(i_o, " | ", line_idx)?;
+        let inputs = parse_patterns(inputs, 10, line_idx, 0)?;
+        let outputs = parse_patterns(outputs, 4, line_idx, outputs_column)?;
+        check_wiring(&inputs, &outputs, line_idx, outputs_column)?;
 
-        let inputs = pipe_split[0]
-            .split(" ")
-            .map(|pattern| pattern.to_string() )
-            .collect();
+        segment_sets.push((inputs, outputs));
+    }
 
-        let outputs = pipe_split[1]
-            .split(" ")
-            .map(|pattern| pattern.to_string() )
-            .collect();
+    Ok(segment_sets)
+}
 
-        segment_sets.push((inputs, outputs));
+/// Parses a given count of space separated segment patterns, each made up of the segments a to g
+fn parse_patterns(
+    patterns: &str,
+    count: usize,
+    line_idx: usize,
+    column: usize
+) -> Result<Vec<String>, ParseError> {
+    let mut parsed = Vec::new();
+
+    for (pattern_column, pattern) in fields(patterns, " ") {
+        if let Some((char_column, c)) = pattern.char_indices().find(|(_, c)| !('a'..='g').contains(c)) {
+            return Err(ParseError::at(
+                line_idx, column + pattern_column + char_column, format!("expected a segment a to g, found `{}`", c)
+            ));
+        }
+        if let Some((char_column, c)) = pattern.char_indices().find(|&(idx, c)| pattern[..idx].contains(c)) {
+            return Err(ParseError::at(
+                line_idx, column + pattern_column + char_column, format!("segment `{}` appears twice in the pattern", c)
+            ));
+        }
+        parsed.push(pattern.to_string());
+    }
+
+    if parsed.len() != count {
+        return Err(ParseError::at(line_idx, column, format!("expected {} patterns, found {}", count, parsed.len())));
     }
 
-    segment_sets
+    Ok(parsed)
 }
 
-pub(crate) fn parse_full_signals(segment_sets: &Vec<SegmentPatternSet>) -> Vec<SegmentSignalSet>{
+// The segments of a pattern as bits, with a as the lowest
+fn segment_bits(pattern: &str) -> u8 {
+    pattern.bytes().fold(0, |bits, segment| bits | 1 << (segment - b'a'))
+}
+
+/// Checks that the ten patterns are the digits 0 to 9 on some wiring of the segments, and that the
+/// outputs are among them, so they can be decoded
+fn check_wiring(inputs: &[String], outputs: &[String], line_idx: usize, outputs_column: usize) -> Result<(), ParseError> {
+    let patterns = inputs.iter().map(|pattern| segment_bits(pattern)).collect::<Vec<_>>();
+    let one = patterns.iter().copied().find(|bits| bits.count_ones() == 2).unwrap_or(0);
+    let four = patterns.iter().copied().find(|bits| bits.count_ones() == 4).unwrap_or(0);
+
+    // Across the ten digits, the segments e, b and f light up 4, 6 and 9 times. Of the ones lighting
+    // up 8 times c is in the one and a isn't, and of those lighting up 7 times d is in the four
+    let mut rewired = [0_u8; 7];
+    for (wire, segment) in rewired.iter_mut().enumerate() {
+        let bit = 1 << wire;
+        let lit = patterns.iter().filter(|&&bits| bits & bit != 0).count();
+        let right_segment = match lit {
+            4 => 'e',
+            6 => 'b',
+            9 => 'f',
+            8 if one & bit != 0 => 'c',
+            8 => 'a',
+            7 if four & bit != 0 => 'd',
+            7 => 'g',
+            _ => continue,
+        };
+        *segment = 1 << (right_segment as u8 - b'a');
+    }
+
+    let mut digits = patterns
+        .iter()
+        .map(|&bits| (0..7).filter(|wire| bits & 1 << wire != 0).fold(0, |digit, wire| digit | rewired[wire]))
+        .collect::<Vec<_>>();
+    digits.sort_unstable();
+    let mut expected = DIGIT_SEGMENTS.iter().map(|segments| segment_bits(segments)).collect::<Vec<_>>();
+    expected.sort_unstable();
+    if digits != expected {
+        return Err(ParseError::at(line_idx, 0, "expected the ten patterns to be the digits 0 to 9 on some wiring of the segments"));
+    }
+
+    if let Some(output) = outputs.iter().find(|output| !patterns.contains(&segment_bits(output))) {
+        return Err(ParseError::at(
+            line_idx, outputs_column, format!("expected every output to be one of the ten patterns, found `{}`", output)
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn parse_full_signals(segment_sets: &[SegmentPatternSet]) -> Vec<SegmentSignalSet>{
     let mut segment_signal_sets = Vec::new();
 
     for pattern_set in segment_sets {
@@ -51,7 +140,7 @@ pub(crate) fn parse_full_signals(segment_sets: &Vec<SegmentPatternSet>) -> Vec<S
                 .chars()
                 .map(|c| {
                     let ascii_number = c.to_string().into_bytes();
-                    ascii_number[0] as u8 - 97
+                    ascii_number[0] - 97
                 })
                 .collect();
 
@@ -63,7 +152,7 @@ pub(crate) fn parse_full_signals(segment_sets: &Vec<SegmentPatternSet>) -> Vec<S
                 .chars()
                 .map(|c| {
                     let ascii_number = c.to_string().into_bytes();
-                    ascii_number[0] as u8 - 97
+                    ascii_number[0] - 97
                 } )
                 .collect();
 
@@ -76,7 +165,6 @@ pub(crate) fn parse_full_signals(segment_sets: &Vec<SegmentPatternSet>) -> Vec<S
     segment_signal_sets
 }
 
-///
 //   0:      1:      2:      3:      4:
 //  aaaa    ....    aaaa    aaaa    ....
 // b    c  .    c  .    c  .    c  b    c
@@ -108,7 +196,7 @@ pub(crate) fn parse_full_signals(segment_sets: &Vec<SegmentPatternSet>) -> Vec<S
 // two is the only five-segment digit that is not a three and not a five
 // zero is the only six-segment digit that is not a nine and has all the elements of the one
 // six is the only six-segment digit that is not a zero and not a nine
-pub(crate) fn decode_inputs(signal_set: &SegmentSignalSet) -> Vec<Signal> {
+pub fn decode_inputs(signal_set: &SegmentSignalSet) -> Vec<Signal> {
     let inputs = &signal_set.0;
 
     // only one contains  2 segments
@@ -180,7 +268,7 @@ pub(crate) fn decode_inputs(signal_set: &SegmentSignalSet) -> Vec<Signal> {
         .filter(|num| !nine.contains(num))
         .collect::<Vec<_>>();
     assert_eq!(bottom_left_candidates.len(), 1);
-    let bottom_left = bottom_left_candidates[0].clone();
+    let bottom_left = *bottom_left_candidates[0];
 
     // five is the only five-segment digit not being a three, having no bottom left segment
     let five_candidates = inputs
@@ -241,10 +329,10 @@ pub(crate) fn decode_inputs(signal_set: &SegmentSignalSet) -> Vec<Signal> {
     vec![zero, one, two, three, four, five, six, seven, eight, nine]
 }
 
-pub(crate) fn decode_display(decoded_inputs: &Vec<Signal>, encoded_outputs: &Vec<Signal>) -> usize {
+pub fn decode_display(decoded_inputs: &[Signal], encoded_outputs: &[Signal]) -> usize {
     let mut display_number = 0;
 
-    let mut smallest_digit_first = encoded_outputs.clone();
+    let mut smallest_digit_first = encoded_outputs.to_vec();
     smallest_digit_first.reverse();
 
     for (pos, signal) in smallest_digit_first.iter().enumerate() {
@@ -258,7 +346,7 @@ pub(crate) fn decode_display(decoded_inputs: &Vec<Signal>, encoded_outputs: &Vec
     display_number
 }
 
-pub(crate) fn count_easy_segments(segment_sets: &Vec<SegmentPatternSet>) -> usize {
+pub fn count_easy_segments(segment_sets: &[SegmentPatternSet]) -> usize {
     let mut count = 0;
 
     for segment_set in segment_sets {
@@ -274,22 +362,26 @@ pub(crate) fn count_easy_segments(segment_sets: &Vec<SegmentPatternSet>) -> usiz
     count
 }
 
-pub(crate) fn sum_outputs(segment_signal_sets: &Vec<SegmentSignalSet>) -> usize {
-    let mut sum = 0;
-
-    for set in segment_signal_sets {
-        let decoded_inputs = decode_inputs(set);
-        let display = decode_display(&decoded_inputs, &set.1);
-        sum += display
-    }
-    sum
+pub fn sum_outputs(segment_signal_sets: &[SegmentSignalSet]) -> usize {
+    // Each display gets decoded on its own, so they can all be decoded at the same time
+    #[cfg(feature = "parallel")]
+    let sets = segment_signal_sets.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let sets = segment_signal_sets.iter();
+
+    sets
+        .map(|set| {
+            let decoded_inputs = decode_inputs(set);
+            decode_display(&decoded_inputs, &set.1)
+        })
+        .sum()
 }
 
 #[cfg(test)]
 #[test]
 fn test_parse_segments() {
-    let inputs = read_lines("data/day_8_sample.txt");
-    let segment_sets = parse_input_output_signals(&inputs);
+    let inputs = read_lines("data/day_8_sample.txt").unwrap();
+    let segment_sets = parse_input_output_signals(&inputs).unwrap();
     assert_eq!(segment_sets.len(), 10);
 
     let expected = vec!["fdgacbe".to_string(), "cefdb".to_string(), "cefbgd".to_string(), "gcbe".to_string()];
@@ -298,16 +390,16 @@ fn test_parse_segments() {
 
 #[test]
 fn test_count_easy_segments() {
-    let inputs = read_lines("data/day_8_sample.txt");
-    let segment_sets = parse_input_output_signals(&inputs);
+    let inputs = read_lines("data/day_8_sample.txt").unwrap();
+    let segment_sets = parse_input_output_signals(&inputs).unwrap();
     let count = count_easy_segments(&segment_sets);
     assert_eq!(count, 26);
 }
 
 #[test]
 fn test_full_signal_parse() {
-    let inputs = read_lines("data/day_8_sample.txt");
-    let segment_sets = parse_input_output_signals(&inputs);
+    let inputs = read_lines("data/day_8_sample.txt").unwrap();
+    let segment_sets = parse_input_output_signals(&inputs).unwrap();
     let segment_signal_sets = parse_full_signals(&segment_sets);
 
     let first_set = segment_signal_sets[0].clone();
@@ -317,7 +409,7 @@ fn test_full_signal_parse() {
 #[test]
 fn test_full_decode() {
     let inputs = vec!["acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf".to_string()];
-    let segment_sets = parse_input_output_signals(&inputs);
+    let segment_sets = parse_input_output_signals(&inputs).unwrap();
     let segment_signal_sets = parse_full_signals(&segment_sets);
 
     let first_set = segment_signal_sets[0].clone();
@@ -363,7 +455,7 @@ fn test_full_decode() {
 #[test]
 fn test_output_decode() {
     let inputs = vec!["acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf".to_string()];
-    let segment_sets = parse_input_output_signals(&inputs);
+    let segment_sets = parse_input_output_signals(&inputs).unwrap();
     let segment_signal_sets = parse_full_signals(&segment_sets);
 
     let first_set = segment_signal_sets[0].clone();
@@ -374,10 +466,38 @@ fn test_output_decode() {
 
 #[test]
 fn test_output_sum() {
-    let inputs = read_lines("data/day_8_sample.txt");
-    let segment_sets = parse_input_output_signals(&inputs);
+    let inputs = read_lines("data/day_8_sample.txt").unwrap();
+    let segment_sets = parse_input_output_signals(&inputs).unwrap();
     let segment_signal_sets = parse_full_signals(&segment_sets);
 
     let sum = sum_outputs(&segment_signal_sets);
     assert_eq!(sum, 61229)
 }
+
+#[test]
+fn test_parse_malformed_signals() {
+    let inputs = vec!["be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb fdgacbe cefdb cgeb".to_string()];
+    let error = parse_input_output_signals(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 1: expected two values separated by ` | `");
+
+    let inputs = vec!["be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cgeb".to_string()];
+    let error = parse_input_output_signals(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 62: expected 4 patterns, found 3");
+
+    let inputs = vec!["be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cgeb gcbx".to_string()];
+    let error = parse_input_output_signals(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 84: expected a segment a to g, found `x`");
+
+    // Every pattern and output is well-formed, but they don't make up a display
+    let inputs = vec!["be be cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cgeb gcb".to_string()];
+    let error = parse_input_output_signals(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 1: expected the ten patterns to be the digits 0 to 9 on some wiring of the segments");
+
+    let inputs = vec!["be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cgeb gc".to_string()];
+    let error = parse_input_output_signals(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 62: expected every output to be one of the ten patterns, found `gc`");
+
+    let inputs = vec!["be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cgeb gcbb".to_string()];
+    let error = parse_input_output_signals(&inputs).unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 84: segment `b` appears twice in the pattern");
+}
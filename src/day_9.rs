@@ -1,257 +1,171 @@
+use std::cmp::Reverse;
 use std::ops::Index;
 use petgraph::algo::dijkstra;
-use petgraph::graph::{DiGraph, NodeIndex};
-use advent_of_code_2021::read_lines;
-
-pub(crate) fn run() {
-    let inputs = read_lines("data/day_9_input.txt");
-    let dem = parse_dem(&inputs);
-    let local_minima = collect_local_minima(&dem);
-    let risk_sum = local_minima
-        .iter()
-        .map(|m| m.risk )
-        .sum::<usize>();
-    println!("Risk levels total: {}", risk_sum);
-
-    let three_largest_basins = find_three_largest_basins(&local_minima, &dem);
-    let first_size = three_largest_basins[0].len();
-    let second_size = three_largest_basins[1].len();
-    let third_size = three_largest_basins[2].len();
-    println!("Size of three largest basins: {}, {}, {}", first_size, second_size, third_size);
-    println!("These multiplied is {}", first_size * second_size * third_size);
-}
+use crate::{parse_digit_grid, Answer, Grid, GridGraph, Neighbourhood, ParseError, Solution};
+#[cfg(test)]
+use crate::{read_lines, Value};
 
-// A digital elevation model (DEM)
-type DEM = Vec<Vec<usize>>;
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: usize = 9;
+    type Parsed = Dem;
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError> {
+        parse_dem(inputs)
+    }
+
+    fn part_one(&self, dem: &Self::Parsed) -> Result<Answer, ParseError> {
+        let local_minima = collect_local_minima(dem);
+        let risk_sum = local_minima
+            .iter()
+            .map(|m| m.risk )
+            .sum::<usize>();
 
-pub(crate) fn parse_dem(inputs: &Vec<String>) -> DEM {
-    let mut dem = Vec::new();
+        Ok(Answer::new(risk_sum, format!("Risk levels total: {}", risk_sum)))
+    }
 
-    for input in inputs {
-        let row = input
-            .chars()
-            .map(|c| c.to_digit(10).unwrap() as usize)
-            .collect();
-        dem.push(row);
+    fn part_two(&self, dem: &Self::Parsed) -> Result<Answer, ParseError> {
+        let local_minima = basin_low_points(dem)?;
+        let sizes = find_three_largest_basins(&local_minima, dem)
+            .iter()
+            .map(|basin| basin.len())
+            .collect::<Vec<_>>();
+        let product = sizes.iter().product::<usize>();
+        let sizes = sizes.iter().map(|size| size.to_string()).collect::<Vec<_>>();
+
+        Ok(Answer::new(product, format!(
+            "Size of three largest basins: {}\nThese multiplied is {}", sizes.join(", "), product
+        )))
     }
+}
 
-    dem
+// A digital elevation model (DEM)
+pub type Dem = Grid<usize>;
+
+pub fn parse_dem(inputs: &[String]) -> Result<Dem, ParseError> {
+    parse_digit_grid(inputs)
 }
 
-#[derive(Clone, PartialEq)]
-pub(crate) struct DEMPoint {
+#[derive(Clone, Debug, PartialEq)]
+pub struct DEMPoint {
     row: usize,
     column: usize,
     // Risk level is the height + 1
     risk: usize,
 }
 
-fn collect_local_minima(dem: &DEM) -> Vec<DEMPoint> {
-    let mut local_minima = Vec::new();
+impl DEMPoint {
+    /// The point at a row and column of the DEM, with the height there
+    pub fn new(row: usize, column: usize, height: usize) -> Self {
+        DEMPoint { row, column, risk: height + 1 }
+    }
 
-    for (row_idx, row) in dem.iter().enumerate() {
-        for (col_idx , entry) in row.iter().enumerate() {
-
-            // Continue if larger than the one above
-            if row_idx > 0 {
-                if entry >= &dem[row_idx - 1][col_idx] { continue }
-            }
-
-            // Continue if entry is larger than the one below
-            if let Some(next_row) = dem.get(row_idx + 1) {
-                if entry >= &next_row[col_idx] { continue }
-            }
-
-            // Continue if entry is larger than the one on the left
-            if col_idx > 0 {
-                if entry >= &dem[row_idx][col_idx - 1] { continue }
-            }
-
-            // Continue if entry is larger than the one on the right
-            if let Some(entry_to_right) = dem[row_idx].get(col_idx + 1) {
-                if entry >= &entry_to_right { continue }
-            }
-
-            local_minima.push(DEMPoint {
-                row: row_idx,
-                column: col_idx,
-                risk: *entry + 1
-            });
-        }
+    pub fn row(&self) -> usize {
+        self.row
     }
 
-    local_minima
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn height(&self) -> usize {
+        self.risk - 1
+    }
+
+    /// The risk level of the point, which is its height + 1
+    pub fn risk(&self) -> usize {
+        self.risk
+    }
 }
 
-type Basin = Vec<DEMPoint>;
+pub fn collect_local_minima(dem: &Dem) -> Vec<DEMPoint> {
+    let mut local_minima = Vec::new();
 
-pub(crate) fn find_three_largest_basins(minima: &Vec<DEMPoint>, dem: &DEM) -> Vec<Basin> {
-    let mut basins = Vec::new();
-    let dem_graph = graph_from_dem(&dem);
+    for ((row_idx, col_idx), entry) in dem.iter() {
+        // Continue if larger than or as large as any of the ones above, below, left or right
+        let mut neighbours = dem.neighbours((row_idx, col_idx), Neighbourhood::VonNeumann);
+        if neighbours.any(|neighbour| entry >= &dem[neighbour]) { continue }
 
-    for minimum in minima {
-        basins.push(expand_basin(minimum, &dem_graph, &dem).to_owned());
+        local_minima.push(DEMPoint {
+            row: row_idx,
+            column: col_idx,
+            risk: *entry + 1
+        });
     }
 
-    basins.sort_by(|a, b| b.len().cmp(&a.len()));
-    let three_largest_basins = basins[0..3].to_vec();
+    local_minima
+}
+
+pub type Basin = Vec<DEMPoint>;
+
+/// The low points of the DEM, each with a basin of its own, as long as there are at least three
+pub fn basin_low_points(dem: &Dem) -> Result<Vec<DEMPoint>, ParseError> {
+    let low_points = collect_local_minima(dem);
+    if low_points.len() < 3 {
+        return Err(ParseError::input(format!(
+            "expected at least 3 low points, for the three largest basins, found {}", low_points.len()
+        )));
+    }
 
-    three_largest_basins
+    Ok(low_points)
 }
 
-pub(crate) fn graph_from_dem(dem: &DEM) -> DiGraph<DEMPoint, ()> {
-    let mut dem_graph= DiGraph::new();
-    let starting_point = DEMPoint {
-        row: 0,
-        column: 0,
-        risk: dem[0][0] + 1
-    };
-    dem_graph.add_node(starting_point);
-
-    for (row_idx, row) in dem.iter().enumerate() {
-        for (col_idx, entry) in row.iter().enumerate() {
-            let dem_point_at_point = DEMPoint { row: row_idx, column: col_idx, risk: dem[row_idx][col_idx] + 1};
-
-            let node_at_point = find_node(&dem_graph, &dem_point_at_point)
-                .expect(&format!(
-                    "Didn't find node at row {}, column {}, risk {}",
-                    row_idx, col_idx, dem[row_idx][col_idx] + 1
-                ));
-
-            let row_idx_below = row_idx + 1;
-
-            // We only have to compare below and to the right in order to construct the graph
-            if let Some(row_below) = dem.get(row_idx_below) {
-                let point_below = DEMPoint {
-                    row: row_idx_below,
-                    column: col_idx,
-                    risk: dem[row_idx_below][col_idx] + 1
-                };
-
-                let node_below_idx;
-                if let Some(nb) = find_node(&dem_graph, &point_below) {
-                    node_below_idx = nb;
-                } else {
-                    node_below_idx = dem_graph.add_node(point_below);
-                }
-
-                if row_below[col_idx] > *entry {
-                    dem_graph.add_edge(node_at_point, node_below_idx, ());
-                } else if *entry > row_below[col_idx] {
-                    dem_graph.add_edge(node_below_idx, node_at_point, ());
-                }
-            }
-
-            let col_to_right = col_idx + 1;
-            if let Some(_) = row.get(col_to_right) {
-                let point_to_right = DEMPoint {
-                    row: row_idx,
-                    column: col_to_right,
-                    risk: dem[row_idx][col_to_right] + 1
-                };
-
-                let node_to_right;
-
-                if let Some(ntr) = find_node(&dem_graph, &point_to_right){
-                    node_to_right = ntr;
-                } else {
-                    node_to_right = dem_graph.add_node(point_to_right);
-                }
-
-                if row[col_to_right] > *entry {
-                    dem_graph.add_edge(node_at_point, node_to_right, ());
-                } else if *entry > row[col_to_right] {
-                    dem_graph.add_edge(node_to_right, node_at_point, ());
-                }
-            }
-        }
+pub fn find_three_largest_basins(minima: &[DEMPoint], dem: &Dem) -> Vec<Basin> {
+    let mut basins = Vec::new();
+    let dem_graph = graph_from_dem(dem);
+
+    for minimum in minima {
+        basins.push(expand_basin(minimum, &dem_graph, dem).to_owned());
     }
 
-    dem_graph
+    basins.sort_by_key(|basin| Reverse(basin.len()));
+    basins.truncate(3);
+    basins
 }
 
-pub(crate) fn find_node<T>(
-    dem_graph: &DiGraph<T, ()>,
-    needle: &T
-) -> Option<NodeIndex>
-where T: PartialEq
-{
-    dem_graph
-        .node_indices()
-        .find(|idx| dem_graph[*idx] == *needle)
+/// A graph of the DEM with edges running uphill, from each point to its higher neighbours
+pub type DemGraph = GridGraph<DEMPoint, ()>;
+
+pub fn graph_from_dem(dem: &Dem) -> DemGraph {
+    GridGraph::from_grid(
+        dem,
+        Neighbourhood::VonNeumann,
+        |(row, column), height| DEMPoint::new(row, column, *height),
+        |(_, from), (_, to)| if to > from { Some(()) } else { None },
+    )
 }
 
-pub(crate) fn expand_basin(minimum: &DEMPoint, dem_graph: &DiGraph<DEMPoint, ()>, dem: &DEM) -> Basin {
-    let minimum_node_idx = find_node(&dem_graph, minimum).unwrap();
+pub fn expand_basin(minimum: &DEMPoint, dem_graph: &DemGraph, dem: &Dem) -> Basin {
+    let minimum_node_idx = dem_graph.node((minimum.row, minimum.column)).unwrap();
 
-    let connected_nodes = dijkstra(dem_graph, minimum_node_idx, None, |_| 1)
-        .iter()
-        .map(|(node_idx, _)| dem_graph.index(node_idx.clone()).clone() )
+    let connected_nodes = dijkstra(dem_graph.graph(), minimum_node_idx, None, |_| 1).keys().map(|node_idx| dem_graph.graph().index(*node_idx).clone())
         .collect::<Vec<DEMPoint>>();
 
     let basin = connected_nodes
         .iter()
-        .filter(|dem_point| is_surrounded(dem_point, &connected_nodes, dem) )
-        .map(|dem_point| dem_point.clone() )
+        .filter(|dem_point| is_surrounded(dem_point, &connected_nodes, dem) ).cloned()
         .collect();
 
     basin
 }
 
-pub(crate) fn is_surrounded(dem_point: &DEMPoint, connected_nodes: &Vec<DEMPoint>, dem: &DEM) -> bool {
+pub fn is_surrounded(dem_point: &DEMPoint, connected_nodes: &[DEMPoint], dem: &Dem) -> bool {
     let row_idx = dem_point.row;
     let col_idx = dem_point.column;
 
     // Check for 9: it isn't part of any basin
     if dem_point.risk == 10 { return  false };
 
-    // Check above if present
-    if row_idx > 0 {
-        let row_idx_above = row_idx - 1;
-        let point_above = DEMPoint {
-            row: row_idx_above,
-            column: col_idx,
-            risk: dem[row_idx_above][col_idx] + 1
-        };
-
-        if !connected_nodes.contains(&point_above) { return false; }
-    }
-
-    // Check below if present
-    let row_idx_below = row_idx + 1;
-    if let Some(_) = dem.get(row_idx_below) {
-        let point_below = DEMPoint {
-            row: row_idx_below,
-            column: col_idx,
-            risk: dem[row_idx_below][col_idx] + 1
-        };
-
-        if !connected_nodes.contains(&point_below) { return false; }
-    }
-
-    // Check to left if present
-    if col_idx > 0 {
-        let col_idx_to_left = col_idx - 1;
-
-        let point_to_left = DEMPoint {
-            row: row_idx,
-            column: col_idx_to_left,
-            risk: dem[row_idx][col_idx_to_left] + 1
-        };
-
-        if !connected_nodes.contains(&point_to_left) { return false;}
-    }
-
-    // Check to right if present
-    let col_idx_to_right = col_idx + 1;
-    if let Some(_) = dem[row_idx].get(col_idx_to_right) {
-        let point_to_right = DEMPoint {
-            row: row_idx,
-            column: col_idx_to_right,
-            risk: dem[row_idx][col_idx_to_right] + 1
+    // Check above, below, left and right where present
+    for (row, column) in dem.neighbours((row_idx, col_idx), Neighbourhood::VonNeumann) {
+        let neighbour = DEMPoint {
+            row,
+            column,
+            risk: dem[(row, column)] + 1
         };
 
-        if !connected_nodes.contains(&point_to_right) { return false; }
+        if !connected_nodes.contains(&neighbour) { return false; }
     }
 
     true
@@ -260,21 +174,17 @@ pub(crate) fn is_surrounded(dem_point: &DEMPoint, connected_nodes: &Vec<DEMPoint
 #[cfg(test)]
 #[test]
 fn test_parse_dem() {
-    let inputs = read_lines("data/day_9_sample.txt");
-    let dem = parse_dem(&inputs);
-    assert_eq!(dem.len(), 5);
-
-    for row in &dem {
-        assert_eq!(row.len(), 10)
-    }
-
-    assert_eq!(dem[4][9], 8)
+    let inputs = read_lines("data/day_9_sample.txt").unwrap();
+    let dem = parse_dem(&inputs).unwrap();
+    assert_eq!(dem.height(), 5);
+    assert_eq!(dem.width(), 10);
+    assert_eq!(dem[(4, 9)], 8)
 }
 
 #[test]
 fn test_local_minima() {
-    let inputs = read_lines("data/day_9_sample.txt");
-    let dem = parse_dem(&inputs);
+    let inputs = read_lines("data/day_9_sample.txt").unwrap();
+    let dem = parse_dem(&inputs).unwrap();
     let minima = collect_local_minima(&dem);
 
     assert_eq!(minima.len(), 4);
@@ -286,23 +196,23 @@ fn test_local_minima() {
 
 #[test]
 fn test_graph_from_den() {
-    let inputs = read_lines("data/day_9_sample.txt");
-    let dem = parse_dem(&inputs);
+    let inputs = read_lines("data/day_9_sample.txt").unwrap();
+    let dem = parse_dem(&inputs).unwrap();
     let dem_graph = graph_from_dem(&dem);
 
-    let needle = &DEMPoint { row: 0, column: 0, risk: 3 };
-    let found = find_node(&dem_graph, needle).unwrap();
+    let found = dem_graph.node((0, 0)).unwrap();
     assert_eq!(found.index(), 0);
+    assert_eq!(dem_graph.graph()[found], DEMPoint { row: 0, column: 0, risk: 3 });
 
-    let dem_rows = dem.len();
-    let dem_cols = dem[0].len();
-    assert_eq!(dem_graph.node_indices().len(), dem_rows * dem_cols)
+    let dem_rows = dem.height();
+    let dem_cols = dem.width();
+    assert_eq!(dem_graph.graph().node_indices().len(), dem_rows * dem_cols)
 }
 
 #[test]
 fn test_is_surrounded() {
-    let inputs = read_lines("data/day_9_sample.txt");
-    let dem = parse_dem(&inputs);
+    let inputs = read_lines("data/day_9_sample.txt").unwrap();
+    let dem = parse_dem(&inputs).unwrap();
     let dem_graph = graph_from_dem(&dem);
 
     let global_minimum = DEMPoint{
@@ -310,10 +220,8 @@ fn test_is_surrounded() {
         column: 2,
         risk: 6
     };
-    let glob_min_idx = find_node(&dem_graph, &global_minimum).unwrap();
-    let connected_to_global_minimum = dijkstra(&dem_graph, glob_min_idx, None, |_| 1)
-        .iter()
-        .map(|(node_idx, _)| dem_graph.index(node_idx.clone()).clone() )
+    let glob_min_idx = dem_graph.node((global_minimum.row, global_minimum.column)).unwrap();
+    let connected_to_global_minimum = dijkstra(dem_graph.graph(), glob_min_idx, None, |_| 1).keys().map(|node_idx| dem_graph.graph().index(*node_idx).clone())
         .collect::<Vec<DEMPoint>>();
 
     let test_point = DEMPoint {
@@ -322,13 +230,13 @@ fn test_is_surrounded() {
         risk: 10,
     };
     let is_point_surrounded = is_surrounded(&test_point, &connected_to_global_minimum, &dem);
-    assert_eq!(is_point_surrounded, false);
+    assert!(!is_point_surrounded);
 }
 
 #[test]
 fn test_find_three_largest_basins() {
-    let inputs = read_lines("data/day_9_sample.txt");
-    let dem = parse_dem(&inputs);
+    let inputs = read_lines("data/day_9_sample.txt").unwrap();
+    let dem = parse_dem(&inputs).unwrap();
     let minima = collect_local_minima(&dem);
     let largest_basins = find_three_largest_basins(&minima, &dem);
 
@@ -336,4 +244,18 @@ fn test_find_three_largest_basins() {
 
     let first_basin = largest_basins[0].clone();
     assert_eq!(first_basin.len(), 14)
-}
\ No newline at end of file
+}
+#[test]
+fn test_fewer_than_three_basins() {
+    // A single low point in the middle
+    let inputs = vec!["999".to_string(), "909".to_string(), "999".to_string()];
+    let dem = parse_dem(&inputs).unwrap();
+    assert_eq!(find_three_largest_basins(&collect_local_minima(&dem), &dem).len(), 1);
+
+    let error = basin_low_points(&dem).unwrap_err();
+    assert_eq!(error.to_string(), "expected at least 3 low points, for the three largest basins, found 1");
+
+    // Part one doesn't need any basins
+    assert_eq!(Day9.part_one(&dem).unwrap().value, Value::from(1));
+    assert_eq!(Day9.part_two(&dem).unwrap_err(), error);
+}
@@ -0,0 +1,143 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Malformed puzzle input, pointing out where in the input it went wrong
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    file: Option<String>,
+    // Zero-based line and column, absent when the input as a whole is at fault
+    position: Option<(usize, usize)>,
+    message: String,
+}
+
+impl ParseError {
+    /// An error at a zero-based line and column of the input, as they come out of `enumerate`
+    pub fn at(line: usize, column: usize, message: impl Into<String>) -> Self {
+        ParseError { file: None, position: Some((line, column)), message: message.into() }
+    }
+
+    /// An error about the input as a whole, like it being empty
+    pub fn input(message: impl Into<String>) -> Self {
+        ParseError { file: None, position: None, message: message.into() }
+    }
+
+    /// Attributes the error to the file the input was read from, `-` being stdin
+    pub fn in_file(mut self, file: &str) -> Self {
+        self.file = Some(if file == "-" { "<stdin>".to_string() } else { file.to_string() });
+        self
+    }
+
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// The one-based line number the error is on
+    pub fn line(&self) -> Option<usize> {
+        self.position.map(|(line, _)| line + 1)
+    }
+
+    /// The one-based column number the error is at
+    pub fn column(&self) -> Option<usize> {
+        self.position.map(|(_, column)| column + 1)
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.line(), self.column()) {
+            (Some(file), Some(line), Some(column)) => write!(f, "{}:{}:{}: {}", file, line, column, self.message),
+            (None, Some(line), Some(column)) => write!(f, "line {}, column {}: {}", line, column, self.message),
+            (Some(file), _, _) => write!(f, "{}: {}", file, self.message),
+            (None, _, _) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// A field of a line, along with the zero-based column it starts at
+pub type Field<'a> = (usize, &'a str);
+
+/// Splits a line on a separator, pairing each field with the zero-based column it starts at
+pub fn fields<'a>(line: &'a str, separator: &'a str) -> impl Iterator<Item = Field<'a>> + 'a {
+    line.split(separator).map(move |field| (column_of(line, field), field))
+}
+
+/// Splits a line on whitespace, pairing each field with the zero-based column it starts at
+pub fn whitespace_fields(line: &str) -> impl Iterator<Item = Field<'_>> {
+    line.split_ascii_whitespace().map(move |field| (column_of(line, field), field))
+}
+
+/// Splits a line in the two fields on either side of a separator, which has to be there
+pub fn split_pair<'a>(
+    line: &'a str,
+    separator: &str,
+    line_idx: usize,
+) -> Result<(Field<'a>, Field<'a>), ParseError> {
+    match line.split_once(separator) {
+        Some((left, right)) => Ok(((0, left), (left.len() + separator.len(), right))),
+        None => Err(ParseError::at(line_idx, 0, format!("expected two values separated by `{}`", separator))),
+    }
+}
+
+/// Parses a field at a zero-based line and column into a number
+pub fn parse_number<T: FromStr>(field: &str, line: usize, column: usize) -> Result<T, ParseError> {
+    field.parse::<T>().map_err(|_| {
+        if field.is_empty() {
+            ParseError::at(line, column, "expected a number, found nothing")
+        } else {
+            ParseError::at(line, column, format!("expected a number, found `{}`", field))
+        }
+    })
+}
+
+// The byte offset of a field within the line it was split off from
+fn column_of(line: &str, field: &str) -> usize {
+    field.as_ptr() as usize - line.as_ptr() as usize
+}
+
+#[cfg(test)]
+#[test]
+fn test_display() {
+    let error = ParseError::at(2, 4, "expected a number, found `x`");
+    assert_eq!(error.to_string(), "line 3, column 5: expected a number, found `x`");
+    assert_eq!(error.line(), Some(3));
+    assert_eq!(error.column(), Some(5));
+
+    let error = error.in_file("data/day_1_input.txt");
+    assert_eq!(error.to_string(), "data/day_1_input.txt:3:5: expected a number, found `x`");
+
+    let error = ParseError::input("the input is empty").in_file("-");
+    assert_eq!(error.to_string(), "<stdin>: the input is empty");
+    assert_eq!(error.line(), None);
+}
+
+#[test]
+fn test_fields() {
+    let split = fields("3,40,5", ",").collect::<Vec<_>>();
+    assert_eq!(split, vec![(0, "3"), (2, "40"), (5, "5")]);
+
+    let split = whitespace_fields(" 22 13  17").collect::<Vec<_>>();
+    assert_eq!(split, vec![(1, "22"), (4, "13"), (8, "17")]);
+
+    assert_eq!(split_pair("0,9 -> 5,9", " -> ", 0), Ok(((0, "0,9"), (7, "5,9"))));
+    assert!(split_pair("0,9 5,9", " -> ", 0).is_err());
+}
+
+#[test]
+fn test_parse_number() {
+    assert_eq!(parse_number::<usize>("42", 0, 0), Ok(42));
+    assert_eq!(
+        parse_number::<usize>("4x", 1, 3).unwrap_err().to_string(),
+        "line 2, column 4: expected a number, found `4x`"
+    );
+    assert_eq!(
+        parse_number::<usize>("", 0, 0).unwrap_err().message(),
+        "expected a number, found nothing"
+    );
+}
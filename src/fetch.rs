@@ -0,0 +1,208 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+use log::info;
+#[cfg(test)]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(test)]
+use std::net::TcpListener;
+#[cfg(test)]
+use std::thread;
+
+/// The year of the advent these are the solutions for
+pub(crate) const YEAR: usize = 2021;
+
+pub(crate) const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Environment variable holding the session token, which overrules the one in the config file
+pub(crate) const SESSION_VAR: &str = "AOC_SESSION";
+
+/// Advent of Code asks for a user agent that says where the requests come from
+const USER_AGENT: &str = "advent_of_code_2021 by rein@vantveer.me";
+
+/// Settings for talking to the Advent of Code website, from a TOML file like:
+///
+/// ```toml
+/// session = "53616c746564..."
+/// base_url = "http://localhost:8080"
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Config {
+    pub session: Option<String>,
+    pub base_url: Option<String>,
+}
+
+pub(crate) fn parse_config(text: &str) -> Result<Config, String> {
+    let table = text.parse::<toml::Table>().map_err(|e| e.to_string())?;
+    let setting = |key| match table.get(key) {
+        Some(toml::Value::String(value)) => Ok(Some(value.trim().to_string())),
+        Some(_) => Err(format!("{} should be a string", key)),
+        None => Ok(None),
+    };
+
+    Ok(Config { session: setting("session")?, base_url: setting("base_url")? })
+}
+
+/// Reads the config file, which doesn't have to exist
+pub(crate) fn load_config(path: &str) -> Result<Config, String> {
+    if !Path::new(path).exists() {
+        return Ok(Config::default());
+    }
+
+    fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| parse_config(&text))
+        .map_err(|e| format!("Can't read the config in {}: {}", path, e))
+}
+
+/// The session token from the environment variable if it's set, or otherwise the config
+pub(crate) fn session_token(from_env: Option<String>, config: &Config) -> Option<String> {
+    from_env
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+        .or_else(|| config.session.clone())
+}
+
+/// Sends a request as the logged in user, with the session token as a cookie
+pub(crate) fn request(method: &str, url: &str, session: &str) -> ureq::Request {
+    ureq::request(method, url)
+        .timeout(Duration::from_secs(30))
+        .set("Cookie", &format!("session={}", session))
+        .set("User-Agent", USER_AGENT)
+}
+
+/// Describes a failed request, with the start of what the server said about it
+pub(crate) fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(status, response) => {
+            let body = response.into_string().unwrap_or_default();
+            let first_line = body.lines().next().unwrap_or("").trim();
+            format!("the server answered {}: {}", status, first_line)
+        }
+        ureq::Error::Transport(transport) => transport.to_string(),
+    }
+}
+
+pub(crate) fn input_url(base_url: &str, day: usize) -> String {
+    format!("{}/{}/day/{}/input", base_url.trim_end_matches('/'), YEAR, day)
+}
+
+/// What came of fetching a puzzle input
+#[derive(Debug, PartialEq)]
+pub(crate) enum Fetched {
+    Downloaded(usize),
+    AlreadyThere,
+}
+
+/// Downloads the puzzle input of a day to `path`, unless there already is a file there. Puzzle
+/// inputs never change, so there's no need to ever download one again.
+pub(crate) fn fetch_input(base_url: &str, session: &str, day: usize, path: &str) -> Result<Fetched, String> {
+    if Path::new(path).exists() {
+        return Ok(Fetched::AlreadyThere);
+    }
+
+    let url = input_url(base_url, day);
+    info!("Downloading the puzzle input for day {} from {}", day, url);
+    let response = request("GET", &url, session).call().map_err(describe_error)?;
+
+    let mut input = Vec::new();
+    response.into_reader().read_to_end(&mut input).map_err(|e| e.to_string())?;
+
+    // Write to a file alongside first, so a failed write doesn't leave half an input behind
+    if let Some(dir) = Path::new(path).parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let partial_path = format!("{}.partial", path);
+    fs::write(&partial_path, &input)
+        .and_then(|_| fs::rename(&partial_path, path))
+        .map_err(|e| e.to_string())?;
+
+    Ok(Fetched::Downloaded(input.len()))
+}
+
+/// Answers a single request on a local port with the given status and body, like the Advent of
+/// Code website would. Returns the base URL to reach it, and the request it got once it's done.
+#[cfg(test)]
+pub(crate) fn mock_server(status: u16, body: &'static str) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(&stream);
+        let mut request = String::new();
+        let mut content_length = 0;
+
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                content_length = length.trim().parse().unwrap();
+            }
+            request.push_str(&line);
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let mut request_body = vec![0; content_length];
+        reader.read_exact(&mut request_body).unwrap();
+        request.push_str(&String::from_utf8(request_body).unwrap());
+
+        write!(
+            &stream,
+            "HTTP/1.1 {} Mocked\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, body.len(), body
+        ).unwrap();
+        request
+    });
+
+    (base_url, server)
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_config() {
+    assert_eq!(
+        parse_config("session = \"abc123\"\nbase_url = \"http://localhost:8080\"\n"),
+        Ok(Config { session: Some("abc123".to_string()), base_url: Some("http://localhost:8080".to_string()) })
+    );
+    assert_eq!(parse_config(""), Ok(Config::default()));
+    assert_eq!(parse_config("session = 123"), Err("session should be a string".to_string()));
+}
+
+#[test]
+fn test_session_token() {
+    let config = Config { session: Some("from config".to_string()), base_url: None };
+
+    assert_eq!(session_token(Some("from env\n".to_string()), &config), Some("from env".to_string()));
+    assert_eq!(session_token(Some("".to_string()), &config), Some("from config".to_string()));
+    assert_eq!(session_token(None, &config), Some("from config".to_string()));
+    assert_eq!(session_token(None, &Config::default()), None);
+}
+
+#[test]
+fn test_fetch_input() {
+    let dir = std::env::temp_dir().join(format!("advent_of_code_2021_fetch_{}", std::process::id()));
+    let path = dir.join("day_1_input.txt");
+    let path = path.to_str().unwrap();
+
+    let (base_url, server) = mock_server(200, "199\n200\n208\n");
+    assert_eq!(fetch_input(&base_url, "abc123", 1, path), Ok(Fetched::Downloaded(12)));
+    let request = server.join().unwrap();
+    assert!(request.starts_with("GET /2021/day/1/input HTTP/1.1\r\n"));
+    assert!(request.contains("\r\nCookie: session=abc123\r\n"));
+    assert_eq!(fs::read_to_string(path).unwrap(), "199\n200\n208\n");
+
+    // Nothing listens on the URL anymore, so it would fail if it tried to download it again
+    assert_eq!(fetch_input(&base_url, "abc123", 1, path), Ok(Fetched::AlreadyThere));
+
+    let (base_url, _) = mock_server(404, "Please don't repeatedly request this endpoint before it unlocks!\n");
+    assert_eq!(
+        fetch_input(&base_url, "abc123", 2, dir.join("day_2_input.txt").to_str().unwrap()),
+        Err("the server answered 404: Please don't repeatedly request this endpoint before it unlocks!".to_string())
+    );
+    assert!(!dir.join("day_2_input.txt").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,556 @@
+use std::collections::BTreeSet;
+use crate::{day_11, day_13, day_5, day_7, day_8, registry};
+
+/// A small, fast pseudo-random number generator, good enough for puzzle inputs. The same seed
+/// always gives the same numbers, on any platform.
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to but not including `bound`, which has to be positive
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "Can't pick a number below 0");
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// A number from `low` up to and including `high`
+    pub fn between(&mut self, low: usize, high: usize) -> usize {
+        low + self.below(high - low + 1)
+    }
+
+    /// Whether an event with a chance of one in `n` happens
+    pub fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    /// Shuffles the items in place, with every order being equally likely
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.below(idx + 1));
+        }
+    }
+}
+
+/// The days there's an input generator for, which are all the days with a solution
+pub fn days() -> Vec<usize> {
+    registry::solvers().iter().map(|solver| solver.day()).collect()
+}
+
+/// Generates a puzzle input for a day that its solution can parse and solve. The size is the
+/// number of lines, numbers or cells along a side, depending on the day, and is raised to a
+/// minimum where the puzzle needs it. Returns `None` for days without a generator.
+pub fn generate(day: usize, seed: u64, size: usize) -> Option<Vec<String>> {
+    let rng = &mut SplitMix64::new(seed);
+
+    let lines = match day {
+        1 => sonar_depths(rng, size.max(1)),
+        2 => movement_commands(rng, size.max(1)),
+        3 => diagnostic_report(rng, size.max(1)),
+        4 => bingo(rng, size.max(1)),
+        5 => vent_lines(rng, size.max(1)),
+        6 => comma_separated(rng, size.max(1), 1, 5),
+        7 => comma_separated(rng, size.max(1), 0, (size * 2).min(day_7::MAX_POSITION)),
+        8 => segment_displays(rng, size.max(1)),
+        9 => dem(rng, size.max(6)),
+        10 => navigation_subsystem(rng, size.max(1)),
+        11 => synchronizing_octopi(rng, size.max(1)),
+        12 => cave_graph(rng, size.max(2)),
+        13 => fold_sheet(rng, size.max(1)),
+        14 => polymer_rules(rng, size.max(2)),
+        15 => digit_grid(rng, size.max(1), 1, 9),
+        16 => bits_transmission(rng, size.clamp(1, MAX_PACKET_COUNT)),
+        _ => return None,
+    };
+
+    Some(lines)
+}
+
+fn sonar_depths(rng: &mut SplitMix64, count: usize) -> Vec<String> {
+    let mut depth = rng.between(100, 200);
+
+    (0..count)
+        .map(|_| {
+            // Mostly going deeper, like the sea floor in the puzzle
+            depth = (depth + rng.between(0, 50)).saturating_sub(20);
+            depth.to_string()
+        })
+        .collect()
+}
+
+fn movement_commands(rng: &mut SplitMix64, count: usize) -> Vec<String> {
+    let mut depth = 0;
+
+    (0..count)
+        .map(|_| {
+            let amount = rng.between(1, 9);
+            // The submarine can't go up out of the water
+            let direction = match rng.below(3) {
+                0 => "forward",
+                1 if depth >= amount => "up",
+                _ => "down",
+            };
+            match direction {
+                "up" => depth -= amount,
+                "down" => depth += amount,
+                _ => (),
+            }
+
+            format!("{} {}", direction, amount)
+        })
+        .collect()
+}
+
+fn diagnostic_report(rng: &mut SplitMix64, count: usize) -> Vec<String> {
+    // Twice as many numbers to pick from as needed, so they can all be different
+    let width = (bit_length(count) + 1).max(5);
+    let mut numbers = (0..1_usize << width).collect::<Vec<_>>();
+    rng.shuffle(&mut numbers);
+
+    numbers
+        .iter()
+        .take(count)
+        .map(|number| format!("{:0width$b}", number, width = width))
+        .collect()
+}
+
+fn bingo(rng: &mut SplitMix64, board_count: usize) -> Vec<String> {
+    // Calling every number means every board wins in the end
+    let mut calls = (0..100).collect::<Vec<usize>>();
+    rng.shuffle(&mut calls);
+    let calls = calls.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    let mut lines = vec![calls.join(",")];
+
+    for _ in 0..board_count {
+        let mut numbers = (0..100).collect::<Vec<usize>>();
+        rng.shuffle(&mut numbers);
+
+        lines.push(String::new());
+        for row in numbers[..25].chunks(5) {
+            lines.push(row.iter().map(|n| format!("{:>2}", n)).collect::<Vec<_>>().join(" "));
+        }
+    }
+
+    lines
+}
+
+fn vent_lines(rng: &mut SplitMix64, count: usize) -> Vec<String> {
+    let extent = (count * 2).clamp(10, day_5::MAX_COORDINATE + 1);
+
+    (0..count)
+        .map(|_| {
+            let (x1, y1) = (rng.below(extent), rng.below(extent));
+            let (x2, y2) = match rng.below(3) {
+                0 => (rng.below(extent), y1),
+                1 => (x1, rng.below(extent)),
+                _ => {
+                    // Diagonals are at 45 degrees, so they can only go as far as the nearest edge
+                    let (right, down) = (rng.one_in(2), rng.one_in(2));
+                    let room_x = if right { extent - 1 - x1 } else { x1 };
+                    let room_y = if down { extent - 1 - y1 } else { y1 };
+                    let length = rng.between(0, room_x.min(room_y));
+
+                    let x2 = if right { x1 + length } else { x1 - length };
+                    let y2 = if down { y1 + length } else { y1 - length };
+                    (x2, y2)
+                }
+            };
+
+            format!("{},{} -> {},{}", x1, y1, x2, y2)
+        })
+        .collect()
+}
+
+fn comma_separated(rng: &mut SplitMix64, count: usize, low: usize, high: usize) -> Vec<String> {
+    let numbers = (0..count).map(|_| rng.between(low, high).to_string()).collect::<Vec<_>>();
+    vec![numbers.join(",")]
+}
+
+fn segment_displays(rng: &mut SplitMix64, count: usize) -> Vec<String> {
+    (0..count)
+        .map(|_| {
+            // Each display has its wires crossed in its own way
+            let mut wiring = "abcdefg".chars().collect::<Vec<_>>();
+            rng.shuffle(&mut wiring);
+
+            let mut patterns = (0..10).map(|digit| wired_pattern(rng, &wiring, digit)).collect::<Vec<_>>();
+            rng.shuffle(&mut patterns);
+            let outputs = (0..4)
+                .map(|_| {
+                    let digit = rng.below(10);
+                    wired_pattern(rng, &wiring, digit)
+                })
+                .collect::<Vec<_>>();
+
+            format!("{} | {}", patterns.join(" "), outputs.join(" "))
+        })
+        .collect()
+}
+
+// The segments lighting up for a digit on a display with crossed wires, in any order
+fn wired_pattern(rng: &mut SplitMix64, wiring: &[char], digit: usize) -> String {
+    let mut segments = day_8::DIGIT_SEGMENTS[digit]
+        .chars()
+        .map(|segment| wiring[(segment as u8 - b'a') as usize])
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut segments);
+
+    segments.into_iter().collect()
+}
+
+fn dem(rng: &mut SplitMix64, side: usize) -> Vec<String> {
+    // Ridges of 9 along every fifth row and column wall off the basins, each of which gets a
+    // lowest point of 0 in its top left corner so there are always enough of them
+    (0..side)
+        .map(|row| {
+            (0..side)
+                .map(|column| match (row % 5, column % 5) {
+                    (4, _) | (_, 4) => 9,
+                    (0, 0) => 0,
+                    _ => rng.between(1, 8),
+                })
+                .map(|height| char::from_digit(height as u32, 10).unwrap())
+                .collect()
+        })
+        .collect()
+}
+
+fn navigation_subsystem(rng: &mut SplitMix64, count: usize) -> Vec<String> {
+    const PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+    // Autocompletion scores grow fivefold with each unclosed chunk, so keep them shallow
+    const MAX_DEPTH: usize = 20;
+
+    // Half the lines are corrupted, but part two needs an odd number of incomplete ones to take
+    // the middle score of, so the first one is always incomplete and the last one makes it odd
+    let mut corrupted = (0..count).map(|line_idx| line_idx > 0 && rng.one_in(2)).collect::<Vec<_>>();
+    if corrupted.iter().filter(|&&is_corrupted| !is_corrupted).count() % 2 == 0 {
+        corrupted[count - 1] = !corrupted[count - 1];
+    }
+
+    corrupted
+        .into_iter()
+        .map(|is_corrupted| {
+            let length = rng.between(10, 40);
+            // Corrupted from this position on, as soon as there's a chunk open to close wrongly
+            let corrupt_from = rng.below(length);
+            let mut has_wrong_close = false;
+            let mut open = Vec::new();
+            let mut line = String::new();
+
+            for position in 0..length {
+                if is_corrupted && !has_wrong_close && position >= corrupt_from && !open.is_empty() {
+                    let expected = open.pop().unwrap();
+                    line.push(wrong_close(rng, expected));
+                    has_wrong_close = true;
+                } else if !open.is_empty() && (open.len() >= MAX_DEPTH || rng.one_in(2)) {
+                    line.push(open.pop().unwrap());
+                } else {
+                    let (opening, closing) = *rng.choose(&PAIRS);
+                    line.push(opening);
+                    open.push(closing);
+                }
+            }
+
+            // A corrupted line needs a wrong closing character, and an incomplete line has to
+            // leave at least one chunk open
+            if is_corrupted && !has_wrong_close {
+                let (opening, closing) = *rng.choose(&PAIRS);
+                line.push(opening);
+                line.push(wrong_close(rng, closing));
+            } else if !is_corrupted && open.is_empty() {
+                line.push(rng.choose(&PAIRS).0);
+            }
+
+            line
+        })
+        .collect()
+}
+
+/// Any closing character but the expected one
+fn wrong_close(rng: &mut SplitMix64, expected: char) -> char {
+    let wrong = ")]}>".chars().filter(|&close| close != expected).collect::<Vec<_>>();
+    *rng.choose(&wrong)
+}
+
+fn digit_grid(rng: &mut SplitMix64, side: usize, low: usize, high: usize) -> Vec<String> {
+    (0..side)
+        .map(|_| (0..side).map(|_| char::from_digit(rng.between(low, high) as u32, 10).unwrap()).collect())
+        .collect()
+}
+
+/// Octopi that all flash at the same time within `day_11::MAX_STEPS`, which not every grid does.
+/// Most small grids do, so it keeps drawing them, and falls back on octopi that all start out
+/// with the same energy, which flash together from the start.
+fn synchronizing_octopi(rng: &mut SplitMix64, side: usize) -> Vec<String> {
+    for _ in 0..100 {
+        let lines = digit_grid(rng, side, 0, 9);
+        let mut octopi = day_11::octopi_from_input(&lines).expect("generated digit grids are valid");
+        let synchronizes = (0..day_11::MAX_STEPS).any(|_| {
+            day_11::simple_energy_increase(&mut octopi);
+            day_11::flash_octopi(&mut octopi) == side * side
+        });
+        if synchronizes {
+            return lines;
+        }
+    }
+
+    let energy = rng.between(0, 9).to_string();
+    vec![energy.repeat(side); side]
+}
+
+fn cave_graph(rng: &mut SplitMix64, small_count: usize) -> Vec<String> {
+    let small = (0..small_count).map(|idx| cave_name(idx, false)).collect::<Vec<_>>();
+    let big = (0..(small_count / 4).max(1)).map(|idx| cave_name(idx, true)).collect::<Vec<_>>();
+    let all = small.iter().chain(&big).cloned().collect::<Vec<_>>();
+
+    // Big caves are never connected to each other, or there would be endless paths through them
+    let mut tunnels = BTreeSet::new();
+    for _ in 0..2 {
+        tunnels.insert(("start".to_string(), rng.choose(&all).clone()));
+        tunnels.insert((rng.choose(&all).clone(), "end".to_string()));
+    }
+    for (idx, cave) in small.iter().enumerate() {
+        let other = rng.choose(&all);
+        if other != cave {
+            tunnels.insert((cave.clone(), other.clone()));
+        }
+        // Chain the small caves so most of them can be reached
+        if let Some(next) = small.get(idx + 1) {
+            tunnels.insert((cave.clone(), next.clone()));
+        }
+    }
+    for cave in &big {
+        tunnels.insert((cave.clone(), rng.choose(&small).clone()));
+    }
+
+    tunnels.into_iter().map(|(from, to)| format!("{}-{}", from, to)).collect()
+}
+
+// Two or more letters, counting from aa (or AA for big caves) onwards
+fn cave_name(idx: usize, big: bool) -> String {
+    let base = if big { b'A' } else { b'a' };
+    let mut name = vec![base + (idx % 26) as u8];
+    let mut rest = idx / 26;
+
+    loop {
+        name.push(base + (rest % 26) as u8);
+        rest /= 26;
+        if rest == 0 { break; }
+    }
+
+    name.into_iter().rev().map(char::from).collect()
+}
+
+fn fold_sheet(rng: &mut SplitMix64, dot_count: usize) -> Vec<String> {
+    // Each pair of folds halves the sheet both ways, down to a code the size of the puzzle's,
+    // with as many pairs as the coordinates day 13 takes leave room for
+    let mut fold_pairs = 1 + bit_length(dot_count) / 4;
+    while (41 << fold_pairs) - 1 > day_13::MAX_COORDINATE + 1 {
+        fold_pairs -= 1;
+    }
+    let mut width = (41 << fold_pairs) - 1;
+    let mut height = (7 << fold_pairs) - 1;
+
+    let mut lines = (0..dot_count)
+        .map(|_| format!("{},{}", rng.below(width), rng.below(height)))
+        .collect::<Vec<_>>();
+    lines.push(String::new());
+
+    for _ in 0..fold_pairs {
+        width /= 2;
+        height /= 2;
+        lines.push(format!("fold along x={}", width));
+        lines.push(format!("fold along y={}", height));
+    }
+
+    lines
+}
+
+fn polymer_rules(rng: &mut SplitMix64, template_length: usize) -> Vec<String> {
+    const ELEMENTS: [char; 10] = ['B', 'C', 'F', 'H', 'K', 'N', 'O', 'P', 'S', 'V'];
+
+    let template = (0..template_length).map(|_| *rng.choose(&ELEMENTS)).collect::<String>();
+    let mut lines = vec![template, String::new()];
+
+    // A rule for every pair, so every pair of the polymer grows
+    for first in ELEMENTS {
+        for second in ELEMENTS {
+            lines.push(format!("{}{} -> {}", first, second, rng.choose(&ELEMENTS)));
+        }
+    }
+
+    lines
+}
+
+// An operator counting its sub-packets has 11 bits for the count
+const MAX_PACKET_COUNT: usize = (1 << 11) - 1;
+
+fn bits_transmission(rng: &mut SplitMix64, packet_count: usize) -> Vec<String> {
+    // The outermost packet sums all the others
+    let sub_packets = (0..packet_count).map(|_| random_packet(rng, 2)).collect::<Vec<_>>();
+    let mut bits = Vec::new();
+    push_bits(&mut bits, rng.below(8), 3);
+    push_bits(&mut bits, 0, 3);
+    push_bits(&mut bits, 1, 1);
+    push_bits(&mut bits, packet_count, 11);
+    for sub_packet in sub_packets {
+        bits.extend(sub_packet);
+    }
+
+    // Pad to whole bytes, as the transmission is read as hexadecimal pairs
+    while bits.len() % 8 != 0 {
+        bits.push(false);
+    }
+
+    let hex = bits
+        .chunks(4)
+        .map(|nibble| nibble.iter().fold(0, |value, &bit| (value << 1) | bit as u32))
+        .map(|value| char::from_digit(value, 16).unwrap().to_ascii_uppercase())
+        .collect();
+    vec![hex]
+}
+
+// The bits of a random packet with operators nested up to `depth` deep. Literals stay below a
+// thousand and products take at most two sub-packets, so evaluating it can't overflow.
+fn random_packet(rng: &mut SplitMix64, depth: usize) -> Vec<bool> {
+    let mut bits = Vec::new();
+    let type_id = if depth == 0 { 4 } else { rng.below(8) };
+    push_bits(&mut bits, rng.below(8), 3);
+    push_bits(&mut bits, type_id, 3);
+
+    if type_id == 4 {
+        let value = rng.below(1000);
+        let groups = bit_length(value).div_ceil(4);
+        for group in (0..groups.max(1)).rev() {
+            push_bits(&mut bits, (group > 0) as usize, 1);
+            push_bits(&mut bits, (value >> (group * 4)) & 0xF, 4);
+        }
+        return bits;
+    }
+
+    let sub_packet_count = match type_id {
+        1 => rng.between(1, 2),
+        5..=7 => 2,
+        _ => rng.between(1, 3),
+    };
+    let sub_packets = (0..sub_packet_count).map(|_| random_packet(rng, depth - 1)).collect::<Vec<_>>().concat();
+
+    if rng.one_in(2) {
+        push_bits(&mut bits, 0, 1);
+        push_bits(&mut bits, sub_packets.len(), 15);
+    } else {
+        push_bits(&mut bits, 1, 1);
+        push_bits(&mut bits, sub_packet_count, 11);
+    }
+    bits.extend(sub_packets);
+
+    bits
+}
+
+// Appends the lowest `count` bits of a value, most significant first
+fn push_bits(bits: &mut Vec<bool>, value: usize, count: usize) {
+    bits.extend((0..count).rev().map(|shift| (value >> shift) & 1 == 1));
+}
+
+// How many bits it takes to write a number
+fn bit_length(number: usize) -> usize {
+    (usize::BITS - number.leading_zeros()) as usize
+}
+
+#[cfg(test)]
+use crate::day_10::{read_tokens, syntax_check, Syntactical};
+#[cfg(test)]
+use crate::Part;
+
+#[cfg(test)]
+#[test]
+fn test_split_mix_64() {
+    let mut rng = SplitMix64::new(1234567);
+    assert_eq!(rng.next_u64(), 6457827717110365317);
+    assert_eq!(rng.next_u64(), 3203168211198807973);
+
+    let mut numbers = (0..10).collect::<Vec<_>>();
+    rng.shuffle(&mut numbers);
+    numbers.sort();
+    assert_eq!(numbers, (0..10).collect::<Vec<_>>());
+    assert!((0..100).all(|_| (3..=5).contains(&rng.between(3, 5))));
+}
+
+#[test]
+fn test_generate_is_seeded() {
+    assert_eq!(generate(5, 42, 20), generate(5, 42, 20));
+    assert_ne!(generate(5, 42, 20), generate(5, 43, 20));
+    assert_eq!(generate(17, 42, 20), None);
+}
+
+#[test]
+fn test_generated_inputs_are_solvable() {
+    for day in days() {
+        let solver = registry::find_solver(day).unwrap();
+
+        // Small enough for day 12, where the number of paths explodes with every extra cave
+        for seed in 0..3 {
+            let inputs = generate(day, seed, 6).unwrap();
+            if let Err(e) = solver.solve(&inputs, &Part::ALL) {
+                panic!("Generated input for day {} with seed {} is malformed: {}", day, seed, e);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_generated_sizes() {
+    assert_eq!(generate(1, 0, 30).unwrap().len(), 30);
+    assert_eq!(generate(4, 0, 3).unwrap().len(), 1 + 3 * 6);
+    assert_eq!(generate(9, 0, 20).unwrap()[0].len(), 20);
+    assert_eq!(generate(16, 0, 5000).unwrap(), generate(16, 0, MAX_PACKET_COUNT).unwrap());
+}
+
+#[test]
+fn test_generated_octopi_synchronize() {
+    for (seed, side) in (0..10).zip([1, 2, 3, 4, 5, 6, 8, 10, 12, 15]) {
+        let mut octopi = day_11::octopi_from_input(&generate(11, seed, side).unwrap()).unwrap();
+        assert!((0..day_11::MAX_STEPS).any(|_| {
+            day_11::simple_energy_increase(&mut octopi);
+            day_11::flash_octopi(&mut octopi) == side * side
+        }));
+    }
+}
+
+#[test]
+fn test_generated_incomplete_lines_are_odd() {
+    for (seed, size) in (0..20).zip(1..) {
+        let token_lines = read_tokens(&generate(10, seed, size).unwrap()).unwrap();
+        let incomplete = token_lines.iter().filter(|line| matches!(syntax_check(line), Syntactical::Incomplete(_))).count();
+        let corrupted = token_lines.iter().filter(|line| matches!(syntax_check(line), Syntactical::Incorrect(_))).count();
+
+        assert_eq!(incomplete % 2, 1, "seed {} and size {} have {} incomplete lines", seed, size, incomplete);
+        assert_eq!(incomplete + corrupted, size);
+    }
+}
+
+#[test]
+fn test_generated_positions_stay_in_range() {
+    let positions = generate(7, 0, 600_000).unwrap();
+    assert!(positions[0].split(',').all(|position| position.parse::<usize>().unwrap() <= day_7::MAX_POSITION));
+
+    let vent_lines = generate(5, 0, 5_000).unwrap();
+    assert!(day_5::parse_lines(&vent_lines, true).is_ok());
+}
@@ -0,0 +1,487 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use petgraph::graph::{DiGraph, NodeIndex};
+use crate::ParseError;
+
+/// A position in a grid, as a zero-based row and column
+pub type Coordinate = (usize, usize);
+
+/// A rectangular grid, stored row by row and indexed by `(row, column)`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// A grid of `width` columns and `height` rows, with every cell set to `fill`
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid { width, height, cells: vec![fill; width * height] }
+    }
+
+    /// The grid mirrored along its diagonal, turning rows into columns
+    pub fn transpose(&self) -> Grid<T> {
+        Grid::from_fn(self.height, self.width, |(row, column)| self[(column, row)].clone())
+    }
+}
+
+impl<T> Grid<T> {
+    /// A grid of `width` columns and `height` rows, with each cell computed from its coordinate
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Coordinate) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|row| (0..width).map(move |column| (row, column)))
+            .map(&mut f)
+            .collect();
+
+        Grid { width, height, cells }
+    }
+
+    /// A grid from its rows, which panics when they aren't all the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        let mut cells = Vec::with_capacity(width * height);
+
+        for (row_idx, row) in rows.into_iter().enumerate() {
+            assert_eq!(row.len(), width, "Row {} of the grid has {} cells instead of {}", row_idx, row.len(), width);
+            cells.extend(row);
+        }
+
+        Grid { width, height, cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether a (possibly out of bounds) coordinate lies within the grid
+    pub fn contains(&self, (row, column): Coordinate) -> bool {
+        row < self.height && column < self.width
+    }
+
+    /// The cell at a row and column, or `None` when that is outside of the grid
+    pub fn get(&self, row: usize, column: usize) -> Option<&T> {
+        match self.contains((row, column)) {
+            true => Some(&self.cells[row * self.width + column]),
+            false => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, row: usize, column: usize) -> Option<&mut T> {
+        match self.contains((row, column)) {
+            true => Some(&mut self.cells[row * self.width + column]),
+            false => None,
+        }
+    }
+
+    /// The cells of a single row, which panics when the row is outside of the grid
+    pub fn row(&self, row: usize) -> &[T] {
+        assert!(row < self.height, "Row {} is outside of a grid of {} rows", row, self.height);
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // Chunks of zero cells aren't allowed, but then there aren't any cells to chunk either
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// All coordinates of the grid, row by row
+    pub fn coordinates(&self) -> impl Iterator<Item = Coordinate> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |column| (row, column)))
+    }
+
+    /// All cells of the grid along with their coordinate, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinate, &T)> {
+        self.coordinates().zip(self.cells.iter())
+    }
+
+    /// All cells of the grid, row by row
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// A grid of the same size with `f` applied to each cell
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// The coordinates around a cell that lie within the grid, in reading order
+    pub fn neighbours(&self, coordinate: Coordinate, neighbourhood: Neighbourhood) -> Neighbours {
+        Neighbours::new(coordinate, self.width, self.height, neighbourhood, false)
+    }
+
+    /// The coordinates around a cell, wrapping around the edges as if the grid were a torus
+    pub fn wrapping_neighbours(&self, coordinate: Coordinate, neighbourhood: Neighbourhood) -> Neighbours {
+        Neighbours::new(coordinate, self.width, self.height, neighbourhood, true)
+    }
+
+    /// A view on the part of the grid of `height` rows and `width` columns starting at a row and
+    /// column, which panics when it doesn't fit within the grid
+    pub fn view(&self, row: usize, column: usize, height: usize, width: usize) -> GridView<'_, T> {
+        assert!(
+            row + height <= self.height && column + width <= self.width,
+            "A view of {}x{} at ({}, {}) doesn't fit in a grid of {}x{}",
+            height, width, row, column, self.height, self.width
+        );
+
+        GridView { grid: self, row, column, height, width }
+    }
+}
+
+impl<T> Index<Coordinate> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, column): Coordinate) -> &T {
+        self.get(row, column).unwrap_or_else(|| panic!(
+            "({}, {}) is outside of a grid of {}x{}", row, column, self.height, self.width
+        ))
+    }
+}
+
+impl<T> IndexMut<Coordinate> for Grid<T> {
+    fn index_mut(&mut self, (row, column): Coordinate) -> &mut T {
+        let (height, width) = (self.height, self.width);
+        self.get_mut(row, column).unwrap_or_else(|| panic!(
+            "({}, {}) is outside of a grid of {}x{}", row, column, height, width
+        ))
+    }
+}
+
+/// Writes the cells of each row next to each other, with a line per row
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.view(0, 0, self.height, self.width).fmt(f)
+    }
+}
+
+/// A rectangular part of a grid, indexed relative to its own top left corner
+#[derive(Clone, Copy, Debug)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    row: usize,
+    column: usize,
+    height: usize,
+    width: usize,
+}
+
+impl<'a, T> GridView<'a, T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cell at a row and column of the view, or `None` when that is outside of the view
+    pub fn get(&self, row: usize, column: usize) -> Option<&'a T> {
+        match row < self.height && column < self.width {
+            true => self.grid.get(self.row + row, self.column + column),
+            false => None,
+        }
+    }
+
+    /// All cells of the view along with their coordinate within the view, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinate, &'a T)> + '_ {
+        (0..self.height)
+            .flat_map(move |row| (0..self.width).map(move |column| (row, column)))
+            .map(move |(row, column)| ((row, column), &self.grid[(self.row + row, self.column + column)]))
+    }
+}
+
+impl<'a, T: Clone> GridView<'a, T> {
+    /// Copies the cells of the view into a grid of their own
+    pub fn to_grid(&self) -> Grid<T> {
+        Grid::from_fn(self.width, self.height, |(row, column)| self.grid[(self.row + row, self.column + column)].clone())
+    }
+}
+
+impl<'a, T: fmt::Display> fmt::Display for GridView<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..self.height {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            for column in 0..self.width {
+                write!(f, "{}", self.grid[(self.row + row, self.column + column)])?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Which cells around a cell count as its neighbours
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// The four cells sharing a side with the cell
+    VonNeumann,
+    /// The eight cells sharing a side or a corner with the cell
+    Moore,
+}
+
+impl Neighbourhood {
+    // Row and column offsets to the neighbours, in reading order
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Neighbourhood::VonNeumann => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighbourhood::Moore => &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)],
+        }
+    }
+}
+
+/// Iterates over the neighbours of a cell. It only holds the size of the grid, so the grid can
+/// be changed while iterating.
+///
+/// When wrapping around a grid less than three cells wide or high, the same neighbour can come
+/// up more than once and a cell can be its own neighbour.
+#[derive(Clone, Debug)]
+pub struct Neighbours {
+    center: Coordinate,
+    width: usize,
+    height: usize,
+    offsets: &'static [(isize, isize)],
+    wrap: bool,
+}
+
+impl Neighbours {
+    pub fn new(center: Coordinate, width: usize, height: usize, neighbourhood: Neighbourhood, wrap: bool) -> Self {
+        Neighbours { center, width, height, offsets: neighbourhood.offsets(), wrap }
+    }
+
+    // The coordinate at an offset along one axis, if it lies within the length of that axis
+    fn shift(&self, position: usize, offset: isize, length: usize) -> Option<usize> {
+        if self.wrap {
+            return match length {
+                0 => None,
+                _ => Some((position as isize + offset).rem_euclid(length as isize) as usize),
+            };
+        }
+
+        position.checked_add_signed(offset).filter(|shifted| *shifted < length)
+    }
+}
+
+impl Iterator for Neighbours {
+    type Item = Coordinate;
+
+    fn next(&mut self) -> Option<Coordinate> {
+        while let Some((&(row_offset, column_offset), rest)) = self.offsets.split_first() {
+            self.offsets = rest;
+
+            let row = self.shift(self.center.0, row_offset, self.height);
+            let column = self.shift(self.center.1, column_offset, self.width);
+            if let (Some(row), Some(column)) = (row, column) {
+                return Some((row, column));
+            }
+        }
+
+        None
+    }
+}
+
+/// A directed graph with a node for each cell of a grid, which finds the node of a coordinate
+/// without searching the graph for it
+#[derive(Clone, Debug)]
+pub struct GridGraph<N, E> {
+    graph: DiGraph<N, E>,
+    nodes: Grid<NodeIndex>,
+}
+
+impl<N, E> GridGraph<N, E> {
+    /// Builds the graph of a grid, weighing the node of each cell with `node_weight`. Each cell
+    /// gets an edge to each of its neighbours for which `edge_weight` returns a weight, given
+    /// the coordinates and cells at either end of the edge.
+    pub fn from_grid<T>(
+        grid: &Grid<T>,
+        neighbourhood: Neighbourhood,
+        mut node_weight: impl FnMut(Coordinate, &T) -> N,
+        mut edge_weight: impl FnMut((Coordinate, &T), (Coordinate, &T)) -> Option<E>,
+    ) -> Self {
+        let mut graph = DiGraph::with_capacity(grid.width() * grid.height(), 0);
+        let cells = grid.iter().map(|(coordinate, cell)| graph.add_node(node_weight(coordinate, cell))).collect();
+        let nodes = Grid { width: grid.width(), height: grid.height(), cells };
+
+        for (from, from_cell) in grid.iter() {
+            for to in grid.neighbours(from, neighbourhood) {
+                if let Some(weight) = edge_weight((from, from_cell), (to, &grid[to])) {
+                    graph.add_edge(nodes[from], nodes[to], weight);
+                }
+            }
+        }
+
+        GridGraph { graph, nodes }
+    }
+
+    pub fn graph(&self) -> &DiGraph<N, E> {
+        &self.graph
+    }
+
+    /// The node of the cell at a coordinate, or `None` when that is outside of the grid
+    pub fn node(&self, (row, column): Coordinate) -> Option<NodeIndex> {
+        self.nodes.get(row, column).copied()
+    }
+}
+
+/// Parses lines of single digits into a grid, which has to be rectangular and not empty
+pub fn parse_digit_grid(inputs: &[String]) -> Result<Grid<usize>, ParseError> {
+    if inputs.is_empty() {
+        return Err(ParseError::input("the input is empty, expected a grid of digits"));
+    }
+
+    let width = inputs[0].chars().count();
+    let mut rows = Vec::new();
+
+    for (line_idx, line) in inputs.iter().enumerate() {
+        let mut row = Vec::new();
+
+        for (column, c) in line.chars().enumerate() {
+            match c.to_digit(10) {
+                Some(digit) => row.push(digit as usize),
+                None => return Err(ParseError::at(line_idx, column, format!("expected a digit, found `{}`", c))),
+            }
+        }
+
+        if row.len() != width || width == 0 {
+            return Err(ParseError::at(
+                line_idx, row.len(), format!("expected a row of {} digits, found {}", width.max(1), row.len())
+            ));
+        }
+        rows.push(row);
+    }
+
+    Ok(Grid::from_rows(rows))
+}
+
+#[cfg(test)]
+#[test]
+fn test_get() {
+    let grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+    assert_eq!(grid.width(), 3);
+    assert_eq!(grid.height(), 2);
+
+    assert_eq!(grid.get(1, 0), Some(&4));
+    assert_eq!(grid.get(0, 3), None);
+    assert_eq!(grid.get(2, 0), None);
+    assert_eq!(grid[(0, 2)], 3);
+    assert_eq!(grid.row(1), &[4, 5, 6]);
+    assert_eq!(grid.rows().count(), 2);
+}
+
+#[test]
+#[should_panic]
+fn test_index_out_of_bounds() {
+    let grid = Grid::new(3, 2, 0);
+    let _ = grid[(0, 3)];
+}
+
+#[test]
+#[should_panic]
+fn test_from_jagged_rows() {
+    Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5]]);
+}
+
+#[test]
+fn test_iter() {
+    let mut grid = Grid::from_fn(2, 2, |(row, column)| row * 10 + column);
+    assert_eq!(grid.coordinates().collect::<Vec<_>>(), vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+    assert_eq!(grid.iter().nth(2), Some(((1, 0), &10)));
+
+    for cell in grid.values_mut() {
+        *cell += 1;
+    }
+    assert_eq!(grid.values().sum::<usize>(), 26);
+    assert!(!grid.map(|cell| cell % 2 == 0)[(1, 0)]);
+}
+
+#[test]
+fn test_transpose() {
+    let grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+    let transposed = grid.transpose();
+
+    assert_eq!(transposed, Grid::from_rows(vec![vec![1, 4], vec![2, 5], vec![3, 6]]));
+    assert_eq!(transposed.transpose(), grid);
+}
+
+#[test]
+fn test_view() {
+    let grid = Grid::from_fn(4, 3, |(row, column)| row * 4 + column);
+    let view = grid.view(1, 1, 2, 3);
+
+    assert_eq!(view.width(), 3);
+    assert_eq!(view.height(), 2);
+    assert_eq!(view.get(0, 0), Some(&5));
+    assert_eq!(view.get(1, 2), Some(&11));
+    assert_eq!(view.get(2, 0), None);
+    assert_eq!(view.iter().count(), 6);
+    assert_eq!(view.to_grid(), Grid::from_rows(vec![vec![5, 6, 7], vec![9, 10, 11]]));
+}
+
+#[test]
+fn test_neighbours() {
+    let grid = Grid::new(3, 3, 0);
+
+    let around_corner = grid.neighbours((0, 0), Neighbourhood::VonNeumann).collect::<Vec<_>>();
+    assert_eq!(around_corner, vec![(0, 1), (1, 0)]);
+    assert_eq!(grid.neighbours((0, 0), Neighbourhood::Moore).count(), 3);
+    assert_eq!(grid.neighbours((1, 2), Neighbourhood::Moore).count(), 5);
+    assert_eq!(grid.neighbours((1, 1), Neighbourhood::Moore).count(), 8);
+
+    let wrapped = grid.wrapping_neighbours((0, 0), Neighbourhood::VonNeumann).collect::<Vec<_>>();
+    assert_eq!(wrapped, vec![(2, 0), (0, 2), (0, 1), (1, 0)]);
+    assert_eq!(grid.wrapping_neighbours((2, 2), Neighbourhood::Moore).nth(7), Some((0, 0)));
+}
+
+#[test]
+fn test_grid_graph() {
+    let grid = Grid::from_rows(vec![vec![1, 2], vec![3, 4]]);
+    // Only edges going uphill, weighing the height gained
+    let graph = GridGraph::from_grid(
+        &grid,
+        Neighbourhood::VonNeumann,
+        |coordinate, _| coordinate,
+        |(_, from), (_, to)| if to > from { Some(to - from) } else { None },
+    );
+
+    assert_eq!(graph.graph().node_count(), 4);
+    assert_eq!(graph.graph().edge_count(), 4);
+
+    let top_left = graph.node((0, 0)).unwrap();
+    let bottom_left = graph.node((1, 0)).unwrap();
+    assert_eq!(graph.graph()[bottom_left], (1, 0));
+    assert_eq!(graph.graph().edges_connecting(top_left, bottom_left).next().unwrap().weight(), &2);
+    assert!(!graph.graph().contains_edge(bottom_left, top_left));
+    assert_eq!(graph.node((2, 0)), None);
+}
+
+#[test]
+fn test_display() {
+    let grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+    assert_eq!(grid.to_string(), "123\n456");
+    assert_eq!(grid.view(0, 1, 2, 2).to_string(), "23\n56");
+    assert_eq!(Grid::<usize>::new(0, 0, 0).to_string(), "");
+}
+
+#[test]
+fn test_parse_digit_grid() {
+    let inputs = vec!["123".to_string(), "456".to_string()];
+    assert_eq!(parse_digit_grid(&inputs), Ok(Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]])));
+
+    let inputs = vec!["123".to_string(), "4a6".to_string()];
+    assert_eq!(parse_digit_grid(&inputs).unwrap_err().to_string(), "line 2, column 2: expected a digit, found `a`");
+
+    let inputs = vec!["123".to_string(), "".to_string()];
+    assert_eq!(parse_digit_grid(&inputs).unwrap_err().to_string(), "line 2, column 1: expected a row of 3 digits, found 0");
+
+    assert!(parse_digit_grid(&[]).is_err());
+}
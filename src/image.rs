@@ -0,0 +1,212 @@
+use std::io;
+use std::io::Write;
+use std::str::FromStr;
+use crate::{Coordinate, Grid};
+
+/// A colour as its red, green and blue components
+pub type Rgb = [u8; 3];
+
+/// Colours to paint the values of a grid with, running from the lowest value at the first colour
+/// to the highest at the last one, blending the colours for the values in between
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    colors: Vec<Rgb>,
+}
+
+impl Palette {
+    /// Palettes that can be picked by name instead of listing their colours
+    pub const NAMES: [&'static str; 3] = ["grey", "heat", "ocean"];
+
+    pub fn new(colors: Vec<Rgb>) -> Self {
+        assert!(!colors.is_empty(), "A palette needs at least one colour");
+        Palette { colors }
+    }
+
+    /// The colour for a value on the scale from `min` to `max`
+    pub fn color(&self, value: usize, min: usize, max: usize) -> Rgb {
+        let last = self.colors.len() - 1;
+        if max <= min || last == 0 {
+            return self.colors[0];
+        }
+
+        // Find the pair of colours the value falls between, and how far along it is from the first
+        let position = (value.clamp(min, max) - min) as f64 / (max - min) as f64 * last as f64;
+        let from = (position.floor() as usize).min(last - 1);
+        let along = position - from as f64;
+
+        let mut color = [0; 3];
+        for (channel, (start, end)) in color.iter_mut().zip(self.colors[from].iter().zip(self.colors[from + 1])) {
+            *channel = (*start as f64 + (end as f64 - *start as f64) * along).round() as u8;
+        }
+        color
+    }
+}
+
+impl FromStr for Palette {
+    type Err = String;
+
+    /// Parses either the name of a palette, or its colours as hex codes separated by commas,
+    /// like `000000,ff0000`
+    fn from_str(palette: &str) -> Result<Self, Self::Err> {
+        let hex_codes = match palette {
+            "grey" => "000000,ffffff",
+            "heat" => "000000,b00000,ffa000,ffffc0",
+            "ocean" => "001030,0060a0,40c0e0,f0ffff",
+            _ => palette,
+        };
+
+        let colors = hex_codes.split(',').map(parse_color).collect::<Result<Vec<_>, _>>()?;
+        Ok(Palette::new(colors))
+    }
+}
+
+/// Parses a colour as a hex code like `ff8000`, optionally starting with a `#`
+pub fn parse_color(hex_code: &str) -> Result<Rgb, String> {
+    let digits = hex_code.trim().trim_start_matches('#');
+    let invalid = || format!(
+        "`{}` is not a colour, expected a hex code like ff8000 or one of {}", hex_code, Palette::NAMES.join(", ")
+    );
+
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    let channel = |start: usize| u8::from_str_radix(&digits[start..start + 2], 16).map_err(|_| invalid());
+    Ok([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Paints each cell of the grid with the palette, scaled from the lowest to the highest value
+pub fn paint(grid: &Grid<usize>, palette: &Palette) -> Grid<Rgb> {
+    let min = grid.values().min().copied().unwrap_or(0);
+    let max = grid.values().max().copied().unwrap_or(0);
+
+    grid.map(|&value| palette.color(value, min, max))
+}
+
+/// Paints over the given cells in a single colour, to make them stand out
+pub fn highlight(image: &mut Grid<Rgb>, cells: &[Coordinate], color: Rgb) {
+    for &cell in cells {
+        image[cell] = color;
+    }
+}
+
+/// Blows up each cell to a square of `factor` by `factor` pixels
+pub fn scale(image: &Grid<Rgb>, factor: usize) -> Grid<Rgb> {
+    Grid::from_fn(image.width() * factor, image.height() * factor, |(row, col)| {
+        image[(row / factor, col / factor)]
+    })
+}
+
+/// The image file formats that can be written
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+}
+
+impl ImageFormat {
+    /// Picks the format from the extension of the file to write
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = path.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the image in the given format, with each cell of the grid as a pixel
+pub fn write_image(image: &Grid<Rgb>, format: ImageFormat, writer: impl Write) -> io::Result<()> {
+    match format {
+        ImageFormat::Ppm => write_ppm(image, writer),
+        ImageFormat::Png => write_png(image, writer),
+    }
+}
+
+fn pixel_bytes(image: &Grid<Rgb>) -> Vec<u8> {
+    image.values().flatten().copied().collect()
+}
+
+/// Writes the image as a binary PPM, which is about the simplest image format there is
+pub fn write_ppm(image: &Grid<Rgb>, mut writer: impl Write) -> io::Result<()> {
+    write!(writer, "P6\n{} {}\n255\n", image.width(), image.height())?;
+    writer.write_all(&pixel_bytes(image))
+}
+
+/// Writes the image as a compressed PNG, which most image viewers and browsers can show
+pub fn write_png(image: &Grid<Rgb>, writer: impl Write) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(writer, image.width() as u32, image.height() as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut png_writer| png_writer.write_image_data(&pixel_bytes(image)))
+        .map_err(io::Error::other)
+}
+
+#[cfg(test)]
+#[test]
+fn test_palette_color() {
+    let palette = "000000,ff0000,ffffff".parse::<Palette>().unwrap();
+
+    assert_eq!(palette.color(0, 0, 4), [0, 0, 0]);
+    assert_eq!(palette.color(1, 0, 4), [128, 0, 0]);
+    assert_eq!(palette.color(2, 0, 4), [255, 0, 0]);
+    assert_eq!(palette.color(3, 0, 4), [255, 128, 128]);
+    assert_eq!(palette.color(4, 0, 4), [255, 255, 255]);
+    assert_eq!(palette.color(9, 0, 4), [255, 255, 255]);
+
+    // Without a range of values, everything gets the first colour
+    assert_eq!(palette.color(5, 5, 5), [0, 0, 0]);
+}
+
+#[test]
+fn test_parse_palette() {
+    assert_eq!("grey".parse::<Palette>(), Ok(Palette::new(vec![[0, 0, 0], [255, 255, 255]])));
+    assert_eq!("#102030".parse::<Palette>(), Ok(Palette::new(vec![[16, 32, 48]])));
+    assert_eq!(
+        "000000,purple".parse::<Palette>(),
+        Err("`purple` is not a colour, expected a hex code like ff8000 or one of grey, heat, ocean".to_string())
+    );
+    assert!("0000".parse::<Palette>().is_err());
+    assert_eq!(parse_color("FF8000"), Ok([255, 128, 0]));
+    assert!(parse_color("+f+f+f").is_err());
+    assert!(parse_color("ff80é").is_err());
+}
+
+#[test]
+fn test_paint() {
+    let grid = Grid::from_rows(vec![vec![1, 2], vec![3, 3]]);
+    let mut image = paint(&grid, &"grey".parse().unwrap());
+    assert_eq!(image, Grid::from_rows(vec![
+        vec![[0, 0, 0], [128, 128, 128]],
+        vec![[255, 255, 255], [255, 255, 255]],
+    ]));
+
+    highlight(&mut image, &[(0, 1)], [255, 0, 0]);
+    assert_eq!(image[(0, 1)], [255, 0, 0]);
+
+    let scaled = scale(&image, 2);
+    assert_eq!((scaled.width(), scaled.height()), (4, 4));
+    assert_eq!(scaled[(1, 3)], [255, 0, 0]);
+    assert_eq!(scaled[(2, 0)], [255, 255, 255]);
+}
+
+#[test]
+fn test_write_image() {
+    let image = Grid::from_rows(vec![vec![[1, 2, 3], [4, 5, 6]]]);
+
+    let mut ppm = Vec::new();
+    write_image(&image, ImageFormat::Ppm, &mut ppm).unwrap();
+    assert_eq!(ppm, b"P6\n2 1\n255\n\x01\x02\x03\x04\x05\x06");
+
+    let mut png = Vec::new();
+    write_image(&image, ImageFormat::Png, &mut png).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+    assert_eq!(ImageFormat::from_path("out/day_9.PNG"), Some(ImageFormat::Png));
+    assert_eq!(ImageFormat::from_path("day_9.ppm"), Some(ImageFormat::Ppm));
+    assert_eq!(ImageFormat::from_path("day_9"), None);
+}
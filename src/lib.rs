@@ -1,58 +1,102 @@
 use std::fs::File;
 use std::io;
 use std::io::BufRead;
-use petgraph::{EdgeType, Graph};
-use petgraph::graph::{IndexType, NodeIndex};
-
-pub fn read_lines(file: &str) -> Vec<String> {
-    let input = File::open(file).unwrap();
-    let reader = io::BufReader::new(input);
-    let inputs: Vec<String> = reader.lines()
-        .filter_map(io::Result::ok)
-        .collect();
-    inputs
-}
 
-pub fn parse_vec_usize(inputs: &Vec<String>) -> Vec<usize> {
-    let vec = inputs[0]
-        .split(",")
-        .map(|f| f.parse::<usize>().unwrap())
-        .collect();
+mod error;
+mod grid;
+#[cfg(test)]
+mod property;
+mod solution;
+
+pub mod day_1;
+pub mod day_2;
+pub mod day_3;
+pub mod day_4;
+pub mod day_5;
+pub mod day_6;
+pub mod day_7;
+pub mod day_8;
+pub mod day_9;
+pub mod day_10;
+pub mod day_11;
+pub mod day_12;
+pub mod day_13;
+pub mod day_14;
+pub mod day_15;
+pub mod day_16;
+pub mod generate;
+pub mod image;
+pub mod registry;
 
-    vec
+pub use error::{fields, parse_number, split_pair, whitespace_fields, Field, ParseError};
+pub use grid::{parse_digit_grid, Coordinate, Grid, GridGraph, GridView, Neighbourhood, Neighbours};
+pub use solution::{Answer, Part, Solution, Solved, Solver, Value};
+
+/// Reads the lines of a puzzle input file, or of stdin when the file is `-`
+pub fn read_lines(file: &str) -> Result<Vec<String>, ParseError> {
+    let lines = if file == "-" {
+        lines_from_reader(io::stdin().lock())
+    } else {
+        let input = File::open(file)
+            .map_err(|e| ParseError::input(format!("can't be read: {}", e)))
+            .map_err(|e| e.in_file(file))?;
+        lines_from_reader(io::BufReader::new(input))
+    };
+
+    lines.map_err(|e| e.in_file(file))
+}
+
+pub fn lines_from_reader<R: BufRead>(reader: R) -> Result<Vec<String>, ParseError> {
+    reader.lines()
+        .enumerate()
+        .map(|(line_idx, line)| line.map_err(|e| ParseError::at(line_idx, 0, e.to_string())))
+        .collect()
 }
 
-pub fn find_node<N, E, D, I>(
-    graph: &Graph<N, E, D, I>,
-    needle: &N
-) -> Option<NodeIndex<I>>
-    where N: PartialEq, D: EdgeType, I: IndexType
-{
-    graph
-        .node_indices()
-        .find(|idx| graph[*idx] == *needle)
+/// Parses the comma separated numbers on the first line of the input
+pub fn parse_vec_usize(inputs: &[String]) -> Result<Vec<usize>, ParseError> {
+    let line = inputs
+        .first()
+        .ok_or_else(|| ParseError::input("the input is empty, expected comma separated numbers"))?;
+
+    fields(line, ",")
+        .map(|(column, field)| parse_number(field, 0, column))
+        .collect()
 }
 
 #[cfg(test)]
 #[test]
 fn test_vec_usize_from_input() {
-    let inputs = read_lines("data/day_6_sample.txt");
-    let school = parse_vec_usize(&inputs);
+    let inputs = read_lines("data/day_6_sample.txt").unwrap();
+    let school = parse_vec_usize(&inputs).unwrap();
     assert_eq!(school, vec![3, 4, 3, 1, 2]);
+
+    let inputs = vec!["3,4,,1".to_string()];
+    assert_eq!(parse_vec_usize(&inputs).unwrap_err().to_string(), "line 1, column 5: expected a number, found nothing");
+    assert!(parse_vec_usize(&[]).is_err());
 }
 
 #[test]
-fn test_find_node() {
-    let inputs = read_lines("data/day_9_sample.txt");
-    let dem = parse_dem(&inputs);
-    let dem_graph = graph_from_dem(&dem);
-
-    let global_minimum = DEMPoint{
-        row: 2,
-        column: 2,
-        risk: 6
-    };
+fn test_lines_from_reader() {
+    let piped = "3,4,3,1,2\n5,6\n".as_bytes();
+    let inputs = lines_from_reader(piped).unwrap();
+    assert_eq!(inputs, vec!["3,4,3,1,2".to_string(), "5,6".to_string()]);
+
+    let not_utf8: &[u8] = b"1\n\xff\n";
+    assert_eq!(lines_from_reader(not_utf8).unwrap_err().line(), Some(2));
+}
 
-    let found_node = find_node(&dem_graph, global_minimum).unwrap();
-    assert_eq!(found_node.index(), 0)
+#[test]
+fn test_read_lines_missing_file() {
+    let error = read_lines("data/day_0_input.txt").unwrap_err();
+    assert_eq!(error.file(), Some("data/day_0_input.txt"));
+    assert!(error.to_string().starts_with("data/day_0_input.txt: can't be read: "));
+}
+
+#[test]
+fn test_parse_part() {
+    assert_eq!("1".parse::<Part>(), Ok(Part::One));
+    assert_eq!("2".parse::<Part>(), Ok(Part::Two));
+    assert!("3".parse::<Part>().is_err());
+    assert_eq!(Part::Two.to_string(), "2");
 }
@@ -0,0 +1,51 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Writes the diagnostics of the solvers to stderr, keeping stdout clean for the answers
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{:<5} [{}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Which diagnostics to show: warnings by default, more of them for each `-v`, none with `-q`
+pub(crate) fn level_filter(verbose: u64, quiet: bool) -> LevelFilter {
+    if quiet {
+        return LevelFilter::Off;
+    }
+
+    match verbose {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+pub(crate) fn init(level: LevelFilter) {
+    // Only fails when a logger was set before, which then simply stays in place
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_level_filter() {
+    assert_eq!(level_filter(0, false), LevelFilter::Warn);
+    assert_eq!(level_filter(1, false), LevelFilter::Info);
+    assert_eq!(level_filter(2, false), LevelFilter::Debug);
+    assert_eq!(level_filter(5, false), LevelFilter::Trace);
+    assert_eq!(level_filter(0, true), LevelFilter::Off);
+}
@@ -1,54 +1,740 @@
-mod day_1;
-mod day_2;
-mod day_3;
-mod day_4;
-mod day_5;
-mod day_6;
-mod day_7;
-mod day_8;
-mod day_9;
-mod day_10;
-mod day_11;
-mod day_12;
-mod day_13;
-mod day_14;
-mod day_15;
-mod day_16;
-
-use clap::{App, Arg};
+mod animate;
+mod bench;
+mod cache;
+mod fetch;
+mod logger;
+mod output;
+mod render;
+mod repl;
+mod serve;
+mod submit;
+mod summary;
+mod verify;
+mod watch;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::{IsTerminal, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::Duration;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use advent_of_code_2021::{generate, image, read_lines, registry, ParseError, Part, Solver};
+use crate::output::Format;
 
 pub fn main() {
     let matches = App::new("Advent of Code 2021")
         .version("0.1.0")
         .author("Rein van 't Veer <rein@vantveer.me>")
         .about("My solutions to the Advent of Code 2021 - see https://adventofcode.com/2021")
-        .arg(Arg::with_name("day")
-            .long("day")
-            .short("d")
-            .required(true)
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .arg(day_arg().required_unless_one(&["list", "all"]))
+        .arg(part_arg().help("Which part of the day's puzzle to solve, both parts if left out"))
+        .arg(Arg::with_name("format")
+            .long("format")
+            .short("f")
+            .takes_value(true)
+            .possible_values(&["text", "json", "csv"])
+            .default_value("text")
+            .help("Write the answers as human readable text, or as JSON or CSV records"))
+        .arg(Arg::with_name("list")
+            .long("list")
+            .short("l")
+            .help("List the days of the advent that have a solution"))
+        .arg(Arg::with_name("all")
+            .long("all")
+            .short("a")
+            .conflicts_with_all(&["day", "part", "input"])
+            .help("Run all days in sequence and summarise their answers, same as --day all"))
+        .arg(Arg::with_name("jobs")
+            .long("jobs")
+            .short("j")
+            .takes_value(true)
+            .help("Number of days to solve at the same time when running all days, as many as there \
+                   are CPUs if left out"))
+        .arg(Arg::with_name("watch")
+            .long("watch")
+            .short("w")
+            .conflicts_with_all(&["list", "all"])
+            .help("Keep solving the input file, and the day's sample file if there is one, whenever they \
+                   change, printing the answers that changed"))
+        .arg(Arg::with_name("animate")
+            .long("animate")
+            .conflicts_with_all(&["list", "all", "watch"])
+            .help("Draw each step of the octopi on day 11, or each fold of the paper on day 13, in the \
+                   terminal before showing the answers"))
+        .arg(Arg::with_name("delay")
+            .long("delay")
+            .takes_value(true)
+            .value_name("MS")
+            .default_value("100")
+            .help("Milliseconds to show each frame of the animation for"))
+        .arg(Arg::with_name("verbose")
+            .long("verbose")
+            .short("v")
+            .global(true)
+            .multiple(true)
+            .help("Show the diagnostics of the solutions on stderr, more of them for each -v"))
+        .arg(Arg::with_name("quiet")
+            .long("quiet")
+            .short("q")
+            .global(true)
+            .conflicts_with("verbose")
+            .help("Don't show any diagnostics, not even warnings"))
+        .arg(Arg::with_name("input")
+            .long("input")
+            .short("i")
+            .global(true)
             .takes_value(true)
-            .help("Which day of the advent to run"))
+            .value_name("FILE")
+            .help("Puzzle input to solve, instead of the day's input file in the data directory. \
+                   Use - to read it from stdin"))
+        .arg(Arg::with_name("data-dir")
+            .long("data-dir")
+            .global(true)
+            .takes_value(true)
+            .value_name("DIR")
+            .default_value("data")
+            .help("Directory holding the day_N_input.txt puzzle inputs"))
+        .arg(Arg::with_name("no-cache")
+            .long("no-cache")
+            .global(true)
+            .help("Solve the puzzles even when their answers are cached, and don't cache the answers"))
+        .arg(Arg::with_name("cache-dir")
+            .long("cache-dir")
+            .global(true)
+            .takes_value(true)
+            .value_name("DIR")
+            .default_value(".cache")
+            .help("Directory to cache the answers in, under answers/ by day, part, version of the \
+                   solution and hash of the puzzle input"))
+        .subcommand(SubCommand::with_name("bench")
+            .about("Times parsing and solving a part of a day's puzzle over a number of runs")
+            .arg(day_arg().required(true))
+            .arg(part_arg().default_value("1"))
+            .arg(Arg::with_name("runs")
+                .long("runs")
+                .short("n")
+                .takes_value(true)
+                .default_value("10")
+                .help("How many times to parse and solve the puzzle")))
+        .subcommand(SubCommand::with_name("verify")
+            .about("Checks the answers of all days on their puzzle inputs against known-good answers")
+            .arg(Arg::with_name("answers")
+                .long("answers")
+                .short("a")
+                .takes_value(true)
+                .value_name("FILE")
+                .default_value("answers.toml")
+                .help("TOML file with the known-good answers, as part_1 and part_2 under [day_N]")))
+        .subcommand(SubCommand::with_name("generate")
+            .about("Writes a random puzzle input for a day to stdout, the same one for the same seed")
+            .arg(day_arg().required(true))
+            .arg(Arg::with_name("seed")
+                .long("seed")
+                .short("s")
+                .takes_value(true)
+                .default_value("2021")
+                .help("Seed for the random numbers"))
+            .arg(Arg::with_name("size")
+                .long("size")
+                .short("k")
+                .takes_value(true)
+                .default_value("100")
+                .help("Number of lines, numbers or cells along a side of the input, depending on the day")))
+        .subcommand(SubCommand::with_name("render")
+            .about("Draws the grid of day 5, 9, 11, 13 or 15 to a PPM or PNG image, with the cells that \
+                    stand out highlighted")
+            .arg(day_arg().required(true))
+            .arg(Arg::with_name("output")
+                .long("output")
+                .short("o")
+                .takes_value(true)
+                .value_name("FILE")
+                .required(true)
+                .help("Image file to write, as PPM or PNG depending on its extension"))
+            .arg(Arg::with_name("palette")
+                .long("palette")
+                .takes_value(true)
+                .default_value("heat")
+                .help("Colours from the lowest to the highest value, as grey, heat, ocean or a list of hex \
+                       codes like 000000,ff8000"))
+            .arg(Arg::with_name("highlight")
+                .long("highlight")
+                .takes_value(true)
+                .default_value("00c0ff")
+                .help("Colour of the highlighted cells, as a hex code"))
+            .arg(Arg::with_name("scale")
+                .long("scale")
+                .takes_value(true)
+                .default_value("4")
+                .help("Width and height in pixels of each cell of the grid"))
+            .arg(Arg::with_name("steps")
+                .long("steps")
+                .takes_value(true)
+                .help("Number of steps to take on day 11, or folds to make on day 13, before drawing. \
+                       Defaults to none for day 11 and all for day 13")))
+        .subcommand(SubCommand::with_name("cache")
+            .about("Manages the cached answers")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(SubCommand::with_name("clear")
+                .about("Removes all cached answers")))
+        .subcommand(SubCommand::with_name("serve")
+            .about("Answers POST /day/{n}/part/{p} HTTP requests carrying a puzzle input with the answer as JSON")
+            .arg(Arg::with_name("port")
+                .long("port")
+                .takes_value(true)
+                .default_value("2021")
+                .help("Port to listen on"))
+            .arg(Arg::with_name("host")
+                .long("host")
+                .takes_value(true)
+                .default_value("127.0.0.1")
+                .help("Address to listen on, 0.0.0.0 to accept requests from other machines")))
+        .subcommand(SubCommand::with_name("fetch")
+            .about("Downloads a day's puzzle input into the data directory, unless it's already there")
+            .arg(day_arg().required(true))
+            .arg(base_url_arg())
+            .arg(config_arg()))
+        .subcommand(SubCommand::with_name("submit")
+            .about("Solves a part of a day's puzzle and submits the answer to the website, unless the \
+                    submission history already tells it's wrong")
+            .arg(day_arg().required(true))
+            .arg(part_arg().required(true))
+            .arg(base_url_arg())
+            .arg(config_arg())
+            .arg(Arg::with_name("history")
+                .long("history")
+                .takes_value(true)
+                .value_name("FILE")
+                .default_value("submissions.toml")
+                .help("TOML file recording every submitted answer and the verdict on it")))
+        .subcommand(SubCommand::with_name("repl")
+            .about("Loads a day's puzzle input and steps through its state with commands like step, fold, \
+                    show, count and reset, for days 11, 13 and 14")
+            .arg(day_arg().required(true)))
         .get_matches();
 
+    // Global flags given after a subcommand only end up in the matches of that subcommand
+    let subcommand_matches = matches.subcommand().1;
+    let verbose = matches.occurrences_of("verbose")
+        .max(subcommand_matches.map_or(0, |m| m.occurrences_of("verbose")));
+    let quiet = matches.is_present("quiet") || subcommand_matches.is_some_and(|m| m.is_present("quiet"));
+    logger::init(logger::level_filter(verbose, quiet));
+    summary::install_panic_hook();
+
+    match matches.subcommand() {
+        ("bench", Some(bench_matches)) => run_bench(bench_matches),
+        ("cache", Some(cache_matches)) => run_cache(cache_matches),
+        ("verify", Some(verify_matches)) => run_verify(verify_matches),
+        ("fetch", Some(fetch_matches)) => run_fetch(fetch_matches),
+        ("generate", Some(generate_matches)) => run_generate(generate_matches),
+        ("render", Some(render_matches)) => run_render(render_matches),
+        ("repl", Some(repl_matches)) => run_repl(repl_matches),
+        ("serve", Some(serve_matches)) => run_serve(serve_matches),
+        ("submit", Some(submit_matches)) => run_submit(submit_matches),
+        _ => run_solve(&matches),
+    }
+}
+
+fn day_arg() -> Arg<'static, 'static> {
+    Arg::with_name("day")
+        .long("day")
+        .short("d")
+        .takes_value(true)
+        .help("Which day of the advent to run")
+}
+
+fn part_arg() -> Arg<'static, 'static> {
+    Arg::with_name("part")
+        .long("part")
+        .short("p")
+        .takes_value(true)
+        .possible_values(&["1", "2"])
+        .help("Which part of the day's puzzle to solve")
+}
+
+fn base_url_arg() -> Arg<'static, 'static> {
+    Arg::with_name("base-url")
+        .long("base-url")
+        .takes_value(true)
+        .value_name("URL")
+        .help("Address of the Advent of Code website, instead of the one in the config file or \
+               https://adventofcode.com")
+}
+
+fn config_arg() -> Arg<'static, 'static> {
+    Arg::with_name("config")
+        .long("config")
+        .takes_value(true)
+        .value_name("FILE")
+        .default_value("aoc.toml")
+        .help("TOML file with the session token of your Advent of Code login, as session, and \
+               optionally a base_url. The AOC_SESSION environment variable overrules the token in it")
+}
+
+fn run_solve(matches: &ArgMatches) {
+    if matches.is_present("list") {
+        for solver in registry::solvers() {
+            println!("Day {}", solver.day());
+        }
+        return;
+    }
+
+    let day = matches.value_of("day").unwrap_or("all");
+    if day == "all" {
+        run_all(matches);
+        return;
+    }
+
+    let solver = solver_or_exit(day);
+    let (path, inputs) = inputs_or_exit(matches, solver.day());
+
+    let parts = match matches.value_of("part") {
+        Some(part) => vec![part.parse::<Part>().unwrap()],
+        None => Part::ALL.to_vec(),
+    };
+
+    if matches.is_present("animate") {
+        animate_or_exit(matches, solver.day(), &path, &inputs);
+    }
+
+    let format = matches.value_of("format").unwrap().parse::<Format>().unwrap();
+    if matches.is_present("watch") {
+        if path == "-" {
+            eprintln!("Can't watch stdin for changes, give an input file to watch instead");
+            process::exit(1);
+        }
+
+        let sample_path = sample_path(matches.value_of("data-dir").unwrap(), solver.day());
+        let mut paths = vec![path];
+        if Path::new(&sample_path).exists() && !paths.contains(&sample_path) {
+            paths.push(sample_path);
+        }
+        watch::watch(solver.as_ref(), &paths, &parts, format);
+    }
+
+    if format == Format::Text {
+        println!("Running solutions for day {}", &day);
+    }
+
+    let solved = match cache(matches) {
+        Some(cache) => cache.solve(solver.as_ref(), &inputs, &parts),
+        None => solver.solve(&inputs, &parts),
+    };
+    let solved = solved.unwrap_or_else(|e| exit_malformed(e.in_file(&path)));
+    println!("{}", output::render(format, &solved));
+}
+
+fn run_all(matches: &ArgMatches) {
+    let data_dir = matches.value_of("data-dir").unwrap();
+    let format = matches.value_of("format").unwrap().parse::<Format>().unwrap();
+    let solvers = registry::solvers();
+
+    let jobs = match matches.value_of("jobs").map(|jobs| jobs.parse::<usize>()) {
+        Some(Ok(jobs)) if jobs > 0 => jobs,
+        Some(_) => {
+            eprintln!("The number of jobs has to be a positive number");
+            process::exit(1);
+        }
+        None => thread::available_parallelism().map_or(1, |cpus| cpus.get()),
+    };
+
+    let days = solvers
+        .iter()
+        .map(|solver| (solver.day(), input_path(None, data_dir, solver.day())))
+        .collect::<Vec<_>>();
+    let runs = summary::run_days(&days, jobs, cache(matches).as_ref());
+
+    if format == Format::Text {
+        let implemented = solvers.iter().map(|solver| solver.day()).collect::<Vec<_>>();
+        println!("{}", summary::table(&runs, &summary::unimplemented_days(&implemented)));
+    } else {
+        let solved = runs
+            .iter()
+            .flat_map(|(_, run)| match run {
+                summary::DayRun::Solved(solved) => solved.clone(),
+                _ => Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        println!("{}", output::render(format, &solved));
+    }
+
+    let failed = runs
+        .iter()
+        .any(|(_, run)| matches!(run, summary::DayRun::Panicked(_) | summary::DayRun::Malformed(_)));
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// Draws the frames of the animation of a day over one another, pausing in between
+fn animate_or_exit(matches: &ArgMatches, day: usize, path: &str, inputs: &[String]) {
+    if !animate::DAYS.contains(&day) {
+        eprintln!("There's no animation for day {}, only for days 11 and 13", day);
+        process::exit(1);
+    }
+    let delay = matches.value_of("delay").unwrap().parse::<u64>().unwrap_or_else(|_| {
+        eprintln!("The delay has to be a positive number of milliseconds");
+        process::exit(1);
+    });
+
+    let frames = animate::frames(day, inputs, animate::terminal_size())
+        .unwrap_or_else(|e| exit_malformed(e.in_file(path)));
+    for frame in frames {
+        println!("{}{}", animate::CLEAR, frame);
+        io::stdout().flush().unwrap();
+        thread::sleep(Duration::from_millis(delay));
+    }
+}
+
+fn run_cache(matches: &ArgMatches) {
+    if let ("clear", Some(clear_matches)) = matches.subcommand() {
+        let dir = clear_matches.value_of("cache-dir").unwrap();
+        match cache::Cache::new(dir).clear() {
+            Ok(removed) => println!("Removed {} cached answer(s) from {}", removed, dir),
+            Err(e) => {
+                eprintln!("Can't clear the cache in {}: {}", dir, e);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// The cache of answers, unless it's been turned off
+fn cache(matches: &ArgMatches) -> Option<cache::Cache> {
+    match matches.is_present("no-cache") {
+        true => None,
+        false => Some(cache::Cache::new(matches.value_of("cache-dir").unwrap())),
+    }
+}
+
+fn run_bench(matches: &ArgMatches) {
+    let solver = solver_or_exit(matches.value_of("day").unwrap());
+    let (path, inputs) = inputs_or_exit(matches, solver.day());
+    let part = matches.value_of("part").unwrap().parse::<Part>().unwrap();
+
+    let runs = match matches.value_of("runs").unwrap().parse::<usize>() {
+        Ok(runs) if runs > 0 => runs,
+        _ => {
+            eprintln!("The number of runs has to be a positive number");
+            process::exit(1);
+        }
+    };
+
+    let (parse, solve) = bench::bench(solver.as_ref(), &inputs, part, runs)
+        .unwrap_or_else(|e| exit_malformed(e.in_file(&path)));
+    println!("{}", bench::report(solver.day(), part, runs, &parse, &solve));
+}
+
+fn run_verify(matches: &ArgMatches) {
+    let answers_path = matches.value_of("answers").unwrap();
+    let answers = match fs::read_to_string(answers_path).map(|text| verify::parse_answers(&text)) {
+        Ok(Ok(answers)) => answers,
+        Ok(Err(e)) => {
+            eprintln!("Can't parse the answers in {}: {}", answers_path, e);
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Can't read the answers from {}: {}", answers_path, e);
+            process::exit(1);
+        }
+    };
+
+    let data_dir = matches.value_of("data-dir").unwrap();
+    let mut failures = 0;
+
+    for solver in registry::solvers() {
+        let path = input_path(None, data_dir, solver.day());
+        if !Path::new(&path).exists() {
+            println!("Day {}: no puzzle input at {}, skipped", solver.day(), path);
+            continue;
+        }
+
+        let solved = match read_lines(&path).and_then(|inputs| solver.solve(&inputs, &Part::ALL)) {
+            Ok(solved) => solved,
+            Err(e) => {
+                failures += 1;
+                println!("Day {}: malformed puzzle input, {}", solver.day(), e.in_file(&path));
+                continue;
+            }
+        };
+
+        for solved in solved {
+            let outcome = verify::check(&answers, &solved);
+            if let verify::Outcome::Wrong { .. } = outcome {
+                failures += 1;
+            }
+            println!("Day {} part {}: {}", solved.day, solved.part, outcome);
+        }
+    }
+
+    if failures > 0 {
+        eprintln!("{} check(s) against {} failed", failures, answers_path);
+        process::exit(1);
+    }
+}
+
+fn run_fetch(matches: &ArgMatches) {
+    let day = matches.value_of("day").unwrap();
+    let day = match day.parse::<usize>() {
+        Ok(day) if (1..=25).contains(&day) => day,
+        _ => {
+            eprintln!("There's no puzzle for day {}, expected a day from 1 to 25", day);
+            process::exit(1);
+        }
+    };
+
+    let path = input_path(None, matches.value_of("data-dir").unwrap(), day);
+    let (base_url, session) = website_or_exit(matches);
+    match fetch::fetch_input(&base_url, &session, day, &path) {
+        Ok(fetch::Fetched::Downloaded(length)) => println!("Downloaded the puzzle input for day {} to {}, {} bytes", day, path, length),
+        Ok(fetch::Fetched::AlreadyThere) => println!("The puzzle input for day {} is already at {}", day, path),
+        Err(e) => {
+            eprintln!("Can't download the puzzle input for day {}: {}", day, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_submit(matches: &ArgMatches) {
+    let solver = solver_or_exit(matches.value_of("day").unwrap());
+    let day = solver.day();
+    let part = matches.value_of("part").unwrap().parse::<Part>().unwrap();
+    let (path, inputs) = inputs_or_exit(matches, day);
+
+    let solved = match cache(matches) {
+        Some(cache) => cache.solve(solver.as_ref(), &inputs, &[part]),
+        None => solver.solve(&inputs, &[part]),
+    };
+    let answer = solved.unwrap_or_else(|e| exit_malformed(e.in_file(&path)))[0].answer.value.to_string();
+    if answer.contains('\n') {
+        eprintln!("Can't submit day {} part {}, read the answer off the drawing instead:\n{}", day, part, answer);
+        process::exit(1);
+    }
+
+    let history_path = matches.value_of("history").unwrap();
+    let mut history = submit::History::load(history_path).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    if let Some(reason) = history.reason_not_to_submit(day, part, &answer) {
+        eprintln!("Not submitting day {} part {}: {}", day, part, reason);
+        process::exit(1);
+    }
+
+    let (base_url, session) = website_or_exit(matches);
+    let verdict = submit::submit_answer(&base_url, &session, day, part, &answer).unwrap_or_else(|e| {
+        eprintln!("Can't submit day {} part {}: {}", day, part, e);
+        process::exit(1);
+    });
+    println!("Day {} part {}: {} is {}", day, part, answer, verdict);
+
+    let submission = submit::Submission { day, part, answer, verdict: verdict.clone() };
+    if let Err(e) = history.record(submission) {
+        eprintln!("Can't record the submission in {}: {}", history_path, e);
+        process::exit(1);
+    }
+    if verdict != submit::Verdict::Right {
+        process::exit(1);
+    }
+}
+
+/// The base URL of the Advent of Code website, and the session token to log in with
+fn website_or_exit(matches: &ArgMatches) -> (String, String) {
+    let config_path = matches.value_of("config").unwrap();
+    let config = fetch::load_config(config_path).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+
+    let session = fetch::session_token(env::var(fetch::SESSION_VAR).ok(), &config).unwrap_or_else(|| {
+        eprintln!(
+            "Can't log in to Advent of Code without a session token, set {} or the session in {}",
+            fetch::SESSION_VAR, config_path
+        );
+        process::exit(1);
+    });
+    let base_url = matches.value_of("base-url")
+        .map(|url| url.to_string())
+        .or(config.base_url)
+        .unwrap_or_else(|| fetch::DEFAULT_BASE_URL.to_string());
+
+    (base_url, session)
+}
+
+fn run_generate(matches: &ArgMatches) {
+    let day = matches.value_of("day").unwrap();
+    let (seed, size) = match (matches.value_of("seed").unwrap().parse::<u64>(), matches.value_of("size").unwrap().parse::<usize>()) {
+        (Ok(seed), Ok(size)) => (seed, size),
+        _ => {
+            eprintln!("The seed and size have to be positive numbers");
+            process::exit(1);
+        }
+    };
+
+    match day.parse::<usize>().ok().and_then(|day| generate::generate(day, seed, size)) {
+        Some(lines) => {
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+        None => {
+            eprintln!("There's no input generator for day {}", day);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_render(matches: &ArgMatches) {
+    let day = matches.value_of("day").unwrap();
+    let day = match day.parse::<usize>() {
+        Ok(day) if render::DAYS.contains(&day) => day,
+        _ => {
+            eprintln!("There's no grid to render for day {}, only for days 5, 9, 11, 13 and 15", day);
+            process::exit(1);
+        }
+    };
+
+    let output_path = matches.value_of("output").unwrap();
+    let format = image::ImageFormat::from_path(output_path).unwrap_or_else(|| {
+        eprintln!("Can't tell the image format of {}, expected a .ppm or .png file", output_path);
+        process::exit(1);
+    });
+    let palette = matches.value_of("palette").unwrap().parse::<image::Palette>();
+    let highlight = image::parse_color(matches.value_of("highlight").unwrap());
+    let (palette, highlight) = match (palette, highlight) {
+        (Ok(palette), Ok(highlight)) => (palette, highlight),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    let scale = matches.value_of("scale").unwrap().parse::<usize>();
+    let steps = matches.value_of("steps").map(|steps| steps.parse::<usize>()).transpose();
+    let (scale, steps) = match (scale, steps) {
+        (Ok(scale), Ok(steps)) if scale > 0 => (scale, steps),
+        _ => {
+            eprintln!("The scale and steps have to be positive numbers");
+            process::exit(1);
+        }
+    };
+
+    let (path, inputs) = inputs_or_exit(matches, day);
+    let state = render::grid_state(day, &inputs, steps).unwrap_or_else(|e| exit_malformed(e.in_file(&path)));
+
+    let mut picture = image::paint(&state.grid, &palette);
+    image::highlight(&mut picture, &state.highlights, highlight);
+    let picture = image::scale(&picture, scale);
+
+    let written = fs::File::create(output_path)
+        .and_then(|file| image::write_image(&picture, format, io::BufWriter::new(file)));
+    if let Err(e) = written {
+        eprintln!("Can't write the image to {}: {}", output_path, e);
+        process::exit(1);
+    }
+    println!("Drew the {} by {} grid of day {} to {}", state.grid.width(), state.grid.height(), day, output_path);
+}
+
+fn run_serve(matches: &ArgMatches) {
+    let host = matches.value_of("host").unwrap();
+    let port = matches.value_of("port").unwrap().parse::<u16>().unwrap_or_else(|_| {
+        eprintln!("The port has to be a number up to 65535");
+        process::exit(1);
+    });
+
+    let listener = TcpListener::bind((host, port)).unwrap_or_else(|e| {
+        eprintln!("Can't listen on {}:{}: {}", host, port, e);
+        process::exit(1);
+    });
+    println!("Listening on http://{}:{}, POST a puzzle input to /day/{{n}}/part/{{p}} to solve it", host, port);
+
+    serve::serve(listener);
+}
+
+fn run_repl(matches: &ArgMatches) {
     let day = matches.value_of("day").unwrap();
-    println!("Running solutions for day {}", &day);
-    match day {
-        "1" => { day_1::run() },
-        "2" => { day_2::run() },
-        "3" => { day_3::run() },
-        "4" => { day_4::run() },
-        "5" => { day_5::run() },
-        "6" => { day_6::run() },
-        "7" => { day_7::run() },
-        "8" => { day_8::run() },
-        "9" => { day_9::run() },
-        "10" => { day_10::run() },
-        "11" => { day_11::run() },
-        "12" => { day_12::run() },
-        "13" => { day_13::run() },
-        "14" => { day_14::run() },
-        "15" => { day_15::run() },
-        "16" => { day_16::run() },
-        _ => { todo!("This day isn't implemented (yet)") },
+    let day = match day.parse::<usize>() {
+        Ok(day) if repl::DAYS.contains(&day) => day,
+        _ => {
+            eprintln!("There's no REPL for day {}, only for days 11, 13 and 14", day);
+            process::exit(1);
+        }
     };
+
+    let (path, inputs) = inputs_or_exit(matches, day);
+    let mut session = repl::Session::load(day, &inputs).unwrap_or_else(|e| exit_malformed(e.in_file(&path)));
+    println!("Loaded day {} from {}, type help for the commands", day, path);
+
+    if let Err(e) = repl::run(&mut session, io::stdin().lock(), io::stdout()) {
+        eprintln!("Can't read the commands: {}", e);
+        process::exit(1);
+    }
+}
+
+fn solver_or_exit(day: &str) -> Box<dyn Solver> {
+    match day.parse::<usize>().ok().and_then(registry::find_solver) {
+        Some(solver) => solver,
+        None => {
+            eprintln!("Day {} isn't implemented (yet), see --list for the available days", day);
+            process::exit(1);
+        }
+    }
+}
+
+/// Reads the puzzle input for a day, from the file given on the command line or otherwise the
+/// day's input file in the data directory. Returns the path it was read from along with the lines.
+fn inputs_or_exit(matches: &ArgMatches, day: usize) -> (String, Vec<String>) {
+    let mut input_path = input_path(
+        matches.value_of("input"),
+        matches.value_of("data-dir").unwrap(),
+        day
+    );
+
+    // Without an input file of its own, solve whatever gets piped in
+    let has_input_file = Path::new(&input_path).exists();
+    if matches.value_of("input").is_none() && !has_input_file && !io::stdin().is_terminal() {
+        input_path = "-".to_string();
+    } else if input_path != "-" && !has_input_file {
+        eprintln!("No puzzle input for day {} at {}", day, input_path);
+        process::exit(1);
+    }
+
+    let inputs = read_lines(&input_path).unwrap_or_else(|e| exit_malformed(e));
+    (input_path, inputs)
+}
+
+fn exit_malformed(error: ParseError) -> ! {
+    eprintln!("Can't solve the puzzle input: {}", error);
+    process::exit(1);
+}
+
+/// The input file to solve: either given explicitly, or the day's input in the data directory
+fn input_path(input: Option<&str>, data_dir: &str, day: usize) -> String {
+    match input {
+        Some(path) => path.to_string(),
+        None => format!("{}/day_{}_input.txt", data_dir.trim_end_matches('/'), day),
+    }
+}
+
+/// The day's sample input in the data directory
+fn sample_path(data_dir: &str, day: usize) -> String {
+    format!("{}/day_{}_sample.txt", data_dir.trim_end_matches('/'), day)
+}
+
+#[cfg(test)]
+#[test]
+fn test_input_path() {
+    assert_eq!(input_path(None, "data", 9), "data/day_9_input.txt");
+    assert_eq!(input_path(None, "/tmp/inputs/", 12), "/tmp/inputs/day_12_input.txt");
+    assert_eq!(input_path(Some("data/day_9_sample.txt"), "data", 9), "data/day_9_sample.txt");
+}
+
+#[test]
+fn test_sample_path() {
+    assert_eq!(sample_path("data", 9), "data/day_9_sample.txt");
+    assert_eq!(sample_path("/tmp/inputs/", 12), "/tmp/inputs/day_12_sample.txt");
 }
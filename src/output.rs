@@ -0,0 +1,147 @@
+use std::str::FromStr;
+use advent_of_code_2021::{Solved, Value};
+#[cfg(test)]
+use std::time::Duration;
+#[cfg(test)]
+use advent_of_code_2021::{Answer, Part};
+
+/// How the answers get written to stdout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Output format {} is not one of text, json or csv", format)),
+        }
+    }
+}
+
+/// Renders the solved parts in the given format. Text is the human readable report of each
+/// answer; JSON and CSV hold a `day, part, answer, elapsed` record per solved part, with the
+/// elapsed time in seconds.
+pub(crate) fn render(format: Format, solved: &[Solved]) -> String {
+    match format {
+        Format::Text => solved
+            .iter()
+            .map(|s| s.answer.report.clone())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Format::Json => {
+            let records = solved.iter().map(json_record).collect::<Vec<_>>();
+            format!("[{}]", records.join(","))
+        }
+        Format::Csv => {
+            let mut lines = vec!["day,part,answer,elapsed".to_string()];
+
+            lines.extend(solved.iter().map(csv_record));
+            lines.join("\n")
+        }
+    }
+}
+
+/// A JSON object with the day, part, answer and elapsed seconds of a solved part
+pub(crate) fn json_record(solved: &Solved) -> String {
+    format!("{{{}}}", json_fields(solved))
+}
+
+/// The fields of `json_record`, without the braces around them, to add more fields to
+pub(crate) fn json_fields(solved: &Solved) -> String {
+    format!(
+        "\"day\":{},\"part\":{},\"answer\":{},\"elapsed\":{}",
+        solved.day, solved.part, json_value(&solved.answer.value), solved.elapsed.as_secs_f64()
+    )
+}
+
+/// A CSV line with the day, part, answer and elapsed seconds of a solved part
+pub(crate) fn csv_record(solved: &Solved) -> String {
+    format!(
+        "{},{},{},{}",
+        solved.day, solved.part, csv_field(&solved.answer.value.to_string()), solved.elapsed.as_secs_f64()
+    )
+}
+
+/// Numbers stay numbers in JSON, anything else becomes a string
+pub(crate) fn json_value(value: &Value) -> String {
+    match value {
+        Value::Number(number) => number.to_string(),
+        Value::Text(text) => json_string(text),
+    }
+}
+
+pub(crate) fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+/// Quotes a CSV field if it contains anything that would break up the record
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+fn sample_solved() -> Vec<Solved> {
+    vec![
+        Solved {
+            day: 1,
+            part: Part::One,
+            answer: Answer::new(7, "There are 7 increases in depth for part 1"),
+            elapsed: Duration::from_millis(1500),
+        },
+        Solved {
+            day: 13,
+            part: Part::Two,
+            answer: Answer::new("#.#\n\"#\"".to_string(), "After all the folds, the code is:"),
+            elapsed: Duration::from_millis(250),
+        },
+    ]
+}
+
+#[test]
+fn test_render_text() {
+    let rendered = render(Format::Text, &sample_solved());
+    assert_eq!(rendered, "There are 7 increases in depth for part 1\nAfter all the folds, the code is:");
+}
+
+#[test]
+fn test_render_json() {
+    let rendered = render(Format::Json, &sample_solved());
+    assert_eq!(
+        rendered,
+        "[{\"day\":1,\"part\":1,\"answer\":7,\"elapsed\":1.5},\
+         {\"day\":13,\"part\":2,\"answer\":\"#.#\\n\\\"#\\\"\",\"elapsed\":0.25}]"
+    );
+}
+
+#[test]
+fn test_render_csv() {
+    let rendered = render(Format::Csv, &sample_solved());
+    assert_eq!(rendered, "day,part,answer,elapsed\n1,1,7,1.5\n13,2,\"#.#\n\"\"#\"\"\",0.25");
+}
@@ -0,0 +1,17 @@
+use crate::generate::SplitMix64;
+
+/// Checks a property holds for a number of random cases, each drawing from its own seed. A
+/// failing case panics with its seed, so it can be replayed with `SplitMix64::new(seed)`.
+pub(crate) fn check_property(cases: u64, mut property: impl FnMut(&mut SplitMix64) -> Result<(), String>) {
+    for seed in 0..cases {
+        if let Err(message) = property(&mut SplitMix64::new(seed)) {
+            panic!("Property doesn't hold for seed {}: {}", seed, message);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Property doesn't hold for seed 0: odd")]
+fn test_check_property() {
+    check_property(10, |rng| if rng.next_u64() % 2 == 0 { Ok(()) } else { Err("odd".to_string()) });
+}
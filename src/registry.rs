@@ -0,0 +1,67 @@
+use crate::Solver;
+#[cfg(test)]
+use crate::{read_lines, Part, Value};
+use crate::{
+    day_1, day_2, day_3, day_4, day_5, day_6, day_7, day_8, day_9, day_10, day_11, day_12,
+    day_13, day_14, day_15, day_16,
+};
+
+/// All implemented days of the advent, in order
+pub fn solvers() -> Vec<Box<dyn Solver>> {
+    vec![
+        Box::new(day_1::Day1),
+        Box::new(day_2::Day2),
+        Box::new(day_3::Day3),
+        Box::new(day_4::Day4),
+        Box::new(day_5::Day5),
+        Box::new(day_6::Day6),
+        Box::new(day_7::Day7),
+        Box::new(day_8::Day8),
+        Box::new(day_9::Day9),
+        Box::new(day_10::Day10),
+        Box::new(day_11::Day11),
+        Box::new(day_12::Day12),
+        Box::new(day_13::Day13),
+        Box::new(day_14::Day14),
+        Box::new(day_15::Day15),
+        Box::new(day_16::Day16),
+    ]
+}
+
+/// Looks up the solver for a day, if that day is implemented
+pub fn find_solver(day: usize) -> Option<Box<dyn Solver>> {
+    solvers()
+        .into_iter()
+        .find(|solver| solver.day() == day)
+}
+
+#[cfg(test)]
+#[test]
+fn test_registry_is_ordered_by_day() {
+    let days = solvers()
+        .iter()
+        .map(|solver| solver.day())
+        .collect::<Vec<_>>();
+    assert_eq!(days, (1..=16).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_find_solver() {
+    assert_eq!(find_solver(7).unwrap().day(), 7);
+    assert!(find_solver(25).is_none());
+}
+
+#[test]
+fn test_solve_sample_through_registry() {
+    let inputs = read_lines("data/day_1_sample.txt").unwrap();
+    let solved = find_solver(1).unwrap().solve(&inputs, &Part::ALL).unwrap();
+
+    assert_eq!(solved[0].answer.value, Value::Number(7));
+    assert_eq!(solved[1].answer.value, Value::Number(5));
+    assert_eq!(solved[1].answer.report, "There are 5 increases in depth for part 2");
+
+    let solved = find_solver(1).unwrap().solve(&inputs, &[Part::Two]).unwrap();
+    assert_eq!(solved.len(), 1);
+    assert_eq!((solved[0].day, solved[0].part), (1, Part::Two));
+    assert_eq!(solved[0].answer.value, Value::Number(5));
+}
@@ -0,0 +1,113 @@
+use advent_of_code_2021::day_11::{flash_octopi, octopi_from_input, simple_energy_increase};
+use advent_of_code_2021::day_13::{fold_grid, parse_grid, Fold};
+use advent_of_code_2021::day_15::{cheapest_path, parse_graph};
+use advent_of_code_2021::day_5::{grid_sum_from_lines, parse_lines};
+use advent_of_code_2021::day_9::{basin_low_points, find_three_largest_basins, parse_dem};
+use advent_of_code_2021::{day_15, Coordinate, Grid, ParseError};
+#[cfg(test)]
+use advent_of_code_2021::read_lines;
+
+/// The days with a grid to render
+pub(crate) const DAYS: [usize; 5] = [5, 9, 11, 13, 15];
+
+/// The state of a day's grid to render, with the cells that stand out
+pub(crate) struct GridState {
+    pub grid: Grid<usize>,
+    pub highlights: Vec<Coordinate>,
+}
+
+/// Works out the grid of one of the days in `DAYS`, after `steps` steps for day 11 or folds for
+/// day 13. The highlights are:
+///
+/// - day 5: the hotspots where lines overlap
+/// - day 9: the three largest basins
+/// - day 11: the octopi that flashed in the last step
+/// - day 15: the cheapest route
+pub(crate) fn grid_state(day: usize, inputs: &[String], steps: Option<usize>) -> Result<GridState, ParseError> {
+    match day {
+        5 => {
+            let (hor_lines, ver_lines, dia_lines) = parse_lines(inputs, true)?;
+            let all_lines = [hor_lines, ver_lines, dia_lines].concat();
+            let grid = grid_sum_from_lines(&all_lines);
+            let highlights = grid.iter().filter(|(_, &count)| count >= 2).map(|(coord, _)| coord).collect();
+
+            Ok(GridState { grid, highlights })
+        }
+        9 => {
+            let dem = parse_dem(inputs)?;
+            let basins = find_three_largest_basins(&basin_low_points(&dem)?, &dem);
+            let highlights = basins.iter().flatten().map(|point| (point.row(), point.column())).collect();
+
+            Ok(GridState { grid: dem, highlights })
+        }
+        11 => {
+            let mut octopi = octopi_from_input(inputs)?;
+            for _ in 0..steps.unwrap_or(0) {
+                simple_energy_increase(&mut octopi);
+                flash_octopi(&mut octopi);
+            }
+
+            // Before the first step, none of them flashed even though some may be at 0
+            let highlights = match steps.unwrap_or(0) {
+                0 => Vec::new(),
+                _ => octopi.iter().filter(|(_, &energy)| energy == 0).map(|(coord, _)| coord).collect(),
+            };
+
+            Ok(GridState { grid: octopi, highlights })
+        }
+        13 => {
+            let (mut paper, folds) = parse_grid(inputs)?;
+            let folds = &folds[..steps.unwrap_or(folds.len()).min(folds.len())];
+
+            // Only show the part of the paper that is left after folding
+            let mut width = paper.width();
+            let mut height = paper.height();
+            for fold in folds {
+                fold_grid(&mut paper, fold);
+                match fold {
+                    Fold::Left(x) => width = width.min(*x),
+                    Fold::Up(y) => height = height.min(*y),
+                }
+            }
+            let grid = paper.view(0, 0, height, width).to_grid().map(|&dot| dot as usize);
+
+            Ok(GridState { grid, highlights: Vec::new() })
+        }
+        15 => {
+            let grid = day_15::parse_grid(inputs)?;
+            let (_, path) = cheapest_path(&parse_graph(&grid), &grid);
+
+            Ok(GridState { grid, highlights: path })
+        }
+        _ => panic!("There's no grid to render for day {}", day),
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_grid_state() {
+    let inputs = read_lines("data/day_5_sample.txt").unwrap();
+    let state = grid_state(5, &inputs, None).unwrap();
+    assert_eq!((state.grid.width(), state.grid.height()), (10, 10));
+    assert_eq!(state.highlights.len(), 12);
+
+    let inputs = read_lines("data/day_9_sample.txt").unwrap();
+    let state = grid_state(9, &inputs, None).unwrap();
+    assert_eq!(state.highlights.len(), 14 + 9 + 9);
+    let error = grid_state(9, &["999".to_string(), "909".to_string(), "999".to_string()], None).err().unwrap();
+    assert_eq!(error.to_string(), "expected at least 3 low points, for the three largest basins, found 1");
+
+    let inputs = read_lines("data/day_11_sample.txt").unwrap();
+    assert!(grid_state(11, &inputs, None).unwrap().highlights.is_empty());
+    assert_eq!(grid_state(11, &inputs, Some(2)).unwrap().highlights.len(), 35);
+
+    let inputs = read_lines("data/day_13_sample.txt").unwrap();
+    let state = grid_state(13, &inputs, None).unwrap();
+    assert_eq!(state.grid.to_string(), "11111\n10001\n10001\n10001\n11111\n00000\n00000");
+    let state = grid_state(13, &inputs, Some(1)).unwrap();
+    assert_eq!((state.grid.width(), state.grid.height()), (11, 7));
+
+    let inputs = read_lines("data/day_15_sample.txt").unwrap();
+    let state = grid_state(15, &inputs, None).unwrap();
+    assert_eq!(state.highlights.iter().skip(1).map(|&position| state.grid[position]).sum::<usize>(), 40);
+}
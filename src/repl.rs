@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, Write};
+use advent_of_code_2021::day_11::{flash_octopi, octopi_from_input, simple_energy_increase, OctopusGrid};
+use advent_of_code_2021::day_13::{count_dots, fold_grid, parse_grid, render_grid, Fold};
+use advent_of_code_2021::day_14::{elem_counts_from_pairs, even_faster_expand, even_faster_expand_iter, parse_inputs, rules_as_map};
+use advent_of_code_2021::{Grid, ParseError};
+#[cfg(test)]
+use advent_of_code_2021::read_lines;
+
+/// The days that have state to explore in the REPL
+pub(crate) const DAYS: [usize; 3] = [11, 13, 14];
+
+/// The state of a day's puzzle, along with the input to go back to on a reset
+pub(crate) enum Session {
+    Octopi {
+        initial: OctopusGrid,
+        octopi: OctopusGrid,
+        steps: usize,
+        flashes: usize,
+    },
+    Paper {
+        initial: Grid<bool>,
+        folds: Vec<Fold>,
+        paper: Grid<bool>,
+        folded: usize,
+    },
+    Polymer {
+        template: Vec<String>,
+        rules: HashMap<String, String>,
+        pair_counts: HashMap<String, usize>,
+        start_pair: String,
+        steps: usize,
+    },
+}
+
+impl Session {
+    /// Parses the input of one of the days in `DAYS` into its starting state
+    pub(crate) fn load(day: usize, inputs: &[String]) -> Result<Session, ParseError> {
+        match day {
+            11 => {
+                let octopi = octopi_from_input(inputs)?;
+                Ok(Session::Octopi { initial: octopi.clone(), octopi, steps: 0, flashes: 0 })
+            }
+            13 => {
+                let (paper, folds) = parse_grid(inputs)?;
+                Ok(Session::Paper { initial: paper.clone(), folds, paper, folded: 0 })
+            }
+            14 => {
+                let (template, rules) = parse_inputs(inputs)?;
+                let rules = rules_as_map(&rules);
+                let (pair_counts, start_pair) = even_faster_expand_iter(&template, &rules, 0);
+                Ok(Session::Polymer { template, rules, pair_counts, start_pair, steps: 0 })
+            }
+            _ => Err(ParseError::input(format!("there's no REPL for day {}, only for days 11, 13 and 14", day))),
+        }
+    }
+
+    fn day(&self) -> usize {
+        match self {
+            Session::Octopi { .. } => 11,
+            Session::Paper { .. } => 13,
+            Session::Polymer { .. } => 14,
+        }
+    }
+
+    fn help(&self) -> String {
+        let advance = match self {
+            Session::Paper { .. } => "fold [N]   apply the next N folds, 1 if left out",
+            _ => "step [N]   take N steps, 1 if left out",
+        };
+        let show = match self {
+            Session::Octopi { .. } => "show       print the energy levels of the octopi",
+            Session::Paper { .. } => "show       print the dots on the part of the paper that's left",
+            Session::Polymer { .. } => "show       print the counts of the pairs in the polymer",
+        };
+        let count = match self {
+            Session::Octopi { .. } => "count      print the number of flashes so far",
+            Session::Paper { .. } => "count      print the number of dots",
+            Session::Polymer { .. } => "count      print the counts of the elements in the polymer",
+        };
+
+        [advance, show, count, "reset      go back to the puzzle input", "quit       leave the REPL"].join("\n")
+    }
+
+    fn reset(&mut self) {
+        match self {
+            Session::Octopi { initial, octopi, steps, flashes } => {
+                *octopi = initial.clone();
+                *steps = 0;
+                *flashes = 0;
+            }
+            Session::Paper { initial, paper, folded, .. } => {
+                *paper = initial.clone();
+                *folded = 0;
+            }
+            Session::Polymer { template, rules, pair_counts, start_pair, steps } => {
+                let (initial_counts, initial_start) = even_faster_expand_iter(template, rules, 0);
+                *pair_counts = initial_counts;
+                *start_pair = initial_start;
+                *steps = 0;
+            }
+        }
+    }
+
+    /// Runs a single command, returning what to print for it. Commands that don't apply to the
+    /// day, or have a malformed argument, come back as an error message.
+    pub(crate) fn execute(&mut self, line: &str) -> Result<String, String> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let (command, argument) = match words.as_slice() {
+            [] => ("", None),
+            [command] => (*command, None),
+            [command, argument] => (*command, Some(*argument)),
+            [command, ..] => return Err(format!("`{}` takes at most one argument, see help", command)),
+        };
+        let times = match (command, argument) {
+            ("step" | "fold", Some(times)) => match times.parse::<usize>() {
+                Ok(times) if times > 0 => times,
+                _ => return Err(format!("Expected a positive number of times, found `{}`", times)),
+            },
+            ("help" | "reset" | "show" | "count", Some(_)) => {
+                return Err(format!("`{}` doesn't take an argument, see help", command));
+            }
+            _ => 1,
+        };
+
+        match (command, self) {
+            ("help", session) => Ok(session.help()),
+            ("reset", session) => {
+                session.reset();
+                Ok("Back to the puzzle input".to_string())
+            }
+            ("step", Session::Octopi { octopi, steps, flashes, .. }) => {
+                let mut last_flashes = 0;
+                for _ in 0..times {
+                    simple_energy_increase(octopi);
+                    last_flashes = flash_octopi(octopi);
+                    *flashes += last_flashes;
+                }
+                *steps += times;
+                Ok(format!("After step {}, {} octopi flashed in the last step", steps, last_flashes))
+            }
+            ("show", Session::Octopi { octopi, .. }) => Ok(octopi.to_string()),
+            ("count", Session::Octopi { steps, flashes, .. }) => {
+                Ok(format!("There have been {} flashes in {} steps", flashes, steps))
+            }
+            ("fold", Session::Paper { folds, paper, folded, .. }) => {
+                if times > folds.len() - *folded {
+                    return Err(format!(
+                        "There are only {} of the {} folds left, reset to start over", folds.len() - *folded, folds.len()
+                    ));
+                }
+                for fold in &folds[*folded..*folded + times] {
+                    fold_grid(paper, fold);
+                }
+                *folded += times;
+                Ok(format!(
+                    "Folded {} of {} times, last along {}: {} dots left",
+                    folded, folds.len(), describe_fold(&folds[*folded - 1]), count_dots(paper)
+                ))
+            }
+            ("show", Session::Paper { folds, paper, folded, .. }) => Ok(render_grid(paper, &folds[..*folded])),
+            ("count", Session::Paper { paper, .. }) => Ok(format!("There are {} dots", count_dots(paper))),
+            ("step", Session::Polymer { rules, pair_counts, start_pair, steps, .. }) => {
+                let fitting = steps_that_fit(pair_counts.values().sum(), times);
+                if fitting < times {
+                    return Err(format!(
+                        "Only {} more steps fit before the counts overflow, reset to start over", fitting
+                    ));
+                }
+                for _ in 0..times {
+                    even_faster_expand(pair_counts, rules, start_pair);
+                }
+                *steps += times;
+                let length = pair_counts.values().sum::<usize>() + 1;
+                Ok(format!("After step {}, the polymer is {} elements long", steps, length))
+            }
+            ("show", Session::Polymer { pair_counts, .. }) => {
+                let mut pairs = pair_counts.iter().filter(|(_, &count)| count > 0).collect::<Vec<_>>();
+                pairs.sort();
+                Ok(pairs.iter().map(|(pair, count)| format!("{}: {}", pair, count)).collect::<Vec<_>>().join("\n"))
+            }
+            ("count", Session::Polymer { pair_counts, start_pair, .. }) => {
+                let mut elems = elem_counts_from_pairs(pair_counts, start_pair).into_iter().collect::<Vec<_>>();
+                elems.sort();
+                Ok(elems.iter().map(|(elem, count)| format!("{}: {}", elem, count)).collect::<Vec<_>>().join("\n"))
+            }
+            (command @ ("step" | "fold"), session) => {
+                Err(format!("Day {} has no {} command, see help", session.day(), command))
+            }
+            (command, _) => Err(format!("Unknown command `{}`, see help", command)),
+        }
+    }
+}
+
+/// How many of the steps the polymer can take before counting its pairs overflows. Each step at
+/// most doubles the number of pairs, and expanding briefly needs room for half as many again.
+fn steps_that_fit(mut pairs: usize, steps: usize) -> usize {
+    // Without any pairs, there's nothing to insert elements between
+    if pairs == 0 {
+        return steps;
+    }
+
+    for step in 0..steps {
+        match pairs.checked_mul(3) {
+            Some(_) => pairs *= 2,
+            None => return step,
+        }
+    }
+
+    steps
+}
+
+fn describe_fold(fold: &Fold) -> String {
+    match fold {
+        Fold::Left(x) => format!("x={}", x),
+        Fold::Up(y) => format!("y={}", y),
+    }
+}
+
+/// Reads commands line by line until `quit` or the end of the input, writing a prompt before
+/// each command and the outcome after it
+pub(crate) fn run(session: &mut Session, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut lines = input.lines();
+
+    loop {
+        write!(output, "day {}> ", session.day())?;
+        output.flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+
+        match line.trim() {
+            "" => continue,
+            "quit" | "exit" => break,
+            command => match session.execute(command) {
+                Ok(text) | Err(text) => writeln!(output, "{}", text)?,
+            },
+        }
+    }
+
+    writeln!(output)
+}
+
+#[cfg(test)]
+#[test]
+fn test_octopi() {
+    let inputs = read_lines("data/day_11_sample.txt").unwrap();
+    let mut session = Session::load(11, &inputs).unwrap();
+
+    assert_eq!(session.execute("step 10"), Ok("After step 10, 29 octopi flashed in the last step".to_string()));
+    assert_eq!(session.execute("count"), Ok("There have been 204 flashes in 10 steps".to_string()));
+
+    session.execute("reset").unwrap();
+    session.execute("step").unwrap();
+    assert_eq!(session.execute("show").unwrap().lines().next(), Some("6594254334"));
+    assert_eq!(session.execute("count"), Ok("There have been 0 flashes in 1 steps".to_string()));
+}
+
+#[test]
+fn test_paper() {
+    let inputs = read_lines("data/day_13_sample.txt").unwrap();
+    let mut session = Session::load(13, &inputs).unwrap();
+
+    assert_eq!(session.execute("fold"), Ok("Folded 1 of 2 times, last along y=7: 17 dots left".to_string()));
+    assert_eq!(session.execute("fold 2"), Err("There are only 1 of the 2 folds left, reset to start over".to_string()));
+    assert_eq!(session.execute("fold"), Ok("Folded 2 of 2 times, last along x=5: 16 dots left".to_string()));
+    assert_eq!(session.execute("show"), Ok("#####\n#...#\n#...#\n#...#\n#####\n.....\n.....".to_string()));
+
+    session.execute("reset").unwrap();
+    assert_eq!(session.execute("count"), Ok("There are 18 dots".to_string()));
+    assert_eq!(session.execute("step"), Err("Day 13 has no step command, see help".to_string()));
+}
+
+#[test]
+fn test_polymer() {
+    let inputs = read_lines("data/day_14_sample.txt").unwrap();
+    let mut session = Session::load(14, &inputs).unwrap();
+
+    assert_eq!(session.execute("step"), Ok("After step 1, the polymer is 7 elements long".to_string()));
+    assert_eq!(session.execute("show"), Ok("BC: 1\nCH: 1\nCN: 1\nHB: 1\nNB: 1\nNC: 1".to_string()));
+    assert_eq!(session.execute("count"), Ok("B: 2\nC: 2\nH: 1\nN: 2".to_string()));
+
+    session.execute("step 9").unwrap();
+    assert_eq!(session.execute("count"), Ok("B: 1749\nC: 298\nH: 161\nN: 865".to_string()));
+    assert_eq!(session.execute("step ten"), Err("Expected a positive number of times, found `ten`".to_string()));
+
+    // The 3 pairs of the template double to just over 2^63 in 62 steps
+    assert_eq!(steps_that_fit(3, 100), 61);
+    assert_eq!(session.execute("step 100"), Err("Only 51 more steps fit before the counts overflow, reset to start over".to_string()));
+    assert_eq!(session.execute("count"), Ok("B: 1749\nC: 298\nH: 161\nN: 865".to_string()));
+    assert_eq!(steps_that_fit(0, usize::MAX), usize::MAX);
+
+    // A single element has no pairs to step
+    let mut session = Session::load(14, &["N".to_string(), "".to_string(), "NN -> C".to_string()]).unwrap();
+    assert_eq!(session.execute("step 3"), Ok("After step 3, the polymer is 1 elements long".to_string()));
+    assert_eq!(session.execute("count"), Ok("N: 1".to_string()));
+}
+
+#[test]
+fn test_arguments() {
+    let inputs = read_lines("data/day_13_sample.txt").unwrap();
+    let mut session = Session::load(13, &inputs).unwrap();
+
+    assert_eq!(session.execute("show 5"), Err("`show` doesn't take an argument, see help".to_string()));
+    assert_eq!(session.execute("fold 1 2"), Err("`fold` takes at most one argument, see help".to_string()));
+    assert_eq!(session.execute(&format!("fold {}", usize::MAX)), Err("There are only 2 of the 2 folds left, reset to start over".to_string()));
+    assert_eq!(session.execute("fold 0"), Err("Expected a positive number of times, found `0`".to_string()));
+    assert_eq!(session.execute("count"), Ok("There are 18 dots".to_string()));
+
+    let error = Session::load(12, &inputs).err().unwrap();
+    assert_eq!(error.to_string(), "there's no REPL for day 12, only for days 11, 13 and 14");
+}
+
+#[test]
+fn test_run() {
+    let inputs = read_lines("data/day_13_sample.txt").unwrap();
+    let mut session = Session::load(13, &inputs).unwrap();
+    let mut output = Vec::new();
+
+    run(&mut session, "count\n\nunfold\nquit\ncount\n".as_bytes(), &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "day 13> There are 18 dots\nday 13> day 13> Unknown command `unfold`, see help\nday 13> \n"
+    );
+}
@@ -0,0 +1,397 @@
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use log::{info, warn};
+use advent_of_code_2021::{lines_from_reader, registry, Part, Solver};
+use crate::output::{json_record, json_string};
+use crate::summary::catch_panic;
+#[cfg(test)]
+use std::fs;
+#[cfg(test)]
+use crate::summary::Panicking;
+
+/// Refuse puzzle inputs larger than this, the biggest real ones are about 20 kB
+const MAX_BODY_LENGTH: usize = 1 << 20;
+
+/// Refuse request lines and headers that take more than this together
+const MAX_HEAD_LENGTH: usize = 8 << 10;
+
+/// How long a client gets to send its whole request, or to take in the response, before giving
+/// up on it
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long solving a puzzle input can take before the client gets told the server is too busy
+const SOLVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many connections get answered at the same time, the others wait for a turn
+const WORKERS: usize = 8;
+
+/// How many puzzle inputs get solved at the same time, counting the ones that ran out of time
+/// but are still being solved in the background
+const MAX_SOLVING: usize = 2 * WORKERS;
+
+/// How many puzzle inputs are being solved right now
+static SOLVING: AtomicUsize = AtomicUsize::new(0);
+
+/// An HTTP request, with only what's needed to solve a puzzle
+#[derive(Debug, PartialEq)]
+pub(crate) struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+/// An HTTP response with a JSON body
+#[derive(Debug, PartialEq)]
+pub(crate) struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn error(status: u16, message: impl AsRef<str>) -> Self {
+        Response { status, body: format!("{{\"error\":{}}}", json_string(message.as_ref())) }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            411 => "Length Required",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            431 => "Request Header Fields Too Large",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Reads the request line, headers and body of a request. A request that can't be read comes
+/// back as the error response to send instead.
+pub(crate) fn read_request(mut reader: impl BufRead) -> Result<Request, Response> {
+    let bad_request = |e: io::Error| match e.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => Response::error(
+            408, format!("the request has to be sent within {:?}", TIMEOUT)
+        ),
+        _ => Response::error(400, "can't read the request"),
+    };
+
+    // A line is cut short when the head runs out, rather than read for as long as the client sends
+    let mut head = (&mut reader).take(MAX_HEAD_LENGTH as u64);
+    let mut read_head_line = |line: &mut String| match head.read_line(line) {
+        Ok(_) if !line.ends_with('\n') && head.limit() == 0 => Err(Response::error(
+            431, format!("the request line and headers can't be over {} bytes", MAX_HEAD_LENGTH)
+        )),
+        Ok(_) => Ok(()),
+        Err(e) => Err(bad_request(e)),
+    };
+
+    let mut request_line = String::new();
+    read_head_line(&mut request_line)?;
+    let mut words = request_line.split_whitespace();
+    let (method, path) = match (words.next(), words.next(), words.next()) {
+        (Some(method), Some(path), Some(version)) if version.starts_with("HTTP/") => (method, path),
+        _ => return Err(Response::error(400, "expected a request line like POST /day/1/part/1 HTTP/1.1")),
+    };
+
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        read_head_line(&mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let length = value.trim().parse::<usize>()
+                    .map_err(|_| Response::error(400, format!("`{}` is not a content length", value.trim())))?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let body_length = match (method, content_length) {
+        (_, Some(length)) if length > MAX_BODY_LENGTH => {
+            return Err(Response::error(413, format!("the puzzle input can't be over {} bytes", MAX_BODY_LENGTH)));
+        }
+        (_, Some(length)) => length,
+        ("POST", None) => return Err(Response::error(411, "expected a Content-Length header with the puzzle input")),
+        (_, None) => 0,
+    };
+    let mut body = vec![0; body_length];
+    reader.read_exact(&mut body).map_err(bad_request)?;
+
+    Ok(Request { method: method.to_string(), path: path.to_string(), body })
+}
+
+/// Solves the part of the day in the path, `/day/{n}/part/{p}`, on the puzzle input in the body
+pub(crate) fn handle(request: &Request) -> Response {
+    let segments = request.path.trim_matches('/').split('/').collect::<Vec<_>>();
+    let (day, part) = match segments.as_slice() {
+        ["day", day, "part", part] => (*day, *part),
+        _ => return Response::error(404, format!("{} is not a puzzle, expected /day/{{n}}/part/{{p}}", request.path)),
+    };
+
+    let day = match day.parse::<usize>().ok().filter(|&day| registry::find_solver(day).is_some()) {
+        Some(day) => day,
+        None => return Response::error(404, format!("day {} isn't implemented (yet)", day)),
+    };
+    let part = match part.parse::<Part>() {
+        Ok(part) => part,
+        Err(_) => return Response::error(404, format!("part {} doesn't exist, expected 1 or 2", part)),
+    };
+    if request.method != "POST" {
+        return Response::error(405, format!("POST the puzzle input to solve it, instead of using {}", request.method));
+    }
+
+    let inputs = match lines_from_reader(request.body.as_slice()) {
+        Ok(inputs) => inputs,
+        Err(e) => return Response::error(400, format!("can't read the puzzle input: {}", e)),
+    };
+
+    // Solvers can't be sent to another thread, so the thread finds its own
+    answer_in_time(SOLVE_TIMEOUT, move || {
+        let solver = registry::find_solver(day).expect("the day was checked to have a solver");
+        solve(solver.as_ref(), &inputs, part)
+    })
+}
+
+/// Answers on another thread, or with a 503 when that takes longer than the timeout. A thread
+/// can't be stopped, so one that runs out of time goes on in the background, and while there
+/// are `MAX_SOLVING` of those no more get started.
+fn answer_in_time(timeout: Duration, answer: impl FnOnce() -> Response + Send + 'static) -> Response {
+    if SOLVING.fetch_add(1, Ordering::SeqCst) >= MAX_SOLVING {
+        SOLVING.fetch_sub(1, Ordering::SeqCst);
+        return Response::error(503, "the server is still busy solving other puzzle inputs, try again later");
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let response = answer();
+        SOLVING.fetch_sub(1, Ordering::SeqCst);
+        // Nobody is waiting for the answer anymore when it took too long
+        let _ = sender.send(response);
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(response) => response,
+        Err(mpsc::RecvTimeoutError::Timeout) => Response::error(
+            503, format!("solving this puzzle input takes over {:?}", timeout)
+        ),
+        Err(mpsc::RecvTimeoutError::Disconnected) => Response::error(500, "the solution stopped without an answer"),
+    }
+}
+
+/// Solves a part on a puzzle input. One that parses but makes the solution panic still isn't one
+/// it can solve, so that's the client's input to fix as well.
+fn solve(solver: &dyn Solver, inputs: &[String], part: Part) -> Response {
+    match catch_panic(|| solver.solve(inputs, &[part])) {
+        Ok(Ok(solved)) => Response { status: 200, body: json_record(&solved[0]) },
+        Ok(Err(e)) => Response::error(400, format!("malformed puzzle input: {}", e)),
+        Err(message) => {
+            warn!("Day {} part {} panicked: {}", solver.day(), part, message);
+            Response::error(422, format!("the solution can't solve this puzzle input: {}", message))
+        }
+    }
+}
+
+pub(crate) fn write_response(mut writer: impl Write, response: &Response) -> io::Result<()> {
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status, response.reason(), response.body.len(), response.body
+    )?;
+    writer.flush()
+}
+
+/// Reads from a stream until a deadline, however the reads are spread out, rather than giving
+/// each read a timeout of its own
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "the deadline for reading has passed"));
+        }
+
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+fn handle_connection(stream: TcpStream) -> io::Result<()> {
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let reader = DeadlineReader { stream: &stream, deadline: Instant::now() + TIMEOUT };
+    let response = match read_request(BufReader::new(reader)) {
+        Ok(request) => {
+            let response = handle(&request);
+            info!("{} {} {}", request.method, request.path, response.status);
+            response
+        }
+        Err(response) => response,
+    };
+
+    write_response(&stream, &response)
+}
+
+/// Answers the requests on the listener with `WORKERS` threads, until the process gets killed.
+/// Connections that come in while they're all busy wait in line, and once the line is as long
+/// as there are workers, no more get accepted until one is done.
+pub(crate) fn serve(listener: TcpListener) {
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(WORKERS);
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    for _ in 0..WORKERS {
+        let receiver = Arc::clone(&receiver);
+        thread::spawn(move || loop {
+            // The lock is only held while waiting for a connection, not while answering it
+            let stream = match receiver.lock().unwrap().recv() {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            if let Err(e) = handle_connection(stream) {
+                warn!("Can't answer the request: {}", e);
+            }
+        });
+    }
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => sender.send(stream).expect("the workers only stop once the sender is gone"),
+            Err(e) => warn!("Can't accept a connection: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+fn post(path: &str, body: &str) -> Request {
+    Request { method: "POST".to_string(), path: path.to_string(), body: body.as_bytes().to_vec() }
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_request() {
+    let request = "POST /day/1/part/2 HTTP/1.1\r\nHost: localhost\r\ncontent-length: 6\r\n\r\n1\n2\n3\n";
+    assert_eq!(read_request(request.as_bytes()), Ok(post("/day/1/part/2", "1\n2\n3\n")));
+
+    let response = read_request("POST /day/1/part/2 HTTP/1.1\r\n\r\n".as_bytes()).unwrap_err();
+    assert_eq!(response.status, 411);
+
+    let response = read_request("POST /day/1/part/2 HTTP/1.1\r\nContent-Length: 9999999\r\n\r\n".as_bytes()).unwrap_err();
+    assert_eq!(response.status, 413);
+
+    let response = read_request("hello\r\n\r\n".as_bytes()).unwrap_err();
+    assert_eq!(response.status, 400);
+
+    let request = format!("POST /day/1/part/2 HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(MAX_HEAD_LENGTH));
+    assert_eq!(read_request(request.as_bytes()).unwrap_err().status, 431);
+    let request = format!("POST /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_HEAD_LENGTH));
+    assert_eq!(read_request(request.as_bytes()).unwrap_err().status, 431);
+}
+
+#[test]
+fn test_handle() {
+    let sample = fs::read_to_string("data/day_1_sample.txt").unwrap();
+
+    let response = handle(&post("/day/1/part/2", &sample));
+    assert_eq!(response.status, 200);
+    assert!(response.body.starts_with("{\"day\":1,\"part\":2,\"answer\":5,\"elapsed\":"));
+
+    let response = handle(&post("/day/1/part/1", "199\n200\ntwo hundred and eight\n"));
+    assert_eq!(response, Response::error(400, "malformed puzzle input: line 3, column 1: expected a number, found `two hundred and eight`"));
+
+    assert_eq!(handle(&post("/day/1/part/3", &sample)).status, 404);
+    assert_eq!(handle(&post("/day/25/part/1", &sample)).status, 404);
+    assert_eq!(handle(&post("/answers", &sample)).status, 404);
+    assert_eq!(handle(&Request { method: "GET".to_string(), ..post("/day/1/part/1", "") }).status, 405);
+
+    let response = solve(&Panicking, &[], Part::One);
+    assert_eq!(response, Response::error(422, "the solution can't solve this puzzle input: not yet implemented: day 17"));
+
+    // Paths back and forth between two big caves would never stop being enumerated
+    let response = handle(&post("/day/12/part/1", "start-A\nA-end\nA-B\n"));
+    assert_eq!(response.status, 400);
+}
+
+#[test]
+fn test_answer_in_time() {
+    let answer = || Response { status: 200, body: "{}".to_string() };
+    assert_eq!(answer_in_time(Duration::from_secs(5), answer), answer());
+
+    let response = answer_in_time(Duration::from_millis(50), move || {
+        thread::sleep(Duration::from_millis(500));
+        answer()
+    });
+    assert_eq!(response, Response::error(503, "solving this puzzle input takes over 50ms"));
+}
+
+#[test]
+fn test_read_deadline() {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let address = listener.local_addr().unwrap();
+
+    // A client that keeps sending, but too slowly to ever finish its request
+    thread::spawn(move || {
+        let mut stream = TcpStream::connect(address).unwrap();
+        for _ in 0..50 {
+            if stream.write_all(b"x").is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    });
+
+    let (stream, _) = listener.accept().unwrap();
+    let start = Instant::now();
+    let reader = DeadlineReader { stream: &stream, deadline: start + Duration::from_millis(200) };
+    let response = read_request(BufReader::new(reader)).unwrap_err();
+    assert_eq!(response.status, 408);
+    assert!(start.elapsed() < Duration::from_millis(900));
+}
+
+#[test]
+fn test_serve() {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || serve(listener));
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    let sample = "16,1,2,0,4,2,7,1,2,14";
+    write!(stream, "POST /day/7/part/1 HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", sample.len(), sample).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("\r\n\r\n{\"day\":7,\"part\":1,\"answer\":37,"));
+
+    // More clients than workers all get their answer, in turn
+    let clients = (0..WORKERS * 3)
+        .map(|_| {
+            thread::spawn(move || {
+                let mut stream = TcpStream::connect(address).unwrap();
+                write!(stream, "POST /day/7/part/2 HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", sample.len(), sample).unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+                response
+            })
+        })
+        .collect::<Vec<_>>();
+    for client in clients {
+        assert!(client.join().unwrap().contains("\"answer\":168,"));
+    }
+}
@@ -0,0 +1,187 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::hint;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use log::info;
+use crate::ParseError;
+
+/// The typed value of an answer to one part of a puzzle
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(i64),
+    Text(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// Panics on numbers over `i64::MAX`, so a solution that can get answers that big has to check
+/// them with `i64::try_from` first
+impl From<usize> for Value {
+    fn from(number: usize) -> Self {
+        Value::Number(i64::try_from(number).unwrap_or_else(|_| panic!("The answer {} doesn't fit in an i64", number)))
+    }
+}
+
+impl From<i64> for Value {
+    fn from(number: i64) -> Self {
+        Value::Number(number)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(number: i32) -> Self {
+        Value::Number(number as i64)
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Value::Text(text)
+    }
+}
+
+/// The answer to one part of a puzzle, with the human readable report explaining how we got there
+#[derive(Clone, Debug, PartialEq)]
+pub struct Answer {
+    pub value: Value,
+    pub report: String,
+}
+
+impl Answer {
+    pub fn new(value: impl Into<Value>, report: impl Into<String>) -> Self {
+        Answer { value: value.into(), report: report.into() }
+    }
+}
+
+/// One of the two parts of each day's puzzle
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(self) -> usize {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(part: &str) -> Result<Self, Self::Err> {
+        match part {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(format!("There is no part {}, only parts 1 and 2", part)),
+        }
+    }
+}
+
+/// The answer to one part of a day's puzzle, along with how long solving that part took
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solved {
+    pub day: usize,
+    pub part: Part,
+    pub answer: Answer,
+    /// Time spent solving the part, not counting the parsing shared by both parts
+    pub elapsed: Duration,
+}
+
+/// A solution to the puzzle of a single day of the advent
+pub trait Solution {
+    /// The day of the advent this is the solution for
+    const DAY: usize;
+
+    /// Bump this whenever a change to the solution could change its answers, so answers cached by
+    /// an older version don't get used anymore
+    const VERSION: u32 = 1;
+
+    /// What the puzzle input lines are parsed into, shared by both parts
+    type Parsed;
+
+    fn parse(&self, inputs: &[String]) -> Result<Self::Parsed, ParseError>;
+
+    /// Solves a part on the parsed input, or tells why that part has no answer for it. Checks
+    /// that only one part needs go here rather than in `parse`, so the other part still answers.
+    fn part_one(&self, parsed: &Self::Parsed) -> Result<Answer, ParseError>;
+    fn part_two(&self, parsed: &Self::Parsed) -> Result<Answer, ParseError>;
+
+    fn solve_part(&self, parsed: &Self::Parsed, part: Part) -> Result<Answer, ParseError> {
+        match part {
+            Part::One => self.part_one(parsed),
+            Part::Two => self.part_two(parsed),
+        }
+    }
+}
+
+/// Object safe counterpart of `Solution`, so the solutions of all days can live in one registry
+pub trait Solver {
+    fn day(&self) -> usize;
+
+    /// The version of the solution, see `Solution::VERSION`
+    fn version(&self) -> u32;
+
+    /// Parses the inputs once, then solves only the requested parts on them
+    fn solve(&self, inputs: &[String], parts: &[Part]) -> Result<Vec<Solved>, ParseError>;
+
+    /// Parses the inputs and solves one part on them, returning how long parsing and solving took
+    fn time_part(&self, inputs: &[String], part: Part) -> Result<(Duration, Duration), ParseError>;
+}
+
+impl<S: Solution> Solver for S {
+    fn day(&self) -> usize {
+        S::DAY
+    }
+
+    fn version(&self) -> u32 {
+        S::VERSION
+    }
+
+    fn solve(&self, inputs: &[String], parts: &[Part]) -> Result<Vec<Solved>, ParseError> {
+        let start = Instant::now();
+        let parsed = self.parse(inputs)?;
+        info!("Parsed {} lines of input for day {} in {:.2?}", inputs.len(), S::DAY, start.elapsed());
+
+        parts
+            .iter()
+            .map(|part| {
+                let start = Instant::now();
+                let answer = self.solve_part(&parsed, *part)?;
+                Ok(Solved { day: S::DAY, part: *part, answer, elapsed: start.elapsed() })
+            })
+            .collect()
+    }
+
+    fn time_part(&self, inputs: &[String], part: Part) -> Result<(Duration, Duration), ParseError> {
+        let start = Instant::now();
+        let parsed = self.parse(inputs)?;
+        let parse_time = start.elapsed();
+
+        // Keep the optimizer from skipping the work being timed, since the answer isn't used
+        let start = Instant::now();
+        hint::black_box(self.solve_part(hint::black_box(&parsed), part))?;
+        let solve_time = start.elapsed();
+
+        Ok((parse_time, solve_time))
+    }
+}
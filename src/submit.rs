@@ -0,0 +1,293 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use log::info;
+use advent_of_code_2021::Part;
+use crate::fetch::{describe_error, request, YEAR};
+#[cfg(test)]
+use crate::fetch::mock_server;
+
+/// What the website made of a submitted answer
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Verdict {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+    /// Submitted too soon after the last answer, with how long is left to wait if the website said
+    RateLimited(Option<String>),
+}
+
+impl Verdict {
+    fn name(&self) -> &'static str {
+        match self {
+            Verdict::Right => "right",
+            Verdict::Wrong => "wrong",
+            Verdict::TooHigh => "too high",
+            Verdict::TooLow => "too low",
+            Verdict::RateLimited(_) => "rate limited",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "right" => Some(Verdict::Right),
+            "wrong" => Some(Verdict::Wrong),
+            "too high" => Some(Verdict::TooHigh),
+            "too low" => Some(Verdict::TooLow),
+            "rate limited" => Some(Verdict::RateLimited(None)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::RateLimited(Some(wait)) => write!(f, "rate limited, {} left to wait", wait),
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+/// Makes out the verdict from the page the website answers a submission with
+pub(crate) fn parse_verdict(page: &str) -> Result<Verdict, String> {
+    if page.contains("That's the right answer") {
+        Ok(Verdict::Right)
+    } else if page.contains("That's not the right answer") {
+        match (page.contains("your answer is too high"), page.contains("your answer is too low")) {
+            (true, _) => Ok(Verdict::TooHigh),
+            (_, true) => Ok(Verdict::TooLow),
+            _ => Ok(Verdict::Wrong),
+        }
+    } else if page.contains("You gave an answer too recently") {
+        let wait = page
+            .split_once("You have ")
+            .and_then(|(_, rest)| rest.split_once(" left to wait"))
+            .map(|(wait, _)| wait.to_string());
+        Ok(Verdict::RateLimited(wait))
+    } else if page.contains("You don't seem to be solving the right level") {
+        Err("the website says this part is already solved, or not unlocked yet".to_string())
+    } else {
+        Err("can't make out whether the answer was right from the website's response".to_string())
+    }
+}
+
+pub(crate) fn answer_url(base_url: &str, day: usize) -> String {
+    format!("{}/{}/day/{}/answer", base_url.trim_end_matches('/'), YEAR, day)
+}
+
+/// Posts the answer to a part of a day's puzzle to the website, returning its verdict
+pub(crate) fn submit_answer(base_url: &str, session: &str, day: usize, part: Part, answer: &str) -> Result<Verdict, String> {
+    let url = answer_url(base_url, day);
+    info!("Submitting {} for day {} part {} to {}", answer, day, part, url);
+
+    let level = part.to_string();
+    let page = request("POST", &url, session)
+        .send_form(&[("level", &level), ("answer", answer)])
+        .map_err(describe_error)?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+
+    parse_verdict(&page)
+}
+
+/// An answer submitted to the website, and what it made of it
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Submission {
+    pub day: usize,
+    pub part: Part,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+/// Every answer submitted so far, kept in a TOML file as an array of `[[submission]]` tables
+pub(crate) struct History {
+    path: PathBuf,
+    submissions: Vec<Submission>,
+}
+
+impl History {
+    /// Reads the history from its file, which doesn't exist before the first submission
+    pub(crate) fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let submissions = match fs::read_to_string(&path) {
+            Ok(text) => parse_history(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        submissions
+            .map(|submissions| History { path: path.clone(), submissions })
+            .map_err(|e| format!("Can't read the submission history in {}: {}", path.display(), e))
+    }
+
+    /// Why the answer shouldn't be submitted, if the history already tells how it would go: when
+    /// it was submitted before, when the part already got answered right, or when a number at
+    /// least as high was too high or at least as low was too low
+    pub(crate) fn reason_not_to_submit(&self, day: usize, part: Part, answer: &str) -> Option<String> {
+        let earlier = self.submissions
+            .iter()
+            .filter(|submission| submission.day == day && submission.part == part)
+            .filter(|submission| !matches!(submission.verdict, Verdict::RateLimited(_)))
+            .collect::<Vec<_>>();
+
+        if let Some(submission) = earlier.iter().find(|submission| submission.answer == answer) {
+            return Some(format!("{} was submitted before, and was {}", answer, submission.verdict));
+        }
+        if let Some(submission) = earlier.iter().find(|submission| submission.verdict == Verdict::Right) {
+            return Some(format!("this part was already answered right with {}", submission.answer));
+        }
+
+        let number = answer.parse::<i64>().ok()?;
+        earlier.iter().find_map(|submission| {
+            let earlier_number = submission.answer.parse::<i64>().ok()?;
+            match submission.verdict {
+                Verdict::TooHigh if number >= earlier_number => {
+                    Some(format!("{} is too high, since {} already was", answer, earlier_number))
+                }
+                Verdict::TooLow if number <= earlier_number => {
+                    Some(format!("{} is too low, since {} already was", answer, earlier_number))
+                }
+                _ => None,
+            }
+        })
+    }
+
+    /// Adds a submission to the history and writes it to its file
+    pub(crate) fn record(&mut self, submission: Submission) -> io::Result<()> {
+        self.submissions.push(submission);
+
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, history(&self.submissions))
+    }
+}
+
+fn history(submissions: &[Submission]) -> String {
+    let tables = submissions
+        .iter()
+        .map(|submission| {
+            let mut table = toml::Table::new();
+            table.insert("day".to_string(), toml::Value::Integer(submission.day as i64));
+            table.insert("part".to_string(), toml::Value::Integer(submission.part.number() as i64));
+            table.insert("answer".to_string(), toml::Value::String(submission.answer.clone()));
+            table.insert("verdict".to_string(), toml::Value::String(submission.verdict.name().to_string()));
+            toml::Value::Table(table)
+        })
+        .collect();
+
+    let mut history = toml::Table::new();
+    history.insert("submission".to_string(), toml::Value::Array(tables));
+    history.to_string()
+}
+
+fn parse_history(text: &str) -> Result<Vec<Submission>, String> {
+    let history = text.parse::<toml::Table>().map_err(|e| e.to_string())?;
+    let tables = match history.get("submission") {
+        Some(toml::Value::Array(tables)) => tables.as_slice(),
+        Some(_) => return Err("expected submission to be an array of tables".to_string()),
+        None => &[],
+    };
+
+    tables
+        .iter()
+        .enumerate()
+        .map(|(idx, table)| {
+            let field = |key| table.get(key).ok_or(format!("submission {} has no {}", idx + 1, key));
+            let malformed = |key| format!("submission {} has a malformed {}", idx + 1, key);
+
+            let day = field("day")?.as_integer().ok_or_else(|| malformed("day"))? as usize;
+            let part = field("part")?.as_integer().ok_or_else(|| malformed("part"))?
+                .to_string()
+                .parse::<Part>()?;
+            let answer = field("answer")?.as_str().ok_or_else(|| malformed("answer"))?.to_string();
+            let verdict = field("verdict")?.as_str().and_then(Verdict::from_name).ok_or_else(|| malformed("verdict"))?;
+
+            Ok(Submission { day, part, answer, verdict })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+fn submission(day: usize, part: Part, answer: &str, verdict: Verdict) -> Submission {
+    Submission { day, part, answer: answer.to_string(), verdict }
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_verdict() {
+    assert_eq!(parse_verdict("<article><p>That's the right answer!  You are one gold star closer"), Ok(Verdict::Right));
+    assert_eq!(parse_verdict("<p>That's not the right answer; your answer is too high.  If you're stuck"), Ok(Verdict::TooHigh));
+    assert_eq!(parse_verdict("<p>That's not the right answer; your answer is too low.  If you're stuck"), Ok(Verdict::TooLow));
+    assert_eq!(parse_verdict("<p>That's not the right answer.  If you're stuck"), Ok(Verdict::Wrong));
+    assert_eq!(
+        parse_verdict("<p>You gave an answer too recently; you have to wait after submitting an answer before trying \
+                       again.  You have 36s left to wait. <a href=\"/2021/day/1\">[Return to Day 1]</a></p>"),
+        Ok(Verdict::RateLimited(Some("36s".to_string())))
+    );
+    assert!(parse_verdict("<p>You don't seem to be solving the right level.  Did you already complete it?").is_err());
+    assert!(parse_verdict("<html>Puzzle inputs differ by user.</html>").is_err());
+}
+
+#[test]
+fn test_history() {
+    let submissions = vec![
+        submission(1, Part::One, "1700", Verdict::TooHigh),
+        submission(1, Part::One, "1500", Verdict::RateLimited(None)),
+        submission(1, Part::One, "1200", Verdict::TooLow),
+        submission(1, Part::Two, "1743", Verdict::Right),
+        submission(8, Part::One, "abc", Verdict::Wrong),
+    ];
+    assert_eq!(parse_history(&history(&submissions)), Ok(submissions.clone()));
+    assert_eq!(parse_history(""), Ok(Vec::new()));
+    assert_eq!(
+        parse_history("[[submission]]\nday = 1\npart = 1\nanswer = \"7\"\nverdict = \"close\""),
+        Err("submission 1 has a malformed verdict".to_string())
+    );
+
+    let history = History { path: PathBuf::new(), submissions };
+    let reason = |day, part, answer| history.reason_not_to_submit(day, part, answer);
+
+    assert_eq!(reason(1, Part::One, "1700"), Some("1700 was submitted before, and was too high".to_string()));
+    assert_eq!(reason(1, Part::One, "1800"), Some("1800 is too high, since 1700 already was".to_string()));
+    assert_eq!(reason(1, Part::One, "1100"), Some("1100 is too low, since 1200 already was".to_string()));
+    assert_eq!(reason(1, Part::Two, "1000"), Some("this part was already answered right with 1743".to_string()));
+    assert_eq!(reason(8, Part::One, "abc"), Some("abc was submitted before, and was wrong".to_string()));
+
+    // Answers that got rate limited never got a verdict, so they're worth another try
+    assert_eq!(reason(1, Part::One, "1500"), None);
+    assert_eq!(reason(8, Part::One, "abd"), None);
+    assert_eq!(reason(8, Part::Two, "abc"), None);
+}
+
+#[test]
+fn test_submit_answer() {
+    let (base_url, server) = mock_server(200, "<main><article><p>That's the right answer!  You are one gold star closer");
+    assert_eq!(submit_answer(&base_url, "abc123", 1, Part::Two, "1743"), Ok(Verdict::Right));
+
+    let request = server.join().unwrap();
+    assert!(request.starts_with("POST /2021/day/1/answer HTTP/1.1\r\n"));
+    assert!(request.contains("\r\nCookie: session=abc123\r\n"));
+    assert!(request.ends_with("\r\n\r\nlevel=2&answer=1743"));
+
+    let (base_url, _) = mock_server(200, "<main><article><p>That's not the right answer; your answer is too low.");
+    assert_eq!(submit_answer(&base_url, "abc123", 1, Part::One, "12"), Ok(Verdict::TooLow));
+}
+
+#[test]
+fn test_record() {
+    let path = std::env::temp_dir().join(format!("advent_of_code_2021_submissions_{}.toml", std::process::id()));
+
+    let mut history = History::load(&path).unwrap();
+    history.record(submission(3, Part::One, "198", Verdict::Wrong)).unwrap();
+    history.record(submission(3, Part::One, "3309596", Verdict::Right)).unwrap();
+
+    let history = History::load(&path).unwrap();
+    assert_eq!(history.submissions.len(), 2);
+    assert_eq!(history.submissions[1], submission(3, Part::One, "3309596", Verdict::Right));
+
+    fs::remove_file(&path).unwrap();
+}
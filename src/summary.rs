@@ -0,0 +1,308 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::panic;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, Once};
+use std::thread;
+use log::info;
+use advent_of_code_2021::{read_lines, registry, ParseError, Part, Solved, Solver};
+use crate::cache::Cache;
+#[cfg(test)]
+use std::time::Duration;
+#[cfg(test)]
+use advent_of_code_2021::Answer;
+
+/// The last day of the advent
+pub(crate) const LAST_DAY: usize = 25;
+
+/// What came of running a single day as part of a run of all days
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum DayRun {
+    Solved(Vec<Solved>),
+    Panicked(String),
+    NoInput(String),
+    Malformed(ParseError),
+}
+
+/// Solves the given parts of a day on the input file at `path`, catching a panic in its solution
+/// so the other days can still run
+pub(crate) fn run_day(solver: &dyn Solver, path: &str, parts: &[Part]) -> DayRun {
+    catch_day(solver, path, parts, None)
+}
+
+/// Solves both parts of each day on its input file, with up to `jobs` days at a time. The runs
+/// come back in the same order as the days, however long each of them took. Answers come from
+/// the cache when given one.
+pub(crate) fn run_days(days: &[(usize, String)], jobs: usize, cache: Option<&Cache>) -> Vec<(usize, DayRun)> {
+    let next_day = AtomicUsize::new(0);
+    let runs = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, days.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let idx = next_day.fetch_add(1, Ordering::SeqCst);
+                    let Some((day, path)) = days.get(idx) else { break };
+
+                    let run = match registry::find_solver(*day) {
+                        Some(solver) => catch_day(solver.as_ref(), path, &Part::ALL, cache),
+                        None => DayRun::Panicked(format!("day {} isn't implemented", day)),
+                    };
+                    runs.lock().unwrap().push((idx, *day, run));
+                }
+            });
+        }
+    });
+
+    let mut runs = runs.into_inner().unwrap();
+    runs.sort_by_key(|(idx, _, _)| *idx);
+    runs.into_iter().map(|(_, day, run)| (day, run)).collect()
+}
+
+fn catch_day(solver: &dyn Solver, path: &str, parts: &[Part], cache: Option<&Cache>) -> DayRun {
+    if !Path::new(path).exists() {
+        return DayRun::NoInput(path.to_string());
+    }
+
+    let inputs = match read_lines(path) {
+        Ok(inputs) => inputs,
+        Err(e) => return DayRun::Malformed(e),
+    };
+
+    let result = catch_panic(|| match cache {
+        Some(cache) => cache.solve(solver, &inputs, parts),
+        None => solver.solve(&inputs, parts),
+    });
+
+    match result {
+        Ok(Ok(solved)) => DayRun::Solved(solved),
+        Ok(Err(e)) => DayRun::Malformed(e.in_file(path)),
+        Err(message) => DayRun::Panicked(message),
+    }
+}
+
+thread_local! {
+    /// Whether a panic on this thread gets caught by `catch_panic`, which reports it its own way
+    static CATCHING_PANICS: Cell<bool> = const { Cell::new(false) };
+}
+
+static PANIC_HOOK: Once = Once::new();
+
+/// Installs the panic hook of the process, once. Panics that `catch_panic` catches only get
+/// logged, since the default hook would print them halfway through a table or a response. Any
+/// other panic goes to the default hook.
+pub(crate) fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| match CATCHING_PANICS.with(Cell::get) {
+            true => info!("Caught a panic: {}", info),
+            false => default_hook(info),
+        }));
+    });
+}
+
+/// Runs `f`, catching a panic in it as its message
+pub(crate) fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    install_panic_hook();
+
+    let was_catching = CATCHING_PANICS.with(|catching| catching.replace(true));
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(f));
+    CATCHING_PANICS.with(|catching| catching.set(was_catching));
+
+    result.map_err(|payload| panic_message(payload.as_ref()))
+}
+
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown cause".to_string()
+    }
+}
+
+/// The days of the advent without a solution among the given ones
+pub(crate) fn unimplemented_days(implemented: &[usize]) -> Vec<usize> {
+    (1..=LAST_DAY).filter(|day| !implemented.contains(day)).collect()
+}
+
+/// Renders a compact table with the answer and runtime of each part of each day. Answers
+/// spanning multiple lines continue below their row.
+pub(crate) fn table(runs: &[(usize, DayRun)], unimplemented: &[usize]) -> String {
+    let answer_width = runs
+        .iter()
+        .flat_map(|(_, run)| match run {
+            DayRun::Solved(solved) => solved.clone(),
+            _ => Vec::new(),
+        })
+        .flat_map(|s| s.answer.value.to_string().lines().map(|line| line.len()).collect::<Vec<_>>())
+        .chain(Some("Answer".len()))
+        .max()
+        .unwrap();
+
+    let mut lines = vec![format!("{:>3}  {:>4}  {:<width$}  {:>10}", "Day", "Part", "Answer", "Runtime", width = answer_width)];
+
+    for (day, run) in runs {
+        match run {
+            DayRun::Solved(solved) => {
+                for s in solved {
+                    let answer = s.answer.value.to_string();
+                    let mut answer_lines = answer.lines();
+                    lines.push(format!(
+                        "{:>3}  {:>4}  {:<width$}  {:>10}",
+                        day, s.part.number(), answer_lines.next().unwrap_or(""), format!("{:.2?}", s.elapsed),
+                        width = answer_width
+                    ));
+                    for line in answer_lines {
+                        lines.push(format!("{:>3}  {:>4}  {}", "", "", line));
+                    }
+                }
+            }
+            DayRun::Panicked(message) => lines.push(format!("{:>3}  {:>4}  panicked: {}", day, "-", message)),
+            DayRun::NoInput(path) => lines.push(format!("{:>3}  {:>4}  no puzzle input at {}", day, "-", path)),
+            DayRun::Malformed(e) => lines.push(format!("{:>3}  {:>4}  malformed input: {}", day, "-", e)),
+        }
+    }
+
+    let panicked = runs
+        .iter()
+        .filter(|(_, run)| matches!(run, DayRun::Panicked(_)))
+        .map(|(day, _)| day.to_string())
+        .collect::<Vec<_>>();
+    if !panicked.is_empty() {
+        lines.push(format!("Panicked: day {}", panicked.join(", ")));
+    }
+
+    if !unimplemented.is_empty() {
+        let days = unimplemented.iter().map(|day| day.to_string()).collect::<Vec<_>>();
+        lines.push(format!("Not implemented (yet): day {}", days.join(", ")));
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+pub(crate) struct Panicking;
+
+#[cfg(test)]
+impl Solver for Panicking {
+    fn day(&self) -> usize {
+        17
+    }
+
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn solve(&self, _inputs: &[String], _parts: &[Part]) -> Result<Vec<Solved>, ParseError> {
+        todo!("day 17")
+    }
+
+    fn time_part(&self, _inputs: &[String], _part: Part) -> Result<(Duration, Duration), ParseError> {
+        todo!("day 17")
+    }
+}
+
+#[test]
+fn test_run_day() {
+    let solver = advent_of_code_2021::registry::find_solver(1).unwrap();
+    match run_day(solver.as_ref(), "data/day_1_sample.txt", &Part::ALL) {
+        DayRun::Solved(solved) => assert_eq!(solved.len(), 2),
+        run => panic!("Expected day 1 to be solved, not {:?}", run),
+    }
+
+    assert_eq!(
+        run_day(&Panicking, "data/day_1_sample.txt", &Part::ALL),
+        DayRun::Panicked("not yet implemented: day 17".to_string())
+    );
+    assert_eq!(
+        run_day(solver.as_ref(), "data/day_0_input.txt", &Part::ALL),
+        DayRun::NoInput("data/day_0_input.txt".to_string())
+    );
+
+    // The second line of the day 4 sample is blank
+    match run_day(solver.as_ref(), "data/day_4_sample.txt", &Part::ALL) {
+        DayRun::Malformed(e) => assert_eq!(e.file(), Some("data/day_4_sample.txt")),
+        run => panic!("Expected the day 4 sample to be malformed for day 1, not {:?}", run),
+    }
+}
+
+#[test]
+fn test_run_days() {
+    let days = vec![
+        (4, "data/day_4_sample.txt".to_string()),
+        (1, "data/day_1_sample.txt".to_string()),
+        (9, "data/day_9_sample.txt".to_string()),
+        (2, "data/day_0_input.txt".to_string()),
+    ];
+
+    for jobs in [1, 3, 8] {
+        let runs = run_days(&days, jobs, None);
+        assert_eq!(runs.iter().map(|(day, _)| *day).collect::<Vec<_>>(), vec![4, 1, 9, 2]);
+        for (day, run) in &runs[..3] {
+            match run {
+                DayRun::Solved(solved) => assert_eq!(solved.len(), 2),
+                run => panic!("Expected day {} to be solved, not {:?}", day, run),
+            }
+        }
+        assert_eq!(runs[3].1, DayRun::NoInput("data/day_0_input.txt".to_string()));
+    }
+}
+
+#[test]
+fn test_catch_panic() {
+    assert_eq!(catch_panic(|| 1), Ok(1));
+    assert_eq!(catch_panic(|| panic!("oops")), Err::<(), _>("oops".to_string()));
+    assert_eq!(
+        catch_panic(|| catch_panic(|| panic!("inner")).unwrap_err() + " and caught"),
+        Ok("inner and caught".to_string())
+    );
+    assert!(!CATCHING_PANICS.with(Cell::get));
+
+    // Overlapping runs on other threads leave each other's panics caught
+    thread::scope(|scope| {
+        let runs = (0..4)
+            .map(|_| scope.spawn(|| run_day(&Panicking, "data/day_1_sample.txt", &Part::ALL)))
+            .collect::<Vec<_>>();
+        for run in runs {
+            assert_eq!(run.join().unwrap(), DayRun::Panicked("not yet implemented: day 17".to_string()));
+        }
+    });
+}
+
+#[test]
+fn test_unimplemented_days() {
+    let implemented = (1..=16).collect::<Vec<_>>();
+    assert_eq!(unimplemented_days(&implemented), (17..=25).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_table() {
+    let solved = |part, value: &str, millis| Solved {
+        day: 13,
+        part,
+        answer: Answer::new(value.to_string(), ""),
+        elapsed: Duration::from_millis(millis),
+    };
+    let runs = vec![
+        (13, DayRun::Solved(vec![solved(Part::One, "17", 2), solved(Part::Two, "#..\n.##", 30)])),
+        (14, DayRun::Panicked("oops".to_string())),
+        (16, DayRun::NoInput("data/day_16_input.txt".to_string())),
+        (1, DayRun::Malformed(ParseError::at(1, 0, "expected a number, found nothing").in_file("day_1.txt"))),
+    ];
+
+    let expected = [
+        "Day  Part  Answer     Runtime",
+        " 13     1  17          2.00ms",
+        " 13     2  #..        30.00ms",
+        "           .##",
+        " 14     -  panicked: oops",
+        " 16     -  no puzzle input at data/day_16_input.txt",
+        "  1     -  malformed input: day_1.txt:2:1: expected a number, found nothing",
+        "Panicked: day 14",
+        "Not implemented (yet): day 17, 18",
+    ];
+    assert_eq!(table(&runs, &[17, 18]), expected.join("\n"));
+}
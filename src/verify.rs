@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use advent_of_code_2021::{Part, Solved, Value};
+#[cfg(test)]
+use std::time::Duration;
+#[cfg(test)]
+use advent_of_code_2021::Answer;
+
+/// Known-good answers, by day and part
+pub(crate) type Answers = BTreeMap<(usize, Part), Value>;
+
+/// How a solved part compares to its known-good answer
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Outcome {
+    Correct,
+    Wrong { expected: Value, actual: Value },
+    Unknown,
+}
+
+/// Parses the answers file, which has a `[day_N]` table per day holding `part_1` and `part_2`
+/// as either integers or strings:
+///
+/// ```toml
+/// [day_1]
+/// part_1 = 1655
+/// part_2 = 1683
+/// ```
+pub(crate) fn parse_answers(text: &str) -> Result<Answers, String> {
+    let table = text.parse::<toml::Table>().map_err(|e| e.to_string())?;
+    let mut answers = Answers::new();
+
+    for (day_key, parts) in table.iter() {
+        let day = day_key
+            .strip_prefix("day_")
+            .and_then(|day| day.parse::<usize>().ok())
+            .ok_or(format!("{} is not a day, expected something like day_1", day_key))?;
+        let parts = parts.as_table().ok_or(format!("{} should be a table of parts", day_key))?;
+
+        for (part_key, value) in parts.iter() {
+            let part = part_key
+                .strip_prefix("part_")
+                .and_then(|part| part.parse::<Part>().ok())
+                .ok_or(format!("{}.{} is not a part, expected part_1 or part_2", day_key, part_key))?;
+            let value = match value {
+                toml::Value::Integer(number) => Value::Number(*number),
+                toml::Value::String(text) => Value::Text(text.clone()),
+                _ => return Err(format!("{}.{} should be an integer or a string", day_key, part_key)),
+            };
+            answers.insert((day, part), value);
+        }
+    }
+
+    Ok(answers)
+}
+
+pub(crate) fn check(answers: &Answers, solved: &Solved) -> Outcome {
+    match answers.get(&(solved.day, solved.part)) {
+        Some(expected) if *expected == solved.answer.value => Outcome::Correct,
+        Some(expected) => Outcome::Wrong {
+            expected: expected.clone(),
+            actual: solved.answer.value.clone(),
+        },
+        None => Outcome::Unknown,
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "ok"),
+            Outcome::Wrong { expected, actual } => {
+                write!(f, "MISMATCH, expected {} but got {}", expected, actual)
+            }
+            Outcome::Unknown => write!(f, "no known answer"),
+        }
+    }
+}
+
+#[cfg(test)]
+fn solved(day: usize, part: Part, value: impl Into<Value>) -> Solved {
+    Solved { day, part, answer: Answer::new(value, ""), elapsed: Duration::from_secs(0) }
+}
+
+#[test]
+fn test_parse_answers() {
+    let answers = parse_answers("[day_1]\npart_1 = 7\npart_2 = 5\n\n[day_13]\npart_2 = \"\"\"\n#.#\n.#.\"\"\"\n").unwrap();
+
+    assert_eq!(answers.len(), 3);
+    assert_eq!(answers[&(1, Part::One)], Value::Number(7));
+    assert_eq!(answers[&(1, Part::Two)], Value::Number(5));
+    assert_eq!(answers[&(13, Part::Two)], Value::Text("#.#\n.#.".to_string()));
+
+    assert!(parse_answers("[one]\npart_1 = 7").is_err());
+    assert!(parse_answers("[day_1]\npart_3 = 7").is_err());
+    assert!(parse_answers("[day_1]\npart_1 = 7.5").is_err());
+}
+
+#[test]
+fn test_check() {
+    let answers = parse_answers("[day_1]\npart_1 = 7").unwrap();
+
+    assert_eq!(check(&answers, &solved(1, Part::One, 7)), Outcome::Correct);
+    assert_eq!(
+        check(&answers, &solved(1, Part::One, 8)),
+        Outcome::Wrong { expected: Value::Number(7), actual: Value::Number(8) }
+    );
+    assert_eq!(check(&answers, &solved(1, Part::Two, 5)), Outcome::Unknown);
+}
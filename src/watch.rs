@@ -0,0 +1,193 @@
+use std::fs;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use log::info;
+use advent_of_code_2021::{Part, Solved, Solver};
+use crate::output::{csv_field, csv_record, json_fields, json_string, Format};
+use crate::summary::{run_day, DayRun};
+#[cfg(test)]
+use std::fs::File;
+#[cfg(test)]
+use advent_of_code_2021::{registry, Answer};
+
+/// How long to wait between checking the watched files for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// An input file being watched, along with the answers to its last version that could be solved
+pub(crate) struct WatchedFile {
+    pub path: String,
+    /// The modification time at the last poll, if there was a poll and the file was there
+    modified: Option<Option<SystemTime>>,
+    answers: Vec<Solved>,
+    /// Why the last version couldn't be solved, so the same reason doesn't get repeated
+    last_error: Option<String>,
+}
+
+impl WatchedFile {
+    pub(crate) fn new(path: &str) -> WatchedFile {
+        WatchedFile { path: path.to_string(), modified: None, answers: Vec::new(), last_error: None }
+    }
+
+    /// Solves the file again if it changed since the last poll, returning the answers that
+    /// changed in the given format, or why it couldn't be solved. Returns `None` when neither the
+    /// file nor the reason it can't be solved changed.
+    pub(crate) fn poll(&mut self, solver: &dyn Solver, parts: &[Part], format: Format) -> Option<Result<String, String>> {
+        let modified = fs::metadata(&self.path).and_then(|metadata| metadata.modified()).ok();
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+
+        let error = match run_day(solver, &self.path, parts) {
+            DayRun::Solved(solved) => {
+                let changes = render_changes(format, &self.path, &self.answers, &solved);
+                self.answers = solved;
+                self.last_error = None;
+                return Some(Ok(changes));
+            }
+            DayRun::Panicked(message) => format!("Day {} panicked on {}: {}", solver.day(), self.path, message),
+            DayRun::NoInput(path) => format!("No puzzle input for day {} at {}", solver.day(), path),
+            DayRun::Malformed(e) => format!("Can't solve the puzzle input: {}", e),
+        };
+
+        if self.last_error.as_ref() == Some(&error) {
+            return None;
+        }
+        self.last_error = Some(error.clone());
+        Some(Err(error))
+    }
+}
+
+/// The answers that differ from the previous ones in the given format, or nothing when none did.
+/// As text, they're listed under the path of the file as `part 1: 7 -> 5`; as JSON and CSV, each
+/// record starts with the path of the file.
+pub(crate) fn render_changes(format: Format, path: &str, previous: &[Solved], current: &[Solved]) -> String {
+    let changed = current
+        .iter()
+        .filter(|solved| {
+            !previous.iter().any(|before| before.part == solved.part && before.answer.value == solved.answer.value)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    match format {
+        _ if changed.is_empty() => String::new(),
+        Format::Text => format!("{}\n  {}", path, diff(previous, &changed).join("\n  ")),
+        Format::Json => {
+            let records = changed
+                .iter()
+                .map(|solved| format!("{{\"path\":{},{}}}", json_string(path), json_fields(solved)))
+                .collect::<Vec<_>>();
+            format!("[{}]", records.join(","))
+        }
+        Format::Csv => {
+            let mut lines = vec!["path,day,part,answer,elapsed".to_string()];
+            lines.extend(changed.iter().map(|solved| format!("{},{}", csv_field(path), csv_record(solved))));
+            lines.join("\n")
+        }
+    }
+}
+
+/// Describes the answers that differ from the previous ones, as `part 1: 7 -> 5`, or just the
+/// answer when there was none for the part before
+pub(crate) fn diff(previous: &[Solved], current: &[Solved]) -> Vec<String> {
+    current
+        .iter()
+        .filter_map(|solved| {
+            let before = previous.iter().find(|before| before.part == solved.part);
+            match before {
+                Some(before) if before.answer.value == solved.answer.value => None,
+                Some(before) => Some(format!("part {}: {} -> {}", solved.part, before.answer.value, solved.answer.value)),
+                None => Some(format!("part {}: {}", solved.part, solved.answer.value)),
+            }
+        })
+        .collect()
+}
+
+/// Solves the parts of a day on each of the files, then again on a file whenever it changes,
+/// printing only the answers that changed. Keeps watching until the process gets killed.
+pub(crate) fn watch(solver: &dyn Solver, paths: &[String], parts: &[Part], format: Format) -> ! {
+    let mut files = paths.iter().map(|path| WatchedFile::new(path)).collect::<Vec<_>>();
+    info!("Watching {} for changes", paths.join(" and "));
+
+    loop {
+        for file in files.iter_mut() {
+            match file.poll(solver, parts, format) {
+                Some(Ok(changes)) if changes.is_empty() => info!("{} changed, but its answers didn't", file.path),
+                Some(Ok(changes)) => println!("{}", changes),
+                Some(Err(message)) => eprintln!("{}", message),
+                None => {}
+            }
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+fn solved(part: Part, value: usize) -> Solved {
+    Solved { day: 1, part, answer: Answer::new(value, ""), elapsed: Duration::ZERO }
+}
+
+#[cfg(test)]
+#[test]
+fn test_diff() {
+    let previous = vec![solved(Part::One, 7), solved(Part::Two, 5)];
+
+    assert_eq!(diff(&[], &previous), vec!["part 1: 7", "part 2: 5"]);
+    assert_eq!(diff(&previous, &previous), Vec::<String>::new());
+    assert_eq!(diff(&previous, &[solved(Part::One, 7), solved(Part::Two, 6)]), vec!["part 2: 5 -> 6"]);
+    assert_eq!(diff(&previous, &[solved(Part::One, 8)]), vec!["part 1: 7 -> 8"]);
+}
+
+#[test]
+fn test_poll() {
+    let solver = registry::find_solver(1).unwrap();
+    let path = std::env::temp_dir().join(format!("advent_of_code_2021_watch_{}.txt", std::process::id()));
+    fs::write(&path, "1\n2\n3\n").unwrap();
+
+    let mut file = WatchedFile::new(path.to_str().unwrap());
+    let poll = |file: &mut WatchedFile| file.poll(solver.as_ref(), &[Part::One], Format::Text);
+    let changes = |changes: &str| Some(Ok(format!("{}\n  {}", path.display(), changes)));
+    assert_eq!(poll(&mut file), changes("part 1: 2"));
+    assert_eq!(poll(&mut file), None);
+
+    // Make sure the modification time changes, even on file systems that only keep seconds
+    fs::write(&path, "1\n2\n1\n").unwrap();
+    File::options().write(true).open(&path).unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(2)).unwrap();
+    assert_eq!(poll(&mut file), changes("part 1: 2 -> 1"));
+
+    fs::write(&path, "1\ntwo\n").unwrap();
+    File::options().write(true).open(&path).unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(4)).unwrap();
+    assert!(matches!(poll(&mut file), Some(Err(_))));
+
+    // A missing file gets reported once, rather than on every poll
+    fs::remove_file(&path).unwrap();
+    assert_eq!(poll(&mut file), Some(Err(format!("No puzzle input for day 1 at {}", path.display()))));
+    assert_eq!(poll(&mut file), None);
+
+    // The same goes for a file that's missing from the start
+    let mut missing = WatchedFile::new(path.to_str().unwrap());
+    assert!(matches!(poll(&mut missing), Some(Err(_))));
+    assert_eq!(poll(&mut missing), None);
+}
+
+#[test]
+fn test_render_changes() {
+    let previous = vec![solved(Part::One, 7), solved(Part::Two, 5)];
+    let current = vec![solved(Part::One, 7), solved(Part::Two, 6)];
+
+    assert_eq!(render_changes(Format::Text, "input.txt", &previous, &current), "input.txt\n  part 2: 5 -> 6");
+    assert_eq!(
+        render_changes(Format::Json, "input.txt", &previous, &current),
+        "[{\"path\":\"input.txt\",\"day\":1,\"part\":2,\"answer\":6,\"elapsed\":0}]"
+    );
+    assert_eq!(render_changes(Format::Csv, "input.txt", &previous, &current), "path,day,part,answer,elapsed\ninput.txt,1,2,6,0");
+    assert_eq!(
+        render_changes(Format::Csv, "data/day 1, sample.txt", &[], &current[..1]),
+        "path,day,part,answer,elapsed\n\"data/day 1, sample.txt\",1,1,7,0"
+    );
+    assert_eq!(render_changes(Format::Json, "input.txt", &current, &current), "");
+}